@@ -24,6 +24,7 @@ fn main() {
         link_shape_mode,
         link_shape_rep,
         None,
+        None,
         false,
     );
     let stop1 = start.elapsed();
@@ -37,9 +38,10 @@ fn main() {
         link_shape_mode,
         link_shape_rep,
         Some(
-            c.link_shapes_skips_nalgebra_module
+            c.link_shapes_skips_nalgebra_module()
                 .get_skips(link_shape_mode, link_shape_rep),
         ),
+        None,
         false,
     );
     let stop2 = start.elapsed();
@@ -1574,6 +1574,7 @@ impl BevyChainProximityVisualizerRaw {
             link_shape_mode_b.clone(),
             link_shape_rep_b.clone(),
             skips,
+            None,
             false,
             f64::INFINITY,
             &double_group_proximity_query_mode,
@@ -2351,12 +2352,13 @@ impl ChainMeshesRepresentation {
     ) -> Self {
         match link_shape_mode {
             LinkShapeMode::Full => match link_shape_rep {
-                LinkShapeRep::ConvexHull => Self::ConvexHull,
+                // sphere sets are drawn as the convex hulls they approximate.
+                LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => Self::ConvexHull,
                 LinkShapeRep::OBB => Self::OBBFull,
                 LinkShapeRep::BoundingSphere => Self::BoundingSphereFull,
             },
             LinkShapeMode::Decomposition => match link_shape_rep {
-                LinkShapeRep::ConvexHull => Self::ConvexDecomposition,
+                LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => Self::ConvexDecomposition,
                 LinkShapeRep::OBB => Self::OBBDecomposition,
                 LinkShapeRep::BoundingSphere => Self::BoundingSphereDecomposition,
             },
@@ -551,7 +551,7 @@ impl ApolloBevyTrait for App {
                     .link_shapes_distance_statistics_module
                     .get_stats(&link_shape_rep_a, &link_shape_mode_a);
                 let skips = a
-                    .link_shapes_skips_nalgebra_module
+                    .link_shapes_skips_nalgebra_module()
                     .get_skips(link_shape_mode_a, link_shape_rep_a);
                 SidePanel::left("proximity_visualizer").show(egui_contexts.ctx_mut(), |ui| {
                     ui.heading("Pairwise Distances");
@@ -777,7 +777,7 @@ impl ApolloChainBevyTrait for ChainNalgebra {
                     .link_shapes_distance_statistics_module
                     .get_stats(&link_shape_rep_a, &link_shape_mode_a);
                 let skips = a
-                    .link_shapes_skips_nalgebra_module
+                    .link_shapes_skips_nalgebra_module()
                     .get_skips(link_shape_mode_a, link_shape_rep_a);
                 SidePanel::left("proximity_visualizer").show(egui_contexts.ctx_mut(), |ui| {
                     ui.heading("Pairwise Distances");
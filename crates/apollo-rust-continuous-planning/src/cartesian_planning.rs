@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_linalg::{M, V};
+use apollo_rust_robotics_core::ChainNalgebra;
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+
+/// Plans joint-space paths that move a link of a robot along Cartesian paths.  The motion
+/// between consecutive waypoints is the geodesic given by `LieGroupISE3q::interpolate`, which is
+/// a straight line for pure translations, and it is tracked with damped least squares IK seeded
+/// with the previous state of the path.
+pub struct CartesianPathPlanner {
+    pub robot_chain: Arc<ChainNalgebra>,
+    pub link_idx: usize,
+    /// maximum translation of the link between consecutive interpolated poses
+    pub max_translation_step: f64,
+    /// maximum rotation angle (in radians) of the link between consecutive interpolated poses
+    pub max_rotation_step: f64,
+    /// maximum joint-space distance between consecutive states.  Larger steps are jumps, e.g.,
+    /// when IK switches to another solution branch near a singularity, and the link does not
+    /// follow the Cartesian path between the two states.
+    pub jump_threshold: f64,
+    /// IK converges once the position error is below this value
+    pub ik_position_tolerance: f64,
+    /// IK converges once the rotation error (in radians) is below this value
+    pub ik_rotation_tolerance: f64,
+    pub max_ik_iterations: usize,
+    /// non-negative damping factor of the damped least squares steps
+    pub ik_damping: f64
+}
+impl CartesianPathPlanner {
+    /// Uses a position tolerance of 1e-4, a rotation tolerance of 1e-3, and at most 100 IK
+    /// iterations per pose.  These can be changed through the fields.  A damping factor of 0.01
+    /// works well for most arms.
+    pub fn new(robot_chain: Arc<ChainNalgebra>, link_idx: usize, max_translation_step: f64, max_rotation_step: f64, jump_threshold: f64, ik_damping: f64) -> Self {
+        assert!(max_translation_step > 0.0 && max_rotation_step > 0.0 && jump_threshold > 0.0);
+        assert!(ik_damping >= 0.0);
+        assert!(link_idx < robot_chain.urdf_module().links.len());
+        Self { robot_chain, link_idx, max_translation_step, max_rotation_step, jump_threshold, ik_position_tolerance: 1e-4, ik_rotation_tolerance: 1e-3, max_ik_iterations: 100, ik_damping }
+    }
+
+    /// Plans a path that starts at `start_state` and moves the link from its pose at
+    /// `start_state` through `waypoints` in order.  Planning stops at the first interpolated pose
+    /// that cannot be reached by IK, that requires a jump, or whose motion from the previous
+    /// state is infeasible according to `feasibility_checker`.  The output holds the path up to
+    /// that point and the fraction of the interpolated poses that were reached.
+    pub fn plan<F: FeasibilityCheckerTrait>(&self, start_state: &V, waypoints: &Vec<ISE3q>, feasibility_checker: &F) -> CartesianPathOutput {
+        let start_pose = self.robot_chain.fk(start_state)[self.link_idx].clone();
+        let interpolated_poses = self.interpolate_waypoints(&start_pose, waypoints);
+
+        let mut path = vec![start_state.clone()];
+        let mut poses = vec![];
+        let mut failure = None;
+        if !feasibility_checker.is_feasible_state(start_state) { failure = Some(CartesianPathFailure::InfeasibleStart); }
+
+        if failure.is_none() {
+            for pose in interpolated_poses.iter() {
+                let previous = path.last().expect("error");
+                let state = match self.solve_ik(previous, pose) {
+                    None => { failure = Some(CartesianPathFailure::IKFailure); break; }
+                    Some(state) => { state }
+                };
+                if (&state - previous).norm() > self.jump_threshold { failure = Some(CartesianPathFailure::JointSpaceJump); break; }
+                if !feasibility_checker.is_feasible_motion(previous, &state) { failure = Some(CartesianPathFailure::InfeasibleMotion); break; }
+                path.push(state);
+                poses.push(pose.clone());
+            }
+        }
+
+        let fraction = if interpolated_poses.is_empty() {
+            if failure.is_none() { 1.0 } else { 0.0 }
+        } else {
+            poses.len() as f64 / interpolated_poses.len() as f64
+        };
+
+        CartesianPathOutput { path, poses, fraction, failure }
+    }
+
+    /// Interpolates the geodesics from `start_pose` through `waypoints` so that consecutive poses
+    /// are at most `max_translation_step` and `max_rotation_step` apart.  The output contains
+    /// every waypoint, but not `start_pose`.
+    pub fn interpolate_waypoints(&self, start_pose: &ISE3q, waypoints: &Vec<ISE3q>) -> Vec<ISE3q> {
+        let mut out = vec![];
+        let mut previous = start_pose.clone();
+        for waypoint in waypoints {
+            let translation = (waypoint.0.translation.vector - previous.0.translation.vector).norm();
+            let angle = previous.0.rotation.angle_to(&waypoint.0.rotation);
+            let num_steps = (translation / self.max_translation_step).ceil().max((angle / self.max_rotation_step).ceil()).max(1.0) as usize;
+            for i in 1..num_steps {
+                out.push(previous.interpolate(waypoint, i as f64 / num_steps as f64));
+            }
+            out.push(waypoint.clone());
+            previous = waypoint.clone();
+        }
+
+        out
+    }
+
+    /// Damped least squares IK for the pose of the link, starting from `seed` and staying within
+    /// the joint bounds.  Returns `None` if it does not converge within `max_ik_iterations`, or if
+    /// a step cannot be solved, i.e., at a singularity without damping.
+    pub fn solve_ik(&self, seed: &V, target: &ISE3q) -> Option<V> {
+        let bounds = &self.robot_chain.bounds_module().bounds;
+        let mut state = seed.clone();
+        for _ in 0..self.max_ik_iterations {
+            let pose = self.robot_chain.fk(&state)[self.link_idx].clone();
+            let error = pose_error(&pose, target);
+            if error.rows(0, 3).norm() < self.ik_position_tolerance && error.rows(3, 3).norm() < self.ik_rotation_tolerance { return Some(state); }
+
+            let jacobian = self.jacobian(&state);
+            let damped = &jacobian * jacobian.transpose() + M::identity(6, 6) * self.ik_damping.powi(2);
+            let step = jacobian.transpose() * damped.cholesky()?.solve(&error);
+            state += step;
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = x.clamp(bounds[i].0, bounds[i].1);
+            }
+        }
+
+        None
+    }
+
+    /// The 6 x n Jacobian of the link pose (linear velocity on top, angular velocity below, both
+    /// in the world frame) with respect to the state, by finite differences.
+    pub fn jacobian(&self, state: &V) -> M {
+        let h = 1e-6;
+        let pose = self.robot_chain.fk(state)[self.link_idx].clone();
+        let mut out = M::zeros(6, state.len());
+        for j in 0..state.len() {
+            let mut state_h = state.clone();
+            state_h[j] += h;
+            let pose_h = self.robot_chain.fk(&state_h)[self.link_idx].clone();
+            out.set_column(j, &(pose_error(&pose, &pose_h) / h));
+        }
+
+        out
+    }
+}
+
+/// The world-frame twist that moves `pose` to `target` in unit time, i.e., the translation
+/// followed by the scaled axis of the rotation from `pose` to `target`.
+pub fn pose_error(pose: &ISE3q, target: &ISE3q) -> V {
+    let translation = target.0.translation.vector - pose.0.translation.vector;
+    let rotation = (target.0.rotation * pose.0.rotation.inverse()).scaled_axis();
+
+    V::from_column_slice(&[translation.x, translation.y, translation.z, rotation.x, rotation.y, rotation.z])
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CartesianPathFailure {
+    InfeasibleStart,
+    /// IK did not converge for the next interpolated pose
+    IKFailure,
+    /// the IK solution for the next interpolated pose is farther than `jump_threshold` from the
+    /// previous state
+    JointSpaceJump,
+    /// the motion to the IK solution for the next interpolated pose is infeasible
+    InfeasibleMotion
+}
+
+#[derive(Clone, Debug)]
+pub struct CartesianPathOutput {
+    /// joint-space path from the start state to the state of the last reached pose
+    pub path: Vec<V>,
+    /// the interpolated link poses reached by `path[1..]`
+    pub poses: Vec<ISE3q>,
+    /// the fraction of the interpolated poses that were reached
+    pub fraction: f64,
+    /// why planning stopped before the end of the Cartesian path, or `None` if it reached the end
+    pub failure: Option<CartesianPathFailure>
+}
+impl CartesianPathOutput {
+    #[inline(always)]
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+}
@@ -0,0 +1,107 @@
+pub mod robot_constraints;
+
+use std::sync::Arc;
+use apollo_rust_linalg::{M, V};
+
+/// A constraint on states, expressed as a residual function that is zero exactly on the
+/// constraint manifold.  Inequalities, such as task space regions, use residuals that are zero
+/// inside the allowed region and grow with the violation outside of it.
+pub trait ConstraintTrait {
+    fn residual(&self, state: &V) -> V;
+
+    /// The Jacobian of `residual`.  By default, it is computed with forward finite differences.
+    fn jacobian(&self, state: &V) -> M {
+        let h = 1e-6;
+        let r0 = self.residual(state);
+        let mut out = M::zeros(r0.len(), state.len());
+        for j in 0..state.len() {
+            let mut state_h = state.clone();
+            state_h[j] += h;
+            out.set_column(j, &((self.residual(&state_h) - &r0) / h));
+        }
+
+        out
+    }
+
+    fn is_satisfied(&self, state: &V, tolerance: f64) -> bool {
+        self.residual(state).norm() <= tolerance
+    }
+}
+impl<T: ConstraintTrait + ?Sized> ConstraintTrait for Arc<T> {
+    fn residual(&self, state: &V) -> V {
+        (**self).residual(state)
+    }
+
+    fn jacobian(&self, state: &V) -> M {
+        (**self).jacobian(state)
+    }
+}
+
+/// An equality constraint `function(state) = 0` given by an arbitrary function.
+#[derive(Clone)]
+pub struct FunctionConstraint {
+    pub function: Arc<dyn Fn(&V) -> V + Send + Sync>
+}
+impl FunctionConstraint {
+    pub fn new(function: Arc<dyn Fn(&V) -> V + Send + Sync>) -> Self {
+        Self { function }
+    }
+}
+impl ConstraintTrait for FunctionConstraint {
+    fn residual(&self, state: &V) -> V {
+        (self.function)(state)
+    }
+}
+
+/// All of the given constraints at once; the residuals and Jacobians are stacked.
+#[derive(Clone)]
+pub struct CompositeConstraint {
+    pub constraints: Vec<Arc<dyn ConstraintTrait + Send + Sync>>
+}
+impl CompositeConstraint {
+    pub fn new(constraints: Vec<Arc<dyn ConstraintTrait + Send + Sync>>) -> Self {
+        assert!(!constraints.is_empty());
+        Self { constraints }
+    }
+}
+impl ConstraintTrait for CompositeConstraint {
+    fn residual(&self, state: &V) -> V {
+        let residuals: Vec<V> = self.constraints.iter().map(|x| x.residual(state)).collect();
+        V::from_iterator(residuals.iter().map(|x| x.len()).sum(), residuals.iter().flat_map(|x| x.iter().cloned()))
+    }
+
+    fn jacobian(&self, state: &V) -> M {
+        let jacobians: Vec<M> = self.constraints.iter().map(|x| x.jacobian(state)).collect();
+        let mut out = M::zeros(jacobians.iter().map(|x| x.nrows()).sum(), state.len());
+        let mut row = 0;
+        for jacobian in jacobians {
+            out.view_mut((row, 0), (jacobian.nrows(), state.len())).copy_from(&jacobian);
+            row += jacobian.nrows();
+        }
+
+        out
+    }
+}
+
+/// Projects `state` onto the constraint manifold with Jacobian-based projection (Stilman, 2010;
+/// Berenson et al., 2009): repeated Gauss-Newton steps `state -= J^+ residual(state)` with a
+/// slightly damped pseudo-inverse, clamped to `bounds`.  Returns `None` if the residual norm is
+/// not below `tolerance` after `max_iterations` steps.
+pub fn project_onto_constraint<C: ConstraintTrait + ?Sized>(constraint: &C, state: &V, bounds: &Vec<(f64, f64)>, tolerance: f64, max_iterations: usize) -> Option<V> {
+    let damping: f64 = 1e-4;
+    let mut out = state.clone();
+    for _ in 0..max_iterations {
+        let residual = constraint.residual(&out);
+        if residual.norm() <= tolerance { return Some(out); }
+
+        let jacobian = constraint.jacobian(&out);
+        let damped = &jacobian * jacobian.transpose() + M::identity(residual.len(), residual.len()) * damping.powi(2);
+        let step = jacobian.transpose() * damped.cholesky()?.solve(&residual);
+        out -= step;
+        for (i, x) in out.iter_mut().enumerate() {
+            *x = x.clamp(bounds[i].0, bounds[i].1);
+        }
+    }
+
+    if constraint.is_satisfied(&out, tolerance) { Some(out) } else { None }
+}
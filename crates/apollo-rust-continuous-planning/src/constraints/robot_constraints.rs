@@ -0,0 +1,87 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use apollo_rust_linalg::V;
+use apollo_rust_robotics_core::ChainNalgebra;
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use crate::constraints::ConstraintTrait;
+
+/// A task space region (Berenson et al., 2011): a set of end-effector poses given by bounds on
+/// the displacement of the end-effector frame in the region frame.  The six bounds are on x, y,
+/// z, roll, pitch, and yaw (as returned by `UnitQuaternion::euler_angles`).  Position bounds may
+/// be infinite, and angle bounds that span at least `2 * PI` leave that angle free.
+///
+/// For example, a held cup stays upright with zero roll and pitch bounds and free yaw, and an
+/// end effector stays in the region's xy-plane with z bounds of `(0.0, 0.0)`.
+#[derive(Clone, Debug)]
+pub struct TaskSpaceRegion {
+    pub world_to_region: ISE3q,
+    /// offset of the end-effector frame from the frame of the constrained link, e.g., a grasp
+    pub link_to_end_effector: ISE3q,
+    pub bounds: [(f64, f64); 6]
+}
+impl TaskSpaceRegion {
+    pub fn new(world_to_region: ISE3q, link_to_end_effector: ISE3q, bounds: [(f64, f64); 6]) -> Self {
+        assert!(bounds.iter().all(|(lower, upper)| lower <= upper));
+        Self { world_to_region, link_to_end_effector, bounds }
+    }
+
+    /// The x, y, z, roll, pitch, and yaw of the end-effector frame in the region frame, given
+    /// the world pose of the constrained link.
+    pub fn displacement(&self, link_pose: &ISE3q) -> [f64; 6] {
+        let pose = self.world_to_region.0.inverse() * link_pose.0 * self.link_to_end_effector.0;
+        let t = &pose.translation.vector;
+        let (roll, pitch, yaw) = pose.rotation.euler_angles();
+
+        [t.x, t.y, t.z, roll, pitch, yaw]
+    }
+
+    /// The signed violation of each bound, which is zero for poses within the region.  Angle
+    /// violations are measured to the nearest bound around the circle.
+    pub fn violation(&self, link_pose: &ISE3q) -> V {
+        let displacement = self.displacement(link_pose);
+        V::from_fn(6, |i, _| {
+            let (lower, upper) = self.bounds[i];
+            if i < 3 { position_violation(displacement[i], lower, upper) } else { angle_violation(displacement[i], lower, upper) }
+        })
+    }
+
+    pub fn contains(&self, link_pose: &ISE3q, tolerance: f64) -> bool {
+        self.violation(link_pose).norm() <= tolerance
+    }
+}
+
+/// Keeps the end effector on a link of a robot within a task space region.
+#[derive(Clone)]
+pub struct TaskSpaceRegionConstraint {
+    pub robot_chain: Arc<ChainNalgebra>,
+    pub link_idx: usize,
+    pub task_space_region: TaskSpaceRegion
+}
+impl TaskSpaceRegionConstraint {
+    pub fn new(robot_chain: Arc<ChainNalgebra>, link_idx: usize, task_space_region: TaskSpaceRegion) -> Self {
+        assert!(link_idx < robot_chain.urdf_module().links.len());
+        Self { robot_chain, link_idx, task_space_region }
+    }
+}
+impl ConstraintTrait for TaskSpaceRegionConstraint {
+    fn residual(&self, state: &V) -> V {
+        let link_pose = self.robot_chain.fk(state)[self.link_idx].clone();
+        self.task_space_region.violation(&link_pose)
+    }
+}
+
+fn position_violation(x: f64, lower: f64, upper: f64) -> f64 {
+    if x < lower { x - lower } else if x > upper { x - upper } else { 0.0 }
+}
+
+fn angle_violation(x: f64, lower: f64, upper: f64) -> f64 {
+    if upper - lower >= 2.0 * PI { return 0.0; }
+
+    // the representative of x in [lower, lower + 2 * PI).
+    let x = lower + (x - lower).rem_euclid(2.0 * PI);
+    if x <= upper { return 0.0; }
+    let above = x - upper;
+    let below = x - (lower + 2.0 * PI);
+
+    if above < -below { above } else { below }
+}
@@ -1,7 +1,90 @@
 pub mod robot_feasibility_checkers;
 
+use std::hash::Hasher;
 use apollo_rust_linalg::V;
 
+/// The joint-space resolution used by the default `is_feasible_motion`.
+pub const DEFAULT_JOINT_SPACE_MOTION_RESOLUTION: f64 = 0.01;
+
 pub trait FeasibilityCheckerTrait {
     fn is_feasible_state(&self, state: &V) -> bool;
-}
\ No newline at end of file
+
+    /// Checks the straight joint-space motion from `start` to `end`.  `start` itself is assumed
+    /// to be feasible and is not checked.
+    ///
+    /// By default, states spaced at most `DEFAULT_JOINT_SPACE_MOTION_RESOLUTION` apart are
+    /// checked in bisection order.
+    fn is_feasible_motion(&self, start: &V, end: &V) -> bool {
+        let num_steps = num_joint_space_motion_steps(start, end, DEFAULT_JOINT_SPACE_MOTION_RESOLUTION);
+        is_feasible_motion_bisection(self, start, end, num_steps)
+    }
+
+    /// Feeds everything the checker's answers depend on (e.g., the robot, the environment, and
+    /// how motions are checked) into `state`, so that results saved with one checker, such as
+    /// roadmaps, are only reused with an equivalent one.  By default nothing is fed, so all
+    /// checkers of a type are treated as equivalent.  Since these hashes are saved to disk, inputs
+    /// should be fed through `Hasher::write` with a fixed byte encoding rather than through `Hash`
+    /// impls, whose output may differ between platforms and Rust releases.
+    fn hash_inputs(&self, _state: &mut dyn Hasher) { }
+}
+
+/// How `is_feasible_motion` checks the states between the two ends of a motion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MotionValidationMode {
+    /// Checks evenly spaced states in order from start to end.
+    Discretized(MotionResolution),
+    /// Checks the same states as `Discretized`, but in bisection order (the midpoint first, then
+    /// the midpoints of both halves, and so on), which finds collisions in the middle of long
+    /// motions with fewer checks.
+    Bisection(MotionResolution),
+    /// Conservative advancement on distance queries: the motion is advanced by steps that are
+    /// guaranteed to be collision free given the clearance at the current state, and it is
+    /// infeasible once the clearance drops below `tolerance` (in workspace units).
+    Continuous { tolerance: f64 }
+}
+
+/// The spacing of the states checked along a motion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MotionResolution {
+    /// Maximum joint-space (Euclidean) distance between consecutive checked states.
+    JointSpace(f64),
+    /// Maximum workspace distance that any point on the robot can move between consecutive
+    /// checked states.
+    Workspace(f64)
+}
+
+/// Returns the number of steps needed so that consecutive states on the straight motion from
+/// `start` to `end` are at most `resolution` apart in joint space.
+pub fn num_joint_space_motion_steps(start: &V, end: &V, resolution: f64) -> usize {
+    assert!(resolution > 0.0);
+    ((end - start).norm() / resolution).ceil().max(1.0) as usize
+}
+
+/// Checks the states `start + (end - start) * i / num_steps` for `i = 1..=num_steps` in order.
+pub fn is_feasible_motion_discretized<F: FeasibilityCheckerTrait + ?Sized>(feasibility_checker: &F, start: &V, end: &V, num_steps: usize) -> bool {
+    for i in 1..=num_steps {
+        let t = i as f64 / num_steps as f64;
+        if !feasibility_checker.is_feasible_state(&(start + (end - start) * t)) { return false; }
+    }
+
+    true
+}
+
+/// Checks the same states as `is_feasible_motion_discretized`, but the end state first and then
+/// the remaining states in bisection order.
+pub fn is_feasible_motion_bisection<F: FeasibilityCheckerTrait + ?Sized>(feasibility_checker: &F, start: &V, end: &V, num_steps: usize) -> bool {
+    if !feasibility_checker.is_feasible_state(end) { return false; }
+
+    // ranges of step indices (exclusive on both ends) that still have to be checked.
+    let mut queue = std::collections::VecDeque::from(vec![(0, num_steps)]);
+    while let Some((a, b)) = queue.pop_front() {
+        if b - a < 2 { continue; }
+        let mid = (a + b) / 2;
+        let t = mid as f64 / num_steps as f64;
+        if !feasibility_checker.is_feasible_state(&(start + (end - start) * t)) { return false; }
+        queue.push_back((a, mid));
+        queue.push_back((mid, b));
+    }
+
+    true
+}
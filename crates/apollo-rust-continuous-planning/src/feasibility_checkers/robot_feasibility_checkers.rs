@@ -1,10 +1,96 @@
+use std::hash::Hasher;
 use std::sync::{Arc, RwLock};
 use apollo_rust_linalg::V;
+use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFJointType;
 use apollo_rust_proximity_parry::bvh::{Bvh, BvhShape};
 use apollo_rust_proximity_parry::ToIntersectionResult;
 use apollo_rust_robotics_core::ChainNalgebra;
 use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
-use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::feasibility_checkers::{is_feasible_motion_bisection, is_feasible_motion_discretized, num_joint_space_motion_steps, FeasibilityCheckerTrait, MotionResolution, MotionValidationMode, DEFAULT_JOINT_SPACE_MOTION_RESOLUTION};
+
+/// Bounds how far any point on a robot can move in the workspace during a straight joint-space
+/// motion.  `dof_reaches[i]` is an upper bound on the distance from the axis of DOF `i` to any
+/// point on the geometry it moves (including attached objects), so no point moves farther than
+/// `sum_i dof_reaches[i] * |end_i - start_i|`.  The bounds do not depend on the configuration.
+#[derive(Clone, Debug)]
+pub struct RobotMotionBound {
+    pub dof_reaches: Vec<f64>
+}
+impl RobotMotionBound {
+    pub fn new(robot_chain: &ChainNalgebra) -> Self {
+        let urdf_module = robot_chain.urdf_module();
+        let chain_module = robot_chain.chain_module();
+
+        // radius of the geometry rigidly attached to each link, measured from the link frame.
+        let mut link_radii: Vec<f64> = robot_chain.link_shapes_max_distance_from_origin_module().full_bounding_spheres_maximum_distances.iter().map(|x| x.unwrap_or(0.0)).collect();
+        let attached_objects_module = robot_chain.attached_objects_module();
+        attached_objects_module.attached_objects.iter().zip(attached_objects_module.shapes().iter()).for_each(|(object, shape)| {
+            let radius = object.relative_pose.0.translation.vector.norm() + shape.calculate_max_dis_from_origin_to_point_on_shape();
+            link_radii[object.link_idx] = link_radii[object.link_idx].max(radius);
+        });
+
+        // walk up from every link, growing the distance to its geometry by each joint offset.
+        let mut joint_reaches = vec![0.0; urdf_module.joints.len()];
+        for (link_idx, link_radius) in link_radii.iter().enumerate() {
+            let mut extent = *link_radius;
+            let mut curr = chain_module.links_in_chain[link_idx].parent_joint_idx;
+            while let Some(joint_idx) = curr {
+                joint_reaches[joint_idx] = f64::max(joint_reaches[joint_idx], extent);
+                let joint = &urdf_module.joints[joint_idx];
+                extent += joint.origin.ise3q.0.translation.vector.norm();
+                if matches!(joint.joint_type, ApolloURDFJointType::Prismatic) {
+                    extent += joint.limit.lower.abs().max(joint.limit.upper.abs());
+                }
+                curr = chain_module.links_in_chain[chain_module.joints_in_chain[joint_idx].parent_link_idx].parent_joint_idx;
+            }
+        }
+
+        let dof_reaches = robot_chain.dof_module().dof_idx_to_joint_idx_mapping.iter().map(|joint_idx| {
+            match urdf_module.joints[*joint_idx].joint_type {
+                ApolloURDFJointType::Prismatic => { 1.0 }
+                ApolloURDFJointType::Revolute | ApolloURDFJointType::Continuous => { joint_reaches[*joint_idx] }
+                // these joints mix translational and rotational DOFs.
+                _ => { joint_reaches[*joint_idx].max(1.0) }
+            }
+        }).collect();
+
+        Self { dof_reaches }
+    }
+
+    /// An upper bound on the workspace distance moved by any point on the robot along the
+    /// straight joint-space motion from `start` to `end`.
+    pub fn displacement_bound(&self, start: &V, end: &V) -> f64 {
+        assert_eq!(self.dof_reaches.len(), start.len());
+        self.dof_reaches.iter().zip(start.iter().zip(end.iter())).map(|(r, (a, b))| r * (b - a).abs()).sum()
+    }
+
+    pub fn num_motion_steps(&self, start: &V, end: &V, resolution: MotionResolution) -> usize {
+        match resolution {
+            MotionResolution::JointSpace(r) => { num_joint_space_motion_steps(start, end, r) }
+            MotionResolution::Workspace(r) => {
+                assert!(r > 0.0);
+                (self.displacement_bound(start, end) / r).ceil().max(1.0) as usize
+            }
+        }
+    }
+}
+
+/// Checks a motion by conservative advancement.  `clearance_function` returns the minimum
+/// distance between the robot and everything it may collide with.  Two links of the robot can
+/// approach each other at most twice as fast as any single point moves, so advancing by
+/// `clearance / (2 * displacement_bound)` can never skip over a collision.
+pub fn is_feasible_motion_continuous<C: Fn(&V) -> f64>(clearance_function: C, motion_bound: &RobotMotionBound, start: &V, end: &V, tolerance: f64) -> bool {
+    assert!(tolerance > 0.0);
+    let bound = 2.0 * motion_bound.displacement_bound(start, end);
+
+    let mut t: f64 = 0.0;
+    loop {
+        let clearance = clearance_function(&(start + (end - start) * t));
+        if clearance < tolerance { return false; }
+        if t >= 1.0 { return true; }
+        t = if bound > 0.0 { (t + clearance / bound).min(1.0) } else { 1.0 };
+    }
+}
 
 #[derive(Clone)]
 pub struct RobotBoundsFeasibilityChecker {
@@ -29,10 +115,22 @@ impl FeasibilityCheckerTrait for RobotBoundsFeasibilityChecker {
         return true;
     }
 
+    /// The joint bounds form a box, so a straight motion between two states within the bounds
+    /// stays within them for every motion validation mode.
+    #[inline(always)]
+    fn is_feasible_motion(&self, _start: &V, end: &V) -> bool {
+        self.is_feasible_state(end)
+    }
+
+    fn hash_inputs(&self, state: &mut dyn Hasher) {
+        hash_chain(&self.robot_chain, state);
+    }
 }
 
+/// What the robot collision checkers check: the robot against itself and, optionally, against an
+/// environment chain at a fixed state, along with how motions are checked.
 #[derive(Clone)]
-pub struct RobotNaiveFeasibilityChecker {
+pub struct RobotCollisionScene {
     bounds_checker: RobotBoundsFeasibilityChecker,
     robot_chain: Arc<ChainNalgebra>,
     environment_chain: Option<Arc<ChainNalgebra>>,
@@ -40,22 +138,99 @@ pub struct RobotNaiveFeasibilityChecker {
     self_link_shape_mode: LinkShapeMode,
     self_link_shape_rep: LinkShapeRep,
     environment_link_shape_mode: Option<LinkShapeMode>,
-    environment_link_shape_rep: Option<LinkShapeRep>
+    environment_link_shape_rep: Option<LinkShapeRep>,
+    motion_validation_mode: MotionValidationMode,
+    motion_bound: RobotMotionBound
 }
-impl RobotNaiveFeasibilityChecker {
-    pub fn new(robot_chain: Arc<ChainNalgebra>, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep) -> Self {
+impl RobotCollisionScene {
+    /// `environment` holds the environment chain, its link shape mode and representation, and its
+    /// state.
+    pub fn new(robot_chain: Arc<ChainNalgebra>, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep, environment: Option<(Arc<ChainNalgebra>, LinkShapeMode, LinkShapeRep, &V)>) -> Self {
+        let motion_bound = RobotMotionBound::new(&robot_chain);
+        let (environment_chain, environment_link_shape_mode, environment_link_shape_rep, environment_chain_state) = match environment {
+            None => { (None, None, None, Default::default()) }
+            Some((chain, mode, rep, state)) => { (Some(chain), Some(mode), Some(rep), state.clone()) }
+        };
+
         Self {
             bounds_checker: RobotBoundsFeasibilityChecker::new(robot_chain.clone()),
             robot_chain,
-            environment_chain: None,
-            environment_chain_state: Default::default(),
+            environment_chain,
+            environment_chain_state,
             self_link_shape_mode,
             self_link_shape_rep,
-            environment_link_shape_mode: None,
-            environment_link_shape_rep: None,
+            environment_link_shape_mode,
+            environment_link_shape_rep,
+            motion_validation_mode: MotionValidationMode::Bisection(MotionResolution::JointSpace(DEFAULT_JOINT_SPACE_MOTION_RESOLUTION)),
+            motion_bound,
         }
     }
 
+    /// See `FeasibilityCheckerTrait::hash_inputs`.
+    pub fn hash_inputs(&self, state: &mut dyn Hasher) {
+        hash_chain(&self.robot_chain, state);
+        if let Some(environment_chain) = &self.environment_chain { hash_chain(environment_chain, state); }
+        state.write(format!("{:?}", (&self.environment_chain_state, self.self_link_shape_mode, self.self_link_shape_rep, self.environment_link_shape_mode, self.environment_link_shape_rep, self.motion_validation_mode)).as_bytes());
+    }
+}
+
+/// Methods shared by the checkers that check a robot against itself and an environment chain
+/// (see `RobotCollisionScene`).
+pub trait RobotCollisionCheckerTrait: FeasibilityCheckerTrait {
+    fn scene(&self) -> &RobotCollisionScene;
+
+    fn scene_mut(&mut self) -> &mut RobotCollisionScene;
+
+    /// Sets how `is_feasible_motion` checks motions.  The default is bisection with a joint-space
+    /// resolution of `DEFAULT_JOINT_SPACE_MOTION_RESOLUTION`.  Workspace resolutions and
+    /// continuous checking use a motion bound computed when the checker was created, so objects
+    /// attached to the chain afterward are not accounted for.
+    fn set_motion_validation_mode(&mut self, motion_validation_mode: MotionValidationMode) {
+        self.scene_mut().motion_validation_mode = motion_validation_mode;
+    }
+
+    fn motion_validation_mode(&self) -> MotionValidationMode {
+        self.scene().motion_validation_mode
+    }
+
+    /// The minimum distance between the robot and itself or the environment chain.
+    fn clearance(&self, state: &V) -> f64 {
+        let scene = self.scene();
+        let mut out = scene.robot_chain.self_distance_from_state(state, scene.self_link_shape_mode, scene.self_link_shape_rep, false).outputs.iter().fold(f64::INFINITY, |a, b| a.min(*b));
+        if let Some(environment_chain) = &scene.environment_chain {
+            let res = scene.robot_chain.double_chain_distance_from_states(environment_chain, state, scene.self_link_shape_mode, scene.self_link_shape_rep, &scene.environment_chain_state, scene.environment_link_shape_mode.unwrap(), scene.environment_link_shape_rep.unwrap(), false);
+            out = res.outputs.iter().fold(out, |a, b| a.min(*b));
+        }
+
+        out
+    }
+}
+
+/// Checks a motion with a robot collision checker in its motion validation mode.
+pub fn is_feasible_robot_motion<F: RobotCollisionCheckerTrait>(feasibility_checker: &F, start: &V, end: &V) -> bool {
+    let scene = feasibility_checker.scene();
+    match scene.motion_validation_mode {
+        MotionValidationMode::Discretized(resolution) => {
+            is_feasible_motion_discretized(feasibility_checker, start, end, scene.motion_bound.num_motion_steps(start, end, resolution))
+        }
+        MotionValidationMode::Bisection(resolution) => {
+            is_feasible_motion_bisection(feasibility_checker, start, end, scene.motion_bound.num_motion_steps(start, end, resolution))
+        }
+        MotionValidationMode::Continuous { tolerance } => {
+            scene.bounds_checker.is_feasible_motion(start, end) && is_feasible_motion_continuous(|x| feasibility_checker.clearance(x), &scene.motion_bound, start, end, tolerance)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RobotNaiveFeasibilityChecker {
+    scene: RobotCollisionScene
+}
+impl RobotNaiveFeasibilityChecker {
+    pub fn new(robot_chain: Arc<ChainNalgebra>, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep) -> Self {
+        Self { scene: RobotCollisionScene::new(robot_chain, self_link_shape_mode, self_link_shape_rep, None) }
+    }
+
     pub fn new_with_environment_chain(robot_chain: Arc<ChainNalgebra>,
                                       self_link_shape_mode: LinkShapeMode,
                                       self_link_shape_rep: LinkShapeRep,
@@ -63,44 +238,42 @@ impl RobotNaiveFeasibilityChecker {
                                       environment_link_shape_mode: LinkShapeMode,
                                       environment_link_shape_rep: LinkShapeRep,
                                       environment_state: &V) -> Self {
-        Self {
-            bounds_checker: RobotBoundsFeasibilityChecker::new(robot_chain.clone()),
-            robot_chain,
-            environment_chain: Some(environment_chain),
-            environment_chain_state: environment_state.clone(),
-            self_link_shape_mode,
-            self_link_shape_rep,
-            environment_link_shape_mode: Some(environment_link_shape_mode),
-            environment_link_shape_rep: Some(environment_link_shape_rep),
-        }
+        Self { scene: RobotCollisionScene::new(robot_chain, self_link_shape_mode, self_link_shape_rep, Some((environment_chain, environment_link_shape_mode, environment_link_shape_rep, environment_state))) }
     }
 }
+impl RobotCollisionCheckerTrait for RobotNaiveFeasibilityChecker {
+    fn scene(&self) -> &RobotCollisionScene { &self.scene }
+
+    fn scene_mut(&mut self) -> &mut RobotCollisionScene { &mut self.scene }
+}
 impl FeasibilityCheckerTrait for RobotNaiveFeasibilityChecker {
     fn is_feasible_state(&self, state: &V) -> bool {
-        if !self.bounds_checker.is_feasible_state(state) { return false; }
+        let scene = &self.scene;
+        if !scene.bounds_checker.is_feasible_state(state) { return false; }
 
-        if let Some(environment_chain) = &self.environment_chain {
-            let res = self.robot_chain.double_chain_intersect_from_states(environment_chain, state, self.self_link_shape_mode, self.self_link_shape_rep, &self.environment_chain_state, self.environment_link_shape_mode.unwrap(), self.environment_link_shape_rep.unwrap(), true).to_intersection_result();
+        if let Some(environment_chain) = &scene.environment_chain {
+            let res = scene.robot_chain.double_chain_intersect_from_states(environment_chain, state, scene.self_link_shape_mode, scene.self_link_shape_rep, &scene.environment_chain_state, scene.environment_link_shape_mode.unwrap(), scene.environment_link_shape_rep.unwrap(), true).to_intersection_result();
             if res { return false; }
         }
 
-        let res = self.robot_chain.self_intersect_from_state(state, self.self_link_shape_mode, self.self_link_shape_rep, true).to_intersection_result();
+        let res = scene.robot_chain.self_intersect_from_state(state, scene.self_link_shape_mode, scene.self_link_shape_rep, true).to_intersection_result();
         if res { return false; }
 
         return true;
     }
+
+    fn is_feasible_motion(&self, start: &V, end: &V) -> bool {
+        is_feasible_robot_motion(self, start, end)
+    }
+
+    fn hash_inputs(&self, state: &mut dyn Hasher) {
+        self.scene.hash_inputs(state);
+    }
 }
 
 #[derive(Clone)]
 pub struct RobotBVHFeasibilityChecker<B: BvhShape> {
-    bounds_checker: RobotBoundsFeasibilityChecker,
-    robot_chain: Arc<ChainNalgebra>,
-    environment_chain: Option<Arc<ChainNalgebra>>,
-    environment_chain_state: V,
-    self_link_shape_mode: LinkShapeMode,
-    self_link_shape_rep: LinkShapeRep,
-    environment_link_shape_mode: Option<LinkShapeMode>,
-    environment_link_shape_rep: Option<LinkShapeRep>,
+    scene: RobotCollisionScene,
     robot_bvh: Arc<RwLock<Bvh<B>>>,
     environment_bvh: Option<Arc<RwLock<Bvh<B>>>>
 }
@@ -108,14 +281,7 @@ impl<B: BvhShape> RobotBVHFeasibilityChecker<B> {
     pub fn new(robot_chain: Arc<ChainNalgebra>, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep, branch_factor: usize) -> Self {
         let robot_bvh = robot_chain.get_bvh::<B>(&robot_chain.zeros_state(), self_link_shape_mode, self_link_shape_rep, branch_factor);
         Self {
-            bounds_checker: RobotBoundsFeasibilityChecker::new(robot_chain.clone()),
-            robot_chain,
-            environment_chain: None,
-            environment_chain_state: Default::default(),
-            self_link_shape_mode,
-            self_link_shape_rep,
-            environment_link_shape_mode: None,
-            environment_link_shape_rep: None,
+            scene: RobotCollisionScene::new(robot_chain, self_link_shape_mode, self_link_shape_rep, None),
             robot_bvh: Arc::new(RwLock::new(robot_bvh)),
             environment_bvh: None,
         }
@@ -133,36 +299,50 @@ impl<B: BvhShape> RobotBVHFeasibilityChecker<B> {
         let environment_bvh = environment_chain.get_bvh::<B>(environment_state, environment_link_shape_mode, environment_link_shape_rep, branch_factor);
 
         Self {
-            bounds_checker: RobotBoundsFeasibilityChecker::new(robot_chain.clone()),
-            robot_chain,
-            environment_chain: Some(environment_chain),
-            environment_chain_state: environment_state.clone(),
-            self_link_shape_mode,
-            self_link_shape_rep,
-            environment_link_shape_mode: Some(environment_link_shape_mode),
-            environment_link_shape_rep: Some(environment_link_shape_rep),
+            scene: RobotCollisionScene::new(robot_chain, self_link_shape_mode, self_link_shape_rep, Some((environment_chain, environment_link_shape_mode, environment_link_shape_rep, environment_state))),
             robot_bvh: Arc::new(RwLock::new(robot_bvh)),
             environment_bvh: Some(Arc::new(RwLock::new(environment_bvh))),
         }
     }
 }
+impl<B: BvhShape> RobotCollisionCheckerTrait for RobotBVHFeasibilityChecker<B> {
+    fn scene(&self) -> &RobotCollisionScene { &self.scene }
+
+    fn scene_mut(&mut self) -> &mut RobotCollisionScene { &mut self.scene }
+}
 impl<B: BvhShape> FeasibilityCheckerTrait for RobotBVHFeasibilityChecker<B> {
     fn is_feasible_state(&self, state: &V) -> bool {
-        if !self.bounds_checker.is_feasible_state(state) { return false; }
+        let scene = &self.scene;
+        if !scene.bounds_checker.is_feasible_state(state) { return false; }
 
-        let frames = self.robot_chain.fk(state);
+        let frames = scene.robot_chain.fk(state);
         let mut robot_bvh = self.robot_bvh.write().unwrap();
 
-        if let Some(environment_chain) = &self.environment_chain {
-            let environment_frames = environment_chain.fk(&self.environment_chain_state);
+        if let Some(environment_chain) = &scene.environment_chain {
+            let environment_frames = environment_chain.fk(&scene.environment_chain_state);
             let mut environment_bvh = self.environment_bvh.as_ref().unwrap().write().unwrap();
-            let res = self.robot_chain.double_chain_intersect_bvh(&mut robot_bvh, &mut environment_bvh, environment_chain, &frames, self.self_link_shape_mode, self.self_link_shape_rep, &environment_frames, self.environment_link_shape_mode.unwrap(), self.environment_link_shape_rep.unwrap(), true).to_intersection_result();
+            let res = scene.robot_chain.double_chain_intersect_bvh(&mut robot_bvh, &mut environment_bvh, environment_chain, &frames, scene.self_link_shape_mode, scene.self_link_shape_rep, &environment_frames, scene.environment_link_shape_mode.unwrap(), scene.environment_link_shape_rep.unwrap(), true).to_intersection_result();
             if res { return false; }
         }
 
-        let res = self.robot_chain.self_intersect_bvh(&mut robot_bvh, &frames, self.self_link_shape_mode, self.self_link_shape_rep, true).to_intersection_result();
+        let res = scene.robot_chain.self_intersect_bvh(&mut robot_bvh, &frames, scene.self_link_shape_mode, scene.self_link_shape_rep, true).to_intersection_result();
         if res { return false; }
 
         return true;
     }
-}
\ No newline at end of file
+
+    fn is_feasible_motion(&self, start: &V, end: &V) -> bool {
+        is_feasible_robot_motion(self, start, end)
+    }
+
+    fn hash_inputs(&self, state: &mut dyn Hasher) {
+        self.scene.hash_inputs(state);
+    }
+}
+
+/// Feeds the name, URDF, bounds, allowed collision rules, paddings, and attached objects (but not
+/// their shapes) of `chain` into `state`.
+pub fn hash_chain(chain: &ChainNalgebra, state: &mut dyn Hasher) {
+    state.write(chain.resources_sub_directory().name.as_bytes());
+    state.write(format!("{:?}", (chain.urdf_module(), &chain.bounds_module.bounds, chain.allowed_collision_matrix.rules(), &chain.link_shapes_padding_nalgebra_module.link_paddings, &chain.link_shapes_padding_nalgebra_module.link_pair_paddings, &chain.attached_objects_module.attached_objects)).as_bytes());
+}
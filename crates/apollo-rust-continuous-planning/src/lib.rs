@@ -1,2 +1,6 @@
 pub mod feasibility_checkers;
-
+pub mod constraints;
+pub mod planners;
+pub mod path_processing;
+pub mod trajectory_optimization;
+pub mod cartesian_planning;
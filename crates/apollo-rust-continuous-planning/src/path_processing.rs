@@ -0,0 +1,190 @@
+use apollo_rust_interpolation::splines::BSpline;
+use apollo_rust_interpolation::InterpolatorTraitLite;
+use apollo_rust_linalg::V;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::planners::path_length;
+
+/// Returns true if the first state of `path` and every straight motion along it are feasible.
+pub fn is_feasible_path<F: FeasibilityCheckerTrait>(feasibility_checker: &F, path: &Vec<V>) -> bool {
+    match path.first() {
+        None => { false }
+        Some(first) => {
+            feasibility_checker.is_feasible_state(first) && path.windows(2).all(|x| feasibility_checker.is_feasible_motion(&x[0], &x[1]))
+        }
+    }
+}
+
+/// Returns the point at joint-space arclength `s` along `path`, along with the index `i` of the
+/// edge `path[i] -> path[i + 1]` that it lies on.  `s` is clamped to the length of the path.
+pub fn interpolate_path_by_arclength(path: &Vec<V>, s: f64) -> (usize, V) {
+    interpolate_path_by_arclength_with_lengths(path, &cumulative_lengths(path), s)
+}
+
+/// Inserts evenly spaced states into every edge of `path` so that consecutive states are at most
+/// `spacing` apart.  All states of the original path are kept.
+pub fn resample_path(path: &Vec<V>, spacing: f64) -> Vec<V> {
+    assert!(spacing > 0.0);
+    if path.is_empty() { return vec![]; }
+
+    let mut out = vec![path[0].clone()];
+    path.windows(2).for_each(|x| {
+        let num_steps = ((&x[1] - &x[0]).norm() / spacing).ceil().max(1.0) as usize;
+        for i in 1..=num_steps {
+            out.push(&x[0] + (&x[1] - &x[0]) * (i as f64 / num_steps as f64));
+        }
+    });
+
+    out
+}
+
+/// Random shortcutting: repeatedly picks two random points along the path and replaces the part
+/// of the path between them with a straight motion if that motion is feasible.
+pub fn random_shortcutting<F: FeasibilityCheckerTrait>(path: &Vec<V>, feasibility_checker: &F, max_iterations: usize, seed: Option<u64>) -> Vec<V> {
+    let mut rng = get_rng(seed);
+    let mut out = remove_duplicate_states(path);
+
+    for _ in 0..max_iterations {
+        if out.len() < 3 { break; }
+        let lengths = cumulative_lengths(&out);
+        let (s_a, s_b) = sample_arclength_pair(&mut rng, *lengths.last().unwrap());
+        let (i, a) = interpolate_path_by_arclength_with_lengths(&out, &lengths, s_a);
+        let (j, b) = interpolate_path_by_arclength_with_lengths(&out, &lengths, s_b);
+        if i == j { continue; }
+
+        if feasibility_checker.is_feasible_motion(&a, &b) {
+            let mut new_path = out[..=i].to_vec();
+            new_path.push(a);
+            new_path.push(b);
+            new_path.extend_from_slice(&out[j + 1..]);
+            out = remove_duplicate_states(&new_path);
+        }
+    }
+
+    out
+}
+
+/// Partial shortcutting (Geraerts and Overmars, 2007): repeatedly picks two random points along
+/// the path and a random DOF, and linearly interpolates only that DOF between the two points
+/// while the other DOFs follow the path.  This removes detours in single joints that full
+/// shortcuts cannot remove because other joints have to go around obstacles.
+pub fn partial_shortcutting<F: FeasibilityCheckerTrait>(path: &Vec<V>, feasibility_checker: &F, max_iterations: usize, seed: Option<u64>) -> Vec<V> {
+    let mut rng = get_rng(seed);
+    let mut out = remove_duplicate_states(path);
+    if out.is_empty() { return out; }
+    let num_dofs = out[0].len();
+
+    for _ in 0..max_iterations {
+        if out.len() < 3 { break; }
+        let lengths = cumulative_lengths(&out);
+        let (s_a, s_b) = sample_arclength_pair(&mut rng, *lengths.last().unwrap());
+        let (i, a) = interpolate_path_by_arclength_with_lengths(&out, &lengths, s_a);
+        let (j, b) = interpolate_path_by_arclength_with_lengths(&out, &lengths, s_b);
+        if i == j { continue; }
+        let dof = rng.gen_range(0..num_dofs);
+
+        let mut segment = vec![a.clone()];
+        segment.extend_from_slice(&out[i + 1..=j]);
+        segment.push(b.clone());
+        let segment_lengths = cumulative_lengths(&segment);
+        let total = *segment_lengths.last().unwrap();
+        if total <= 0.0 { continue; }
+        for k in 1..segment.len() - 1 {
+            segment[k][dof] = a[dof] + (b[dof] - a[dof]) * (segment_lengths[k] / total);
+        }
+
+        if !segment.windows(2).all(|x| feasibility_checker.is_feasible_motion(&x[0], &x[1])) { continue; }
+
+        let mut new_path = out[..=i].to_vec();
+        new_path.extend(segment);
+        new_path.extend_from_slice(&out[j + 1..]);
+        let new_path = remove_duplicate_states(&new_path);
+        if path_length(&new_path) < path_length(&out) { out = new_path; }
+    }
+
+    out
+}
+
+/// Smooths a path with a clamped B-spline of order `order` (e.g., 4 for cubic) whose control
+/// points are the path resampled at `control_point_spacing`.  The spline starts and ends at the
+/// ends of the path and is sampled so that consecutive output states are at most
+/// `output_resolution` apart.
+///
+/// The spline only stays close to the path, so it is validated with `feasibility_checker`.  If
+/// it is infeasible, the control point spacing is halved (which pulls the spline closer to the
+/// path) up to `max_attempts` times.  If no attempt is feasible, the input path is returned.
+pub fn bspline_smoothing<F: FeasibilityCheckerTrait>(path: &Vec<V>, feasibility_checker: &F, order: usize, control_point_spacing: f64, output_resolution: f64, max_attempts: usize) -> Vec<V> {
+    assert!(order > 1 && control_point_spacing > 0.0 && output_resolution > 0.0);
+    let path = remove_duplicate_states(path);
+
+    let mut spacing = control_point_spacing;
+    for _ in 0..max_attempts {
+        let control_points = resample_path(&path, spacing);
+        if control_points.len() < order { return path; }
+
+        let num_points = (path_length(&control_points) / output_resolution).ceil().max(1.0) as usize + 1;
+        let spline = BSpline::new(control_points, order, true, true);
+        let mut out = spline.interpolate_points_by_num_points(num_points);
+        // the spline is clamped, so this only removes floating point error at the ends.
+        out[0] = path[0].clone();
+        *out.last_mut().unwrap() = path.last().unwrap().clone();
+
+        if is_feasible_path(feasibility_checker, &out) { return out; }
+        spacing *= 0.5;
+    }
+
+    path
+}
+
+fn get_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        None => { StdRng::from_entropy() }
+        Some(seed) => { StdRng::seed_from_u64(seed) }
+    }
+}
+
+fn cumulative_lengths(path: &Vec<V>) -> Vec<f64> {
+    let mut out = vec![0.0];
+    let mut total = 0.0;
+    path.windows(2).for_each(|x| {
+        total += (&x[1] - &x[0]).norm();
+        out.push(total);
+    });
+
+    out
+}
+
+fn interpolate_path_by_arclength_with_lengths(path: &Vec<V>, lengths: &Vec<f64>, s: f64) -> (usize, V) {
+    assert!(!path.is_empty());
+    if path.len() == 1 { return (0, path[0].clone()); }
+
+    let s = s.clamp(0.0, *lengths.last().unwrap());
+    let i = match lengths.iter().position(|x| *x > s) {
+        None => { path.len() - 2 }
+        Some(idx) => { idx.max(1) - 1 }
+    };
+    let edge_length = lengths[i + 1] - lengths[i];
+    let t = if edge_length > 0.0 { (s - lengths[i]) / edge_length } else { 0.0 };
+
+    (i, &path[i] + (&path[i + 1] - &path[i]) * t)
+}
+
+fn sample_arclength_pair(rng: &mut StdRng, total_length: f64) -> (f64, f64) {
+    if total_length <= 0.0 { return (0.0, 0.0); }
+    let a = rng.gen_range(0.0..total_length);
+    let b = rng.gen_range(0.0..total_length);
+
+    if a < b { (a, b) } else { (b, a) }
+}
+
+fn remove_duplicate_states(path: &Vec<V>) -> Vec<V> {
+    let mut out: Vec<V> = vec![];
+    path.iter().for_each(|x| {
+        if out.last().map_or(true, |y| (x - y).norm() > 1e-9) { out.push(x.clone()); }
+    });
+    // keep the end of the path exact.
+    if out.len() > 1 { *out.last_mut().unwrap() = path.last().unwrap().clone(); }
+
+    out
+}
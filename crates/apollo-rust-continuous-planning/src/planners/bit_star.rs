@@ -0,0 +1,288 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+use apollo_rust_linalg::V;
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::planners::{JointSpaceSampler, PlannerOutput, PlannerTrait};
+use crate::planners::cost_functions::PathCostTrait;
+
+/// Batch Informed Trees, BIT* (Gammell, Srinivasa, and Barfoot, 2015).  Samples are added in
+/// batches and the implicit random geometric graph over them is searched in order of estimated
+/// solution cost, so edges are only collision checked when they could improve the current
+/// solution.  Once a solution exists, new samples are restricted to states that could improve it
+/// (by rejection sampling against the cost heuristic).  The search continues with new batches
+/// until the time budget or batch limit runs out, and the best path found is returned.
+///
+/// The vertex and edge queues are binary heaps with lazy deletion (see `LazyQueue`), so removing
+/// entries and changing their values after rewiring does not require searching the heaps.
+#[derive(Clone)]
+pub struct BITStar<C: PathCostTrait> {
+    pub bounds: Vec<(f64, f64)>,
+    pub cost_function: C,
+    pub batch_size: usize,
+    /// scales the connection radius `rewire_factor * (ln(n) / n)^(1 / num_dofs)`
+    pub rewire_factor: f64,
+    pub seed: Option<u64>,
+    pub time_budget: Duration,
+    pub max_batches: usize,
+    /// return as soon as the first solution is found instead of improving it
+    pub stop_at_first_solution: bool
+}
+impl<C: PathCostTrait> BITStar<C> {
+    pub fn new(bounds: Vec<(f64, f64)>, cost_function: C, batch_size: usize, rewire_factor: f64, seed: Option<u64>, time_budget: Duration, max_batches: usize, stop_at_first_solution: bool) -> Self {
+        assert!(batch_size > 0 && rewire_factor > 0.0);
+
+        Self { bounds, cost_function, batch_size, rewire_factor, seed, time_budget, max_batches, stop_at_first_solution }
+    }
+}
+impl<C: PathCostTrait> PlannerTrait for BITStar<C> {
+    fn plan<F: FeasibilityCheckerTrait>(&self, start: &V, goal: &V, feasibility_checker: &F) -> PlannerOutput {
+        let start_time = Instant::now();
+        let mut sampler = JointSpaceSampler::new(self.bounds.clone(), self.seed);
+        let num_dofs = start.len() as f64;
+
+        if !feasibility_checker.is_feasible_state(start) || !feasibility_checker.is_feasible_state(goal) {
+            return PlannerOutput { path: None, num_iterations: 0, num_nodes: 0, duration: start_time.elapsed() };
+        }
+
+        let c = &self.cost_function;
+        let g_hat = |x: &V| c.cost_lower_bound(start, x);
+        let h_hat = |x: &V| c.cost_lower_bound(x, goal);
+
+        // states 0 and 1 are the start and goal; every state is either in the tree or a sample.
+        let mut states = vec![start.clone(), goal.clone()];
+        let mut in_tree = vec![true, false];
+        // samples that cannot improve the current solution and are ignored from then on.
+        let mut pruned = vec![false, false];
+        let mut parents: Vec<Option<usize>> = vec![None, None];
+        let mut children: Vec<Vec<usize>> = vec![vec![], vec![]];
+        let mut costs = vec![0.0, f64::INFINITY];
+        // edges known to be infeasible, so they are not checked again in later batches.
+        let mut infeasible_edges: HashSet<(usize, usize)> = HashSet::new();
+
+        let mut vertex_queue: LazyQueue<usize> = LazyQueue::new();
+        let mut edge_queue: LazyQueue<(usize, usize)> = LazyQueue::new();
+        let mut radius = f64::INFINITY;
+        let mut num_batches = 0;
+        let mut num_iterations = 0;
+
+        'outer: while start_time.elapsed() < self.time_budget {
+            num_iterations += 1;
+
+            if vertex_queue.is_empty() && edge_queue.is_empty() {
+                if num_batches >= self.max_batches { break 'outer; }
+                num_batches += 1;
+
+                let c_best = costs[1];
+                // prune samples that cannot improve the solution.
+                if c_best.is_finite() {
+                    for i in 2..states.len() {
+                        if !in_tree[i] && g_hat(&states[i]) + h_hat(&states[i]) >= c_best { pruned[i] = true; }
+                    }
+                }
+
+                let mut num_added = 0;
+                let mut num_attempts = 0;
+                while num_added < self.batch_size && num_attempts < 100 * self.batch_size {
+                    num_attempts += 1;
+                    let sample = sampler.sample();
+                    if c_best.is_finite() && g_hat(&sample) + h_hat(&sample) >= c_best { continue; }
+                    if !feasibility_checker.is_feasible_state(&sample) { continue; }
+                    states.push(sample);
+                    in_tree.push(false);
+                    pruned.push(false);
+                    parents.push(None);
+                    children.push(vec![]);
+                    costs.push(f64::INFINITY);
+                    num_added += 1;
+                }
+
+                let n = states.len() as f64;
+                radius = self.rewire_factor * (n.ln() / n).powf(1.0 / num_dofs);
+                vertex_queue.clear();
+                (0..states.len()).filter(|i| in_tree[*i]).for_each(|i| vertex_queue.push(i, costs[i] + h_hat(&states[i])));
+            }
+
+            let c_best = costs[1];
+            let vertex_value = |i: usize| costs[i] + h_hat(&states[i]);
+            let edge_value = |(v, x): (usize, usize)| costs[v] + c.cost_lower_bound(&states[v], &states[x]) + h_hat(&states[x]);
+
+            // expand vertices while the best vertex could lead to a better edge than the best queued edge.
+            loop {
+                let best_vertex = vertex_queue.peek(vertex_value);
+                let best_edge = edge_queue.peek(edge_value);
+                let expand = match (best_vertex, best_edge) {
+                    (None, _) => { false }
+                    (Some(_), None) => { true }
+                    (Some((_, best_vertex_value)), Some((_, best_edge_value))) => { best_vertex_value <= best_edge_value }
+                };
+                if !expand { break; }
+
+                let (v, _) = vertex_queue.pop(vertex_value).expect("error");
+                let r2 = radius * radius;
+                for x in 0..states.len() {
+                    if x == v || pruned[x] || (&states[x] - &states[v]).norm_squared() > r2 { continue; }
+                    if infeasible_edges.contains(&(v, x)) || edge_queue.contains(&(v, x)) { continue; }
+                    let estimate = g_hat(&states[v]) + c.cost_lower_bound(&states[v], &states[x]) + h_hat(&states[x]);
+                    if estimate >= c_best { continue; }
+
+                    // edges to tree states are rewiring candidates.
+                    let is_candidate = !in_tree[x] || (parents[x] != Some(v) && parents[v] != Some(x) && costs[v] + c.cost_lower_bound(&states[v], &states[x]) < costs[x]);
+                    if is_candidate { edge_queue.push((v, x), edge_value((v, x))); }
+                }
+            }
+
+            let (v, x) = match edge_queue.pop(edge_value) {
+                None => { vertex_queue.clear(); continue 'outer; }
+                Some((e, _)) => { e }
+            };
+
+            if edge_value((v, x)) >= c_best {
+                // no queued edge can improve the solution; start a new batch.
+                vertex_queue.clear();
+                edge_queue.clear();
+                if self.stop_at_first_solution && costs[1].is_finite() { break 'outer; }
+                continue 'outer;
+            }
+
+            let edge_cost = c.edge_cost(&states[v], &states[x]);
+            if costs[v] + edge_cost + h_hat(&states[x]) >= c_best || costs[v] + edge_cost >= costs[x] { continue; }
+            if !feasibility_checker.is_feasible_motion(&states[v], &states[x]) {
+                infeasible_edges.insert((v, x));
+                infeasible_edges.insert((x, v));
+                continue;
+            }
+
+            let new_cost = costs[v] + edge_cost;
+            if in_tree[x] {
+                if let Some(old_parent) = parents[x] { children[old_parent].retain(|y| *y != x); }
+                parents[x] = Some(v);
+                children[v].push(x);
+                // update the costs of the subtree of x, and requeue its queued vertices and edges.
+                let delta = new_cost - costs[x];
+                let mut subtree = HashSet::new();
+                let mut stack = vec![x];
+                while let Some(i) = stack.pop() {
+                    costs[i] += delta;
+                    subtree.insert(i);
+                    stack.extend(children[i].iter().cloned());
+                }
+                vertex_queue.requeue(|i| subtree.contains(i), |i| costs[i] + h_hat(&states[i]));
+                edge_queue.requeue(|(w, _)| subtree.contains(w), |(w, y)| costs[w] + c.cost_lower_bound(&states[w], &states[y]) + h_hat(&states[y]));
+            } else {
+                in_tree[x] = true;
+                parents[x] = Some(v);
+                children[v].push(x);
+                costs[x] = new_cost;
+                vertex_queue.push(x, costs[x] + h_hat(&states[x]));
+            }
+
+            // drop queued edges into x that can no longer improve its cost.
+            let cx = costs[x];
+            edge_queue.retain(|(w, y)| *y != x || costs[*w] + c.cost_lower_bound(&states[*w], &states[x]) < cx);
+        }
+
+        let path = if costs[1].is_finite() {
+            let mut out = vec![];
+            let mut curr = Some(1);
+            while let Some(i) = curr {
+                out.push(states[i].clone());
+                curr = parents[i];
+            }
+            out.reverse();
+            Some(out)
+        } else {
+            None
+        };
+
+        PlannerOutput {
+            path,
+            num_iterations,
+            num_nodes: in_tree.iter().filter(|x| **x).count(),
+            duration: start_time.elapsed(),
+        }
+    }
+}
+
+/// A min-priority queue with lazy deletion.  `members` holds the queued items, and the heap may
+/// also hold stale entries: entries of removed items and entries whose value is no longer the
+/// current value of their item.  Stale entries are dropped once they reach the top.  When the
+/// value of a queued item changes, it has to be pushed again with `requeue`.
+struct LazyQueue<T: Copy + Eq + Hash + Ord> {
+    heap: BinaryHeap<QueueEntry<T>>,
+    members: HashSet<T>
+}
+impl<T: Copy + Eq + Hash + Ord> LazyQueue<T> {
+    fn new() -> Self {
+        Self { heap: BinaryHeap::new(), members: HashSet::new() }
+    }
+
+    fn push(&mut self, item: T, value: f64) {
+        self.members.insert(item);
+        self.heap.push(QueueEntry { value, item });
+    }
+
+    fn contains(&self, item: &T) -> bool {
+        self.members.contains(item)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.heap.clear();
+        self.members.clear();
+    }
+
+    /// Removes the queued items for which `f` returns false.
+    fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.members.retain(f);
+    }
+
+    /// Pushes the queued items for which `f` returns true again, with their current values.
+    fn requeue<F: Fn(&T) -> bool, G: Fn(T) -> f64>(&mut self, f: F, value: G) {
+        let items: Vec<T> = self.members.iter().filter(|x| f(x)).cloned().collect();
+        items.into_iter().for_each(|x| self.heap.push(QueueEntry { value: value(x), item: x }));
+    }
+
+    /// Drops stale entries from the top of the heap and returns the best queued item along with
+    /// its value, where `value` gives the current value of an item.  If only stale entries are
+    /// left, the queue is emptied.
+    fn peek<G: Fn(T) -> f64>(&mut self, value: G) -> Option<(T, f64)> {
+        while let Some(top) = self.heap.peek() {
+            if self.members.contains(&top.item) && top.value == value(top.item) { return Some((top.item, top.value)); }
+            self.heap.pop();
+        }
+        self.members.clear();
+
+        None
+    }
+
+    fn pop<G: Fn(T) -> f64>(&mut self, value: G) -> Option<(T, f64)> {
+        let out = self.peek(value)?;
+        self.heap.pop();
+        self.members.remove(&out.0);
+
+        Some(out)
+    }
+}
+
+/// Min-heap entry ordered by value, with ties broken by item so the search is deterministic.
+struct QueueEntry<T: Ord> {
+    value: f64,
+    item: T
+}
+impl<T: Ord> PartialEq for QueueEntry<T> {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+impl<T: Ord> Eq for QueueEntry<T> { }
+impl<T: Ord> PartialOrd for QueueEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+impl<T: Ord> Ord for QueueEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.value.total_cmp(&self.value).then_with(|| other.item.cmp(&self.item))
+    }
+}
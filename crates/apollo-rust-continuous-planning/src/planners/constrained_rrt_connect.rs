@@ -0,0 +1,138 @@
+use std::mem::swap;
+use std::time::{Duration, Instant};
+use apollo_rust_linalg::V;
+use crate::constraints::{project_onto_constraint, ConstraintTrait};
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::planners::{steer, JointSpaceSampler, PlannerOutput, PlannerTrait, PlannerTree};
+
+/// Constrained bidirectional RRT (CBiRRT2, Berenson et al., 2009): RRT-Connect where every step
+/// of an extension is projected onto the manifold of `constraint` with
+/// `project_onto_constraint`, so that all states of the trees satisfy the constraint.  Extensions
+/// stop when a projection fails, moves the state more than twice the step size, or does not get
+/// closer to the target.
+///
+/// Only the states of the path are on the manifold; the straight motions between them deviate
+/// from it by an amount that shrinks quadratically with `step_size`.  The start and goal have to
+/// satisfy the constraint.
+#[derive(Clone, Debug)]
+pub struct ConstrainedRRTConnect<C: ConstraintTrait> {
+    pub bounds: Vec<(f64, f64)>,
+    pub constraint: C,
+    /// maximum joint-space distance covered by one step of an extension before projection
+    pub step_size: f64,
+    /// probability of extending toward the root of the other tree instead of a random sample
+    pub goal_bias: f64,
+    /// states are on the manifold if the norm of their constraint residual is at most this value
+    pub projection_tolerance: f64,
+    pub max_projection_iterations: usize,
+    pub seed: Option<u64>,
+    pub timeout: Duration,
+    pub max_iterations: usize
+}
+impl<C: ConstraintTrait> ConstrainedRRTConnect<C> {
+    pub fn new(bounds: Vec<(f64, f64)>, constraint: C, step_size: f64, goal_bias: f64, projection_tolerance: f64, max_projection_iterations: usize, seed: Option<u64>, timeout: Duration, max_iterations: usize) -> Self {
+        assert!(step_size > 0.0);
+        assert!((0.0..=1.0).contains(&goal_bias));
+        assert!(projection_tolerance > 0.0);
+
+        Self { bounds, constraint, step_size, goal_bias, projection_tolerance, max_projection_iterations, seed, timeout, max_iterations }
+    }
+
+    /// Projects `state` onto the constraint manifold, see `project_onto_constraint`.
+    pub fn project(&self, state: &V) -> Option<V> {
+        project_onto_constraint(&self.constraint, state, &self.bounds, self.projection_tolerance, self.max_projection_iterations)
+    }
+
+    fn extend<F: FeasibilityCheckerTrait>(&self, tree: &mut PlannerTree, nearest_idx: usize, target: &V, feasibility_checker: &F) -> ExtendStatus {
+        let nearest = &tree.nodes[nearest_idx];
+        if (nearest - target).norm() < 1e-9 { return ExtendStatus::Reached(nearest_idx); }
+        let new_state = match self.project(&steer(nearest, target, self.step_size)) {
+            None => { return ExtendStatus::Trapped; }
+            Some(state) => { state }
+        };
+
+        if (&new_state - nearest).norm() > 2.0 * self.step_size { return ExtendStatus::Trapped; }
+        if (&new_state - target).norm() >= (nearest - target).norm() { return ExtendStatus::Trapped; }
+        if !feasibility_checker.is_feasible_motion(nearest, &new_state) { return ExtendStatus::Trapped; }
+
+        let reached = (&new_state - target).norm() < 1e-9;
+        let new_idx = tree.add_node(new_state, nearest_idx);
+        if reached { ExtendStatus::Reached(new_idx) } else { ExtendStatus::Advanced(new_idx) }
+    }
+
+    fn connect<F: FeasibilityCheckerTrait>(&self, tree: &mut PlannerTree, target: &V, feasibility_checker: &F) -> ExtendStatus {
+        let mut idx = tree.nearest(target);
+        loop {
+            let status = self.extend(tree, idx, target, feasibility_checker);
+            match status {
+                ExtendStatus::Advanced(new_idx) => { idx = new_idx; }
+                _ => { return status; }
+            }
+        }
+    }
+}
+impl<C: ConstraintTrait> PlannerTrait for ConstrainedRRTConnect<C> {
+    fn plan<F: FeasibilityCheckerTrait>(&self, start: &V, goal: &V, feasibility_checker: &F) -> PlannerOutput {
+        let start_time = Instant::now();
+        let mut sampler = JointSpaceSampler::new(self.bounds.clone(), self.seed);
+
+        let failure = |num_iterations: usize, num_nodes: usize| PlannerOutput {
+            path: None,
+            num_iterations,
+            num_nodes,
+            duration: start_time.elapsed(),
+        };
+
+        if !feasibility_checker.is_feasible_state(start) || !feasibility_checker.is_feasible_state(goal) { return failure(0, 0); }
+        if !self.constraint.is_satisfied(start, self.projection_tolerance) || !self.constraint.is_satisfied(goal, self.projection_tolerance) { return failure(0, 0); }
+
+        let mut tree_a = PlannerTree::new(start.clone());
+        let mut tree_b = PlannerTree::new(goal.clone());
+        // true while `tree_a` is the start tree
+        let mut a_is_start = true;
+
+        for iteration in 0..self.max_iterations {
+            if start_time.elapsed() > self.timeout { return failure(iteration, tree_a.num_nodes() + tree_b.num_nodes()); }
+
+            let target = if sampler.sample_unit() < self.goal_bias { tree_b.nodes[0].clone() } else { sampler.sample() };
+
+            // extend as far as possible toward the sample (as in CBiRRT2), since the sample is
+            // usually far from the manifold.
+            let num_nodes = tree_a.num_nodes();
+            let new_idx = match self.connect(&mut tree_a, &target, feasibility_checker) {
+                ExtendStatus::Reached(idx) => { Some(idx) }
+                _ => { if tree_a.num_nodes() > num_nodes { Some(tree_a.num_nodes() - 1) } else { None } }
+            };
+
+            if let Some(new_idx) = new_idx {
+                let new_state = tree_a.nodes[new_idx].clone();
+                if let ExtendStatus::Reached(connect_idx) = self.connect(&mut tree_b, &new_state, feasibility_checker) {
+                    let mut path = tree_a.path_from_root(new_idx);
+                    let mut rest = tree_b.path_from_root(connect_idx);
+                    rest.pop();
+                    rest.reverse();
+                    path.extend(rest);
+                    if !a_is_start { path.reverse(); }
+
+                    return PlannerOutput {
+                        path: Some(path),
+                        num_iterations: iteration + 1,
+                        num_nodes: tree_a.num_nodes() + tree_b.num_nodes(),
+                        duration: start_time.elapsed(),
+                    };
+                }
+            }
+
+            swap(&mut tree_a, &mut tree_b);
+            a_is_start = !a_is_start;
+        }
+
+        failure(self.max_iterations, tree_a.num_nodes() + tree_b.num_nodes())
+    }
+}
+
+enum ExtendStatus {
+    Trapped,
+    Advanced(usize),
+    Reached(usize)
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use apollo_rust_linalg::V;
+
+/// A cost of straight joint-space edges, used by the asymptotically optimal planners.
+pub trait PathCostTrait {
+    /// The cost of the straight edge from `a` to `b`.
+    fn edge_cost(&self, a: &V, b: &V) -> f64;
+
+    /// A lower bound on the cost of any path from `a` to `b`, used as an admissible heuristic.
+    /// It should be cheap to compute and must never exceed `edge_cost(a, b)`.
+    fn cost_lower_bound(&self, a: &V, b: &V) -> f64;
+
+    fn path_cost(&self, path: &Vec<V>) -> f64 {
+        path.windows(2).map(|x| self.edge_cost(&x[0], &x[1])).sum()
+    }
+}
+impl<T: PathCostTrait + ?Sized> PathCostTrait for Arc<T> {
+    fn edge_cost(&self, a: &V, b: &V) -> f64 {
+        (**self).edge_cost(a, b)
+    }
+
+    fn cost_lower_bound(&self, a: &V, b: &V) -> f64 {
+        (**self).cost_lower_bound(a, b)
+    }
+}
+
+/// Path length under the weighted joint metric `sqrt(sum_i w_i * (b_i - a_i)^2)`.
+#[derive(Clone, Debug)]
+pub struct WeightedJointDistanceCost {
+    pub weights: V
+}
+impl WeightedJointDistanceCost {
+    pub fn new(weights: V) -> Self {
+        assert!(weights.iter().all(|x| *x >= 0.0));
+        Self { weights }
+    }
+
+    /// Unit weights, i.e., the Euclidean joint-space path length.
+    pub fn new_unweighted(num_dofs: usize) -> Self {
+        Self::new(V::from_element(num_dofs, 1.0))
+    }
+}
+impl PathCostTrait for WeightedJointDistanceCost {
+    fn edge_cost(&self, a: &V, b: &V) -> f64 {
+        (b - a).iter().zip(self.weights.iter()).map(|(d, w)| w * d * d).sum::<f64>().sqrt()
+    }
+
+    fn cost_lower_bound(&self, a: &V, b: &V) -> f64 {
+        self.edge_cost(a, b)
+    }
+}
+
+/// Weighted joint distance where each part of an edge costs more the closer it is to
+/// obstacles: the length of a segment is multiplied by
+/// `1 + weight * max(0, 1 - clearance / influence_distance)`.
+///
+/// `clearance_function` maps a state to its clearance, e.g., the minimum of the distances
+/// returned by `ChainNalgebra::self_distance_from_state` and `obstacles_distance_from_state`.
+#[derive(Clone)]
+pub struct ClearanceWeightedCost {
+    pub distance_cost: WeightedJointDistanceCost,
+    pub clearance_function: Arc<dyn Fn(&V) -> f64 + Send + Sync>,
+    pub weight: f64,
+    pub influence_distance: f64,
+    /// joint-space spacing of the states at which clearance is evaluated along an edge
+    pub resolution: f64
+}
+impl ClearanceWeightedCost {
+    pub fn new(distance_cost: WeightedJointDistanceCost, clearance_function: Arc<dyn Fn(&V) -> f64 + Send + Sync>, weight: f64, influence_distance: f64, resolution: f64) -> Self {
+        assert!(weight >= 0.0 && influence_distance > 0.0 && resolution > 0.0);
+        Self { distance_cost, clearance_function, weight, influence_distance, resolution }
+    }
+
+    fn multiplier(&self, state: &V) -> f64 {
+        let clearance = (self.clearance_function)(state);
+        1.0 + self.weight * (1.0 - clearance / self.influence_distance).max(0.0)
+    }
+}
+impl PathCostTrait for ClearanceWeightedCost {
+    fn edge_cost(&self, a: &V, b: &V) -> f64 {
+        let length = self.distance_cost.edge_cost(a, b);
+        let num_steps = ((b - a).norm() / self.resolution).ceil().max(1.0) as usize;
+
+        // trapezoidal rule over the multipliers along the edge.
+        let multipliers: Vec<f64> = (0..=num_steps).map(|i| self.multiplier(&(a + (b - a) * (i as f64 / num_steps as f64)))).collect();
+        let average = multipliers.windows(2).map(|x| 0.5 * (x[0] + x[1])).sum::<f64>() / num_steps as f64;
+
+        length * average
+    }
+
+    fn cost_lower_bound(&self, a: &V, b: &V) -> f64 {
+        self.distance_cost.edge_cost(a, b)
+    }
+}
@@ -0,0 +1,125 @@
+pub mod rrt_connect;
+pub mod prm;
+pub mod cost_functions;
+pub mod rrt_star;
+pub mod bit_star;
+pub mod constrained_rrt_connect;
+
+use std::time::Duration;
+use apollo_rust_linalg::V;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+
+pub trait PlannerTrait {
+    /// Plans a joint-space path from `start` to `goal` whose states and edges are feasible
+    /// according to `feasibility_checker`.
+    fn plan<F: FeasibilityCheckerTrait>(&self, start: &V, goal: &V, feasibility_checker: &F) -> PlannerOutput;
+}
+
+#[derive(Clone, Debug)]
+pub struct PlannerOutput {
+    /// the path from start to goal, or `None` if no path was found
+    pub path: Option<Vec<V>>,
+    pub num_iterations: usize,
+    pub num_nodes: usize,
+    pub duration: Duration
+}
+impl PlannerOutput {
+    #[inline(always)]
+    pub fn is_success(&self) -> bool {
+        self.path.is_some()
+    }
+}
+
+/// Samples states uniformly within joint bounds.  Sampling is reproducible if a seed is given.
+pub struct JointSpaceSampler {
+    pub bounds: Vec<(f64, f64)>,
+    rng: StdRng
+}
+impl JointSpaceSampler {
+    pub fn new(bounds: Vec<(f64, f64)>, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            None => { StdRng::from_entropy() }
+            Some(seed) => { StdRng::seed_from_u64(seed) }
+        };
+
+        Self { bounds, rng }
+    }
+
+    pub fn sample(&mut self) -> V {
+        let bounds = &self.bounds;
+        let rng = &mut self.rng;
+        V::from_iterator(bounds.len(), bounds.iter().map(|(lower, upper)| {
+            if lower >= upper { *lower } else { rng.gen_range(*lower..*upper) }
+        }))
+    }
+
+    /// Returns a uniform sample in `[0, 1)`, e.g., for goal biasing.
+    pub fn sample_unit(&mut self) -> f64 {
+        self.rng.gen::<f64>()
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+
+/// Moves from `from` toward `to` by at most `step_size` (Euclidean joint-space distance).
+pub fn steer(from: &V, to: &V, step_size: f64) -> V {
+    let diff = to - from;
+    let norm = diff.norm();
+    if norm <= step_size { return to.clone(); }
+    from + diff * (step_size / norm)
+}
+
+/// Returns the Euclidean joint-space length of a path.
+pub fn path_length(path: &Vec<V>) -> f64 {
+    path.windows(2).map(|x| (&x[1] - &x[0]).norm()).sum()
+}
+
+/// A tree of states for sampling-based planners, with linear-scan nearest neighbor queries.
+#[derive(Clone, Debug)]
+pub struct PlannerTree {
+    pub nodes: Vec<V>,
+    pub parents: Vec<Option<usize>>
+}
+impl PlannerTree {
+    pub fn new(root: V) -> Self {
+        Self { nodes: vec![root], parents: vec![None] }
+    }
+
+    pub fn add_node(&mut self, state: V, parent: usize) -> usize {
+        self.nodes.push(state);
+        self.parents.push(Some(parent));
+        self.nodes.len() - 1
+    }
+
+    pub fn nearest(&self, state: &V) -> usize {
+        let mut best = (0, f64::INFINITY);
+        self.nodes.iter().enumerate().for_each(|(i, x)| {
+            let d = (x - state).norm_squared();
+            if d < best.1 { best = (i, d); }
+        });
+
+        best.0
+    }
+
+    /// Returns the states from the root to the given node.
+    pub fn path_from_root(&self, idx: usize) -> Vec<V> {
+        let mut out = vec![];
+        let mut curr = Some(idx);
+        while let Some(i) = curr {
+            out.push(self.nodes[i].clone());
+            curr = self.parents[i];
+        }
+        out.reverse();
+
+        out
+    }
+
+    #[inline(always)]
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+}
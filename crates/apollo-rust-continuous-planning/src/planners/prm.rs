@@ -0,0 +1,377 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::hash::Hasher;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_linalg::V;
+use apollo_rust_modules::{load_versioned_module_from_json, save_versioned_module, ResourcesSubDirectory};
+use serde::{Deserialize, Serialize};
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::planners::{JointSpaceSampler, PlannerOutput, PlannerTrait};
+
+/// Probabilistic roadmap (Kavraki et al., 1996) and its lazy variant (Bohlin and Kavraki, 2000).
+/// The roadmap is built once for a static environment and then answers many start-goal queries
+/// through `Roadmap::query`.  With `lazy` set, edges are only collision checked when they lie on
+/// a candidate shortest path, and the results are remembered in the roadmap.
+#[derive(Clone, Debug)]
+pub struct PRM {
+    pub bounds: Vec<(f64, f64)>,
+    /// number of feasible states in the roadmap
+    pub num_samples: usize,
+    /// number of nearest neighbors each state (and each query start and goal) is connected to
+    pub num_neighbors: usize,
+    pub lazy: bool,
+    pub seed: Option<u64>,
+    /// maximum time spent on one query, including roadmap construction in `plan`
+    pub timeout: Duration
+}
+impl PRM {
+    pub fn new(bounds: Vec<(f64, f64)>, num_samples: usize, num_neighbors: usize, lazy: bool, seed: Option<u64>, timeout: Duration) -> Self {
+        assert!(num_neighbors > 0);
+
+        Self { bounds, num_samples, num_neighbors, lazy, seed, timeout }
+    }
+
+    /// Hashes everything a roadmap built by `build_roadmap` depends on: the bounds, the number of
+    /// samples and neighbors, `lazy`, the seed, and the inputs of the feasibility checker (see
+    /// `FeasibilityCheckerTrait::hash_inputs`).  The inputs are serialized to little-endian bytes
+    /// and hashed with `StableHasher`, so the hash does not change between builds of the crate.
+    pub fn inputs_hash<F: FeasibilityCheckerTrait>(&self, feasibility_checker: &F) -> u64 {
+        let mut hasher = StableHasher::new();
+        hasher.write(&(self.bounds.len() as u64).to_le_bytes());
+        self.bounds.iter().for_each(|(lower, upper)| {
+            hasher.write(&lower.to_bits().to_le_bytes());
+            hasher.write(&upper.to_bits().to_le_bytes());
+        });
+        hasher.write(&(self.num_samples as u64).to_le_bytes());
+        hasher.write(&(self.num_neighbors as u64).to_le_bytes());
+        hasher.write(&[self.lazy as u8]);
+        match self.seed {
+            None => { hasher.write(&[0]); }
+            Some(seed) => { hasher.write(&[1]); hasher.write(&seed.to_le_bytes()); }
+        }
+        feasibility_checker.hash_inputs(&mut hasher);
+
+        hasher.finish()
+    }
+
+    /// Builds a roadmap of `num_samples` feasible states, each connected to its `num_neighbors`
+    /// nearest states.  Unless `lazy` is set, all edges are checked and infeasible ones dropped.
+    pub fn build_roadmap<F: FeasibilityCheckerTrait>(&self, feasibility_checker: &F) -> Roadmap {
+        let mut sampler = JointSpaceSampler::new(self.bounds.clone(), self.seed);
+
+        let mut nodes: Vec<V> = vec![];
+        let max_attempts = 1000 * self.num_samples.max(1);
+        let mut num_attempts = 0;
+        while nodes.len() < self.num_samples && num_attempts < max_attempts {
+            let sample = sampler.sample();
+            if feasibility_checker.is_feasible_state(&sample) { nodes.push(sample); }
+            num_attempts += 1;
+        }
+
+        let mut edges: Vec<Vec<RoadmapEdge>> = vec![vec![]; nodes.len()];
+        for i in 0..nodes.len() {
+            for (j, length) in k_nearest(&nodes, &nodes[i], self.num_neighbors + 1) {
+                if i == j || edges[i].iter().any(|x| x.to == j) { continue; }
+
+                let status = if self.lazy {
+                    EdgeStatus::Unchecked
+                } else if feasibility_checker.is_feasible_motion(&nodes[i], &nodes[j]) {
+                    EdgeStatus::Feasible
+                } else {
+                    continue;
+                };
+
+                edges[i].push(RoadmapEdge { to: j, length, status: status.clone() });
+                edges[j].push(RoadmapEdge { to: i, length, status });
+            }
+        }
+
+        Roadmap {
+            nodes: nodes.iter().map(|x| x.as_slice().to_vec()).collect(),
+            edges,
+            num_neighbors: self.num_neighbors,
+            inputs_hash: self.inputs_hash(feasibility_checker),
+        }
+    }
+}
+impl PlannerTrait for PRM {
+    /// Builds a new roadmap and answers a single query.  For repeated queries, build the roadmap
+    /// once with `build_roadmap` (or `Roadmap::load_or_build`) and call `Roadmap::query`.
+    fn plan<F: FeasibilityCheckerTrait>(&self, start: &V, goal: &V, feasibility_checker: &F) -> PlannerOutput {
+        let start_time = Instant::now();
+        let mut roadmap = self.build_roadmap(feasibility_checker);
+        let remaining = self.timeout.saturating_sub(start_time.elapsed());
+        let mut out = roadmap.query(start, goal, feasibility_checker, remaining);
+        out.duration = start_time.elapsed();
+
+        out
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeStatus {
+    Unchecked,
+    Feasible,
+    Infeasible
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoadmapEdge {
+    pub to: usize,
+    pub length: f64,
+    pub status: EdgeStatus
+}
+
+/// A roadmap of feasible states for one robot in one static environment.  Roadmaps are saved
+/// into the robot's URDD under `roadmap_modules/<name>`, in the same layout as preprocessed
+/// modules, so they can be reused across processes.  Edge statuses are only valid for the
+/// feasibility checker, including its motion validation mode, that they were checked with, so
+/// `load_or_build` rebuilds a saved roadmap whose `inputs_hash` does not match.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Roadmap {
+    pub nodes: Vec<Vec<f64>>,
+    /// adjacency lists; every edge is stored in both directions
+    pub edges: Vec<Vec<RoadmapEdge>>,
+    pub num_neighbors: usize,
+    /// `PRM::inputs_hash` of the planner and feasibility checker the roadmap was built with
+    pub inputs_hash: u64
+}
+impl Roadmap {
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn node(&self, idx: usize) -> V {
+        V::from_column_slice(&self.nodes[idx])
+    }
+
+    /// Answers a start-goal query.  The start and goal are connected to their nearest roadmap
+    /// states, then shortest paths are searched until one is found whose unchecked edges are all
+    /// feasible.  Edges found infeasible are marked so later queries skip them.
+    ///
+    /// # Arguments
+    /// - `start`: The start state.
+    /// - `goal`: The goal state.
+    /// - `feasibility_checker`: Validates states and edges.
+    /// - `timeout`: The maximum time spent on the query.
+    ///
+    /// # Returns
+    /// A `PlannerOutput` whose `num_iterations` is the number of graph searches.
+    pub fn query<F: FeasibilityCheckerTrait>(&mut self, start: &V, goal: &V, feasibility_checker: &F, timeout: Duration) -> PlannerOutput {
+        let start_time = Instant::now();
+        let n = self.num_nodes();
+
+        let failure = |num_iterations: usize| PlannerOutput {
+            path: None,
+            num_iterations,
+            num_nodes: n,
+            duration: start_time.elapsed(),
+        };
+
+        if !feasibility_checker.is_feasible_state(start) || !feasibility_checker.is_feasible_state(goal) { return failure(0); }
+
+        if feasibility_checker.is_feasible_motion(start, goal) {
+            return PlannerOutput {
+                path: Some(vec![start.clone(), goal.clone()]),
+                num_iterations: 0,
+                num_nodes: n,
+                duration: start_time.elapsed(),
+            };
+        }
+
+        let nodes: Vec<V> = (0..n).map(|i| self.node(i)).collect();
+        let start_edges: Vec<(usize, f64)> = k_nearest(&nodes, start, self.num_neighbors).into_iter()
+            .filter(|(i, _)| feasibility_checker.is_feasible_motion(start, &nodes[*i])).collect();
+        let goal_edges: Vec<(usize, f64)> = k_nearest(&nodes, goal, self.num_neighbors).into_iter()
+            .filter(|(i, _)| feasibility_checker.is_feasible_motion(&nodes[*i], goal)).collect();
+        if start_edges.is_empty() || goal_edges.is_empty() { return failure(0); }
+
+        let mut num_iterations = 0;
+        loop {
+            if start_time.elapsed() > timeout { return failure(num_iterations); }
+            num_iterations += 1;
+
+            let node_path = match self.shortest_path(&nodes, goal, &start_edges, &goal_edges) {
+                None => { return failure(num_iterations); }
+                Some(node_path) => { node_path }
+            };
+
+            let mut all_feasible = true;
+            for w in node_path.windows(2) {
+                let (a, b) = (w[0], w[1]);
+                let edge_idx = self.edges[a].iter().position(|x| x.to == b).expect("error");
+                if self.edges[a][edge_idx].status != EdgeStatus::Unchecked { continue; }
+
+                let status = if feasibility_checker.is_feasible_motion(&nodes[a], &nodes[b]) { EdgeStatus::Feasible } else { EdgeStatus::Infeasible };
+                self.set_edge_status(a, b, status.clone());
+                if status == EdgeStatus::Infeasible { all_feasible = false; break; }
+            }
+
+            if all_feasible {
+                let mut path = vec![start.clone()];
+                path.extend(node_path.iter().map(|x| nodes[*x].clone()));
+                path.push(goal.clone());
+
+                return PlannerOutput {
+                    path: Some(path),
+                    num_iterations,
+                    num_nodes: n,
+                    duration: start_time.elapsed(),
+                };
+            }
+        }
+    }
+
+    /// Saves the roadmap into `roadmap_modules/<name>` of the given URDD sub-directory.
+    pub fn save(&self, s: &ResourcesSubDirectory, name: &str) {
+        save_versioned_module(&Self::full_path_to_module_dir(s, name), &Self::current_version(), self);
+    }
+
+    /// Loads a roadmap saved with `save`.
+    pub fn load(s: &ResourcesSubDirectory, name: &str) -> Result<Self, String> {
+        load_versioned_module_from_json(&Self::full_path_to_module_dir(s, name), &Self::current_version())
+    }
+
+    /// Loads the roadmap saved under `name`, or builds it with `prm` and saves it if it does not
+    /// exist, is outdated, was built from other inputs (see `PRM::inputs_hash`), or `force_build`
+    /// is set.
+    pub fn load_or_build<F: FeasibilityCheckerTrait>(s: &ResourcesSubDirectory, name: &str, prm: &PRM, feasibility_checker: &F, force_build: bool) -> Self {
+        if !force_build {
+            match Self::load(s, name) {
+                Ok(roadmap) if roadmap.inputs_hash == prm.inputs_hash(feasibility_checker) => { return roadmap; }
+                Ok(_) => { println!("Roadmap {:?} was built from other inputs.  Will rebuild.", name); }
+                Err(e) => { println!("Unable to load roadmap {:?} because of this reason: {:?}.  Will rebuild.", name, e); }
+            }
+        }
+
+        let roadmap = prm.build_roadmap(feasibility_checker);
+        roadmap.save(s, name);
+        roadmap
+    }
+
+    pub fn current_version() -> String {
+        "0.0.2".to_string()
+    }
+
+    pub fn full_path_to_module_dir(s: &ResourcesSubDirectory, name: &str) -> PathBuf {
+        s.directory.clone().append("roadmap_modules").append(name)
+    }
+
+    fn set_edge_status(&mut self, a: usize, b: usize, status: EdgeStatus) {
+        self.edges[a].iter_mut().filter(|x| x.to == b).for_each(|x| x.status = status.clone());
+        self.edges[b].iter_mut().filter(|x| x.to == a).for_each(|x| x.status = status.clone());
+    }
+
+    /// A* from the start to the goal over edges not known to be infeasible, returning the
+    /// roadmap states along the way.
+    fn shortest_path(&self, nodes: &Vec<V>, goal: &V, start_edges: &Vec<(usize, f64)>, goal_edges: &Vec<(usize, f64)>) -> Option<Vec<usize>> {
+        let n = nodes.len();
+        let goal_idx = n;
+        let mut costs = vec![f64::INFINITY; n + 1];
+        let mut parents: Vec<Option<usize>> = vec![None; n + 1];
+        let mut closed = vec![false; n + 1];
+        let mut heap = BinaryHeap::new();
+
+        let heuristic = |i: usize| if i == goal_idx { 0.0 } else { (&nodes[i] - goal).norm() };
+
+        start_edges.iter().for_each(|(i, d)| {
+            if *d < costs[*i] {
+                costs[*i] = *d;
+                heap.push(HeapItem { priority: *d + heuristic(*i), idx: *i });
+            }
+        });
+
+        while let Some(HeapItem { idx, .. }) = heap.pop() {
+            if closed[idx] { continue; }
+            closed[idx] = true;
+
+            if idx == goal_idx {
+                let mut out = vec![];
+                let mut curr = parents[goal_idx];
+                while let Some(i) = curr {
+                    out.push(i);
+                    curr = parents[i];
+                }
+                out.reverse();
+                return Some(out);
+            }
+
+            let mut neighbors: Vec<(usize, f64)> = self.edges[idx].iter()
+                .filter(|x| x.status != EdgeStatus::Infeasible)
+                .map(|x| (x.to, x.length)).collect();
+            goal_edges.iter().filter(|x| x.0 == idx).for_each(|x| neighbors.push((goal_idx, x.1)));
+
+            for (j, d) in neighbors {
+                let cost = costs[idx] + d;
+                if cost < costs[j] {
+                    costs[j] = cost;
+                    parents[j] = Some(idx);
+                    heap.push(HeapItem { priority: cost + heuristic(j), idx: j });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the `k` states nearest to `state` as `(idx, distance)` pairs, nearest first.
+fn k_nearest(nodes: &Vec<V>, state: &V, k: usize) -> Vec<(usize, f64)> {
+    let mut distances: Vec<(usize, f64)> = nodes.iter().enumerate().map(|(i, x)| (i, (x - state).norm())).collect();
+    distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+    distances.truncate(k);
+
+    distances
+}
+
+/// Min-heap entry ordered by priority.
+struct HeapItem {
+    priority: f64,
+    idx: usize
+}
+impl PartialEq for HeapItem {
+    fn eq(&self, other: &Self) -> bool { self.priority == other.priority }
+}
+impl Eq for HeapItem { }
+impl PartialOrd for HeapItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+impl Ord for HeapItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.partial_cmp(&self.priority).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// 64-bit FNV-1a hasher.  Unlike `std::collections::hash_map::DefaultHasher`, whose algorithm may
+/// change between Rust releases, its output only depends on the bytes written to it, so hashes
+/// saved to disk (e.g., `Roadmap::inputs_hash`) stay valid across builds.  Integers passed to the
+/// `write_*` methods other than `write` are fed in native byte order, so inputs should be
+/// serialized with explicit byte orders, as in `PRM::inputs_hash`.
+#[derive(Clone, Debug)]
+pub struct StableHasher {
+    state: u64
+}
+impl StableHasher {
+    pub fn new() -> Self {
+        Self { state: 0xcbf29ce484222325 }
+    }
+}
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(0x100000001b3);
+        }
+    }
+}
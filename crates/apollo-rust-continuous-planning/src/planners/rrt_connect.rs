@@ -0,0 +1,117 @@
+use std::mem::swap;
+use std::time::{Duration, Instant};
+use apollo_rust_linalg::V;
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::planners::{steer, JointSpaceSampler, PlannerOutput, PlannerTrait, PlannerTree};
+
+/// Bidirectional RRT-Connect (Kuffner and LaValle, 2000).  One tree grows from the start and one
+/// from the goal; each iteration extends one tree toward a random sample and then greedily
+/// connects the other tree to the new state.
+#[derive(Clone, Debug)]
+pub struct RRTConnect {
+    pub bounds: Vec<(f64, f64)>,
+    /// maximum joint-space distance covered by one extension
+    pub step_size: f64,
+    /// probability of extending toward the root of the other tree instead of a random sample
+    pub goal_bias: f64,
+    pub seed: Option<u64>,
+    pub timeout: Duration,
+    pub max_iterations: usize
+}
+impl RRTConnect {
+    pub fn new(bounds: Vec<(f64, f64)>, step_size: f64, goal_bias: f64, seed: Option<u64>, timeout: Duration, max_iterations: usize) -> Self {
+        assert!(step_size > 0.0);
+        assert!((0.0..=1.0).contains(&goal_bias));
+
+        Self { bounds, step_size, goal_bias, seed, timeout, max_iterations }
+    }
+
+    /// Creates a planner with a step size of 0.1, goal bias of 0.05, no seed, a timeout of 5
+    /// seconds, and at most 100000 iterations.
+    pub fn new_default(bounds: Vec<(f64, f64)>) -> Self {
+        Self::new(bounds, 0.1, 0.05, None, Duration::from_secs(5), 100_000)
+    }
+
+    fn extend<F: FeasibilityCheckerTrait>(&self, tree: &mut PlannerTree, target: &V, feasibility_checker: &F) -> ExtendStatus {
+        let nearest_idx = tree.nearest(target);
+        let nearest = &tree.nodes[nearest_idx];
+        let new_state = steer(nearest, target, self.step_size);
+
+        if !feasibility_checker.is_feasible_motion(nearest, &new_state) { return ExtendStatus::Trapped; }
+
+        let reached = (&new_state - target).norm() < 1e-9;
+        let new_idx = tree.add_node(new_state, nearest_idx);
+        if reached { ExtendStatus::Reached(new_idx) } else { ExtendStatus::Advanced(new_idx) }
+    }
+
+    fn connect<F: FeasibilityCheckerTrait>(&self, tree: &mut PlannerTree, target: &V, feasibility_checker: &F) -> ExtendStatus {
+        loop {
+            let status = self.extend(tree, target, feasibility_checker);
+            match status {
+                ExtendStatus::Advanced(_) => { }
+                _ => { return status; }
+            }
+        }
+    }
+}
+impl PlannerTrait for RRTConnect {
+    fn plan<F: FeasibilityCheckerTrait>(&self, start: &V, goal: &V, feasibility_checker: &F) -> PlannerOutput {
+        let start_time = Instant::now();
+        let mut sampler = JointSpaceSampler::new(self.bounds.clone(), self.seed);
+
+        let failure = |num_iterations: usize, num_nodes: usize| PlannerOutput {
+            path: None,
+            num_iterations,
+            num_nodes,
+            duration: start_time.elapsed(),
+        };
+
+        if !feasibility_checker.is_feasible_state(start) || !feasibility_checker.is_feasible_state(goal) { return failure(0, 0); }
+
+        let mut tree_a = PlannerTree::new(start.clone());
+        let mut tree_b = PlannerTree::new(goal.clone());
+        // true while `tree_a` is the start tree
+        let mut a_is_start = true;
+
+        for iteration in 0..self.max_iterations {
+            if start_time.elapsed() > self.timeout { return failure(iteration, tree_a.num_nodes() + tree_b.num_nodes()); }
+
+            let target = if sampler.sample_unit() < self.goal_bias { tree_b.nodes[0].clone() } else { sampler.sample() };
+
+            let new_idx = match self.extend(&mut tree_a, &target, feasibility_checker) {
+                ExtendStatus::Trapped => { None }
+                ExtendStatus::Advanced(idx) | ExtendStatus::Reached(idx) => { Some(idx) }
+            };
+
+            if let Some(new_idx) = new_idx {
+                let new_state = tree_a.nodes[new_idx].clone();
+                if let ExtendStatus::Reached(connect_idx) = self.connect(&mut tree_b, &new_state, feasibility_checker) {
+                    let mut path = tree_a.path_from_root(new_idx);
+                    let mut rest = tree_b.path_from_root(connect_idx);
+                    rest.pop();
+                    rest.reverse();
+                    path.extend(rest);
+                    if !a_is_start { path.reverse(); }
+
+                    return PlannerOutput {
+                        path: Some(path),
+                        num_iterations: iteration + 1,
+                        num_nodes: tree_a.num_nodes() + tree_b.num_nodes(),
+                        duration: start_time.elapsed(),
+                    };
+                }
+            }
+
+            swap(&mut tree_a, &mut tree_b);
+            a_is_start = !a_is_start;
+        }
+
+        failure(self.max_iterations, tree_a.num_nodes() + tree_b.num_nodes())
+    }
+}
+
+enum ExtendStatus {
+    Trapped,
+    Advanced(usize),
+    Reached(usize)
+}
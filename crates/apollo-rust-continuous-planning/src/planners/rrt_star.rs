@@ -0,0 +1,178 @@
+use std::time::{Duration, Instant};
+use apollo_rust_linalg::V;
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::planners::{steer, JointSpaceSampler, PlannerOutput, PlannerTrait};
+use crate::planners::cost_functions::PathCostTrait;
+
+/// RRT* (Karaman and Frazzoli, 2011).  New states are connected to the cheapest feasible parent
+/// nearby and nearby states are rewired through them, so the solution cost keeps improving
+/// until the time budget or iteration limit runs out.  The best path found is returned.
+#[derive(Clone)]
+pub struct RRTStar<C: PathCostTrait> {
+    pub bounds: Vec<(f64, f64)>,
+    pub cost_function: C,
+    /// maximum joint-space distance covered by one extension, also the maximum rewiring radius
+    pub step_size: f64,
+    /// probability of extending toward the goal while it is not yet in the tree
+    pub goal_bias: f64,
+    /// scales the rewiring radius `rewire_factor * (ln(n) / n)^(1 / num_dofs)`
+    pub rewire_factor: f64,
+    pub seed: Option<u64>,
+    pub time_budget: Duration,
+    pub max_iterations: usize,
+    /// return as soon as the first solution is found instead of improving it
+    pub stop_at_first_solution: bool
+}
+impl<C: PathCostTrait> RRTStar<C> {
+    pub fn new(bounds: Vec<(f64, f64)>, cost_function: C, step_size: f64, goal_bias: f64, rewire_factor: f64, seed: Option<u64>, time_budget: Duration, max_iterations: usize, stop_at_first_solution: bool) -> Self {
+        assert!(step_size > 0.0 && rewire_factor > 0.0);
+        assert!((0.0..=1.0).contains(&goal_bias));
+
+        Self { bounds, cost_function, step_size, goal_bias, rewire_factor, seed, time_budget, max_iterations, stop_at_first_solution }
+    }
+}
+impl<C: PathCostTrait> PlannerTrait for RRTStar<C> {
+    fn plan<F: FeasibilityCheckerTrait>(&self, start: &V, goal: &V, feasibility_checker: &F) -> PlannerOutput {
+        let start_time = Instant::now();
+        let mut sampler = JointSpaceSampler::new(self.bounds.clone(), self.seed);
+        let num_dofs = start.len() as f64;
+
+        if !feasibility_checker.is_feasible_state(start) || !feasibility_checker.is_feasible_state(goal) {
+            return PlannerOutput { path: None, num_iterations: 0, num_nodes: 0, duration: start_time.elapsed() };
+        }
+
+        let mut tree = CostTree::new(start.clone());
+        let mut goal_idx: Option<usize> = None;
+        let mut num_iterations = 0;
+
+        while num_iterations < self.max_iterations && start_time.elapsed() < self.time_budget {
+            num_iterations += 1;
+
+            let target = if goal_idx.is_none() && sampler.sample_unit() < self.goal_bias { goal.clone() } else { sampler.sample() };
+            let nearest_idx = tree.nearest(&target);
+            let new_state = steer(&tree.nodes[nearest_idx], &target, self.step_size);
+            if !feasibility_checker.is_feasible_motion(&tree.nodes[nearest_idx], &new_state) { continue; }
+
+            let is_goal = (&new_state - goal).norm() < 1e-9;
+            if is_goal && goal_idx.is_some() { continue; }
+
+            let n = tree.nodes.len() as f64 + 1.0;
+            let radius = (self.rewire_factor * (n.ln() / n).powf(1.0 / num_dofs)).min(self.step_size);
+            let mut near = tree.near(&new_state, radius);
+            if !near.contains(&nearest_idx) { near.push(nearest_idx); }
+
+            // choose the cheapest feasible parent, checking candidates in order of cost.
+            let mut candidates: Vec<(usize, f64)> = near.iter().map(|i| (*i, tree.costs[*i] + self.cost_function.edge_cost(&tree.nodes[*i], &new_state))).collect();
+            candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let (parent_idx, new_cost) = *candidates.iter()
+                .find(|(i, _)| *i == nearest_idx || feasibility_checker.is_feasible_motion(&tree.nodes[*i], &new_state))
+                .expect("error");
+
+            let new_idx = tree.add_node(new_state, parent_idx, new_cost);
+            if is_goal { goal_idx = Some(new_idx); }
+
+            // rewire nearby states through the new state.
+            for i in near {
+                if i == parent_idx { continue; }
+                let cost = new_cost + self.cost_function.edge_cost(&tree.nodes[new_idx], &tree.nodes[i]);
+                if cost < tree.costs[i] && feasibility_checker.is_feasible_motion(&tree.nodes[new_idx], &tree.nodes[i]) {
+                    tree.reparent(i, new_idx, cost);
+                }
+            }
+
+            // connect or improve the connection to the goal.
+            if !is_goal && (&tree.nodes[new_idx] - goal).norm() <= self.step_size {
+                let cost = new_cost + self.cost_function.edge_cost(&tree.nodes[new_idx], goal);
+                match goal_idx {
+                    None => {
+                        if feasibility_checker.is_feasible_motion(&tree.nodes[new_idx], goal) {
+                            goal_idx = Some(tree.add_node(goal.clone(), new_idx, cost));
+                        }
+                    }
+                    Some(g) => {
+                        if cost < tree.costs[g] && feasibility_checker.is_feasible_motion(&tree.nodes[new_idx], goal) {
+                            tree.reparent(g, new_idx, cost);
+                        }
+                    }
+                }
+            }
+
+            if self.stop_at_first_solution && goal_idx.is_some() { break; }
+        }
+
+        PlannerOutput {
+            path: goal_idx.map(|g| tree.path_from_root(g)),
+            num_iterations,
+            num_nodes: tree.nodes.len(),
+            duration: start_time.elapsed(),
+        }
+    }
+}
+
+/// A tree with costs-to-come and child lists, so that rewiring can update the costs of subtrees.
+#[derive(Clone, Debug)]
+pub struct CostTree {
+    pub nodes: Vec<V>,
+    pub parents: Vec<Option<usize>>,
+    pub children: Vec<Vec<usize>>,
+    pub costs: Vec<f64>
+}
+impl CostTree {
+    pub fn new(root: V) -> Self {
+        Self { nodes: vec![root], parents: vec![None], children: vec![vec![]], costs: vec![0.0] }
+    }
+
+    pub fn add_node(&mut self, state: V, parent: usize, cost: f64) -> usize {
+        let idx = self.nodes.len();
+        self.nodes.push(state);
+        self.parents.push(Some(parent));
+        self.children.push(vec![]);
+        self.costs.push(cost);
+        self.children[parent].push(idx);
+        idx
+    }
+
+    /// Moves `idx` under `new_parent` with the given cost-to-come and updates its subtree.
+    pub fn reparent(&mut self, idx: usize, new_parent: usize, cost: f64) {
+        if let Some(old_parent) = self.parents[idx] {
+            self.children[old_parent].retain(|x| *x != idx);
+        }
+        self.parents[idx] = Some(new_parent);
+        self.children[new_parent].push(idx);
+
+        let delta = cost - self.costs[idx];
+        let mut stack = vec![idx];
+        while let Some(i) = stack.pop() {
+            self.costs[i] += delta;
+            stack.extend(self.children[i].iter().cloned());
+        }
+    }
+
+    pub fn nearest(&self, state: &V) -> usize {
+        let mut best = (0, f64::INFINITY);
+        self.nodes.iter().enumerate().for_each(|(i, x)| {
+            let d = (x - state).norm_squared();
+            if d < best.1 { best = (i, d); }
+        });
+
+        best.0
+    }
+
+    /// Returns the indices of all states within `radius` (Euclidean joint-space distance).
+    pub fn near(&self, state: &V, radius: f64) -> Vec<usize> {
+        let r2 = radius * radius;
+        self.nodes.iter().enumerate().filter(|(_, x)| (*x - state).norm_squared() <= r2).map(|(i, _)| i).collect()
+    }
+
+    pub fn path_from_root(&self, idx: usize) -> Vec<V> {
+        let mut out = vec![];
+        let mut curr = Some(idx);
+        while let Some(i) = curr {
+            out.push(self.nodes[i].clone());
+            curr = self.parents[i];
+        }
+        out.reverse();
+
+        out
+    }
+}
@@ -0,0 +1,378 @@
+use std::sync::Arc;
+use apollo_rust_differentiation::{DerivativeMethodNalgebraTrait, FunctionEngine, FunctionNalgebraTrait};
+use apollo_rust_interpolation::splines::BSpline;
+use apollo_rust_interpolation::InterpolatorTraitLite;
+use apollo_rust_linalg::{M, V};
+use apollo_rust_optimization::{IterativeOptimizerTrait, OptimizerOutputTrait};
+use crate::feasibility_checkers::FeasibilityCheckerTrait;
+use crate::path_processing::{interpolate_path_by_arclength, is_feasible_path};
+use crate::planners::path_length;
+
+/// How the decision variables of a `TrajectoryOptimizer` define the trajectory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrajectoryRepresentation {
+    /// The decision variables are the waypoints themselves, and costs are evaluated at the
+    /// waypoints.
+    Waypoints,
+    /// The decision variables are the control points of a clamped B-spline of order `order`
+    /// (e.g., 4 for cubic), and costs are evaluated at `num_samples` evenly spaced points on the
+    /// spline.
+    BSpline { order: usize, num_samples: usize }
+}
+
+/// How the end of the trajectory is constrained.  The start is always fixed.
+#[derive(Clone)]
+pub enum TrajectoryGoal {
+    /// The trajectory ends exactly at this state.
+    State(V),
+    /// The end is free, and this cost of the final state is added to the objective, e.g., the
+    /// distance of the end effector to a target pose.
+    Cost(Arc<dyn Fn(&V) -> f64 + Send + Sync>)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TrajectoryCostWeights {
+    /// weight of the sum of squared differences of consecutive sampled states
+    pub velocity: f64,
+    /// weight of the sum of squared second differences of consecutive sampled states
+    pub acceleration: f64,
+    /// weight of the collision cost of the sampled states
+    pub collision: f64,
+    /// weight of the squared violation of the joint bounds by the control points
+    pub joint_limits: f64,
+    /// weight of the goal cost, only used with `TrajectoryGoal::Cost`
+    pub goal: f64
+}
+impl TrajectoryCostWeights {
+    pub fn new(velocity: f64, acceleration: f64, collision: f64, joint_limits: f64, goal: f64) -> Self {
+        assert!(velocity >= 0.0 && acceleration >= 0.0 && collision >= 0.0 && joint_limits >= 0.0 && goal >= 0.0);
+        Self { velocity, acceleration, collision, joint_limits, goal }
+    }
+}
+impl Default for TrajectoryCostWeights {
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 10.0, 100.0, 1.0)
+    }
+}
+
+/// Gradient-based trajectory optimization in the style of CHOMP and TrajOpt.  The trajectory is
+/// represented by control points (see `TrajectoryRepresentation`) whose first point is fixed to
+/// the start, and the last one to the goal if the goal is a `TrajectoryGoal::State`.  The
+/// remaining control points are optimized with `optimizer` (e.g., `LBFGS`) to minimize
+/// smoothness, collision, joint limit, and goal costs (see `TrajectoryObjective`).
+///
+/// Joint limits are enforced with a penalty during optimization, and the control points are
+/// clamped to `bounds` afterwards.  Since a B-spline stays within the convex hull of its control
+/// points, the whole output trajectory is then within bounds.
+///
+/// Collisions are only penalized at the sampled states, so the output is not guaranteed to be
+/// collision free; validate it with `is_feasible_path` or use `refine`.
+pub struct TrajectoryOptimizer<O: IterativeOptimizerTrait> {
+    pub optimizer: O,
+    pub representation: TrajectoryRepresentation,
+    pub num_control_points: usize,
+    pub bounds: Vec<(f64, f64)>,
+    /// maps a state to its clearance, e.g., `RobotCollisionCheckerTrait::clearance`.  Negative
+    /// values are penetration depths.  If `None`, collisions are ignored.
+    pub clearance_function: Option<Arc<dyn Fn(&V) -> f64 + Send + Sync>>,
+    /// clearance below which a state is penalized (CHOMP's epsilon)
+    pub collision_margin: f64,
+    pub weights: TrajectoryCostWeights,
+    pub max_iterations: usize
+}
+impl<O: IterativeOptimizerTrait> TrajectoryOptimizer<O> {
+    pub fn new(optimizer: O, representation: TrajectoryRepresentation, num_control_points: usize, bounds: Vec<(f64, f64)>, clearance_function: Option<Arc<dyn Fn(&V) -> f64 + Send + Sync>>, collision_margin: f64, weights: TrajectoryCostWeights, max_iterations: usize) -> Self {
+        assert!(num_control_points >= 3);
+        assert!(collision_margin > 0.0);
+        if let TrajectoryRepresentation::BSpline { order, num_samples } = representation {
+            assert!(order > 1 && num_control_points >= order && num_samples >= 2);
+        }
+        Self { optimizer, representation, num_control_points, bounds, clearance_function, collision_margin, weights, max_iterations }
+    }
+
+    /// Optimizes a trajectory that starts at the first state of `initial_path`.  The control
+    /// points are initialized by resampling `initial_path` evenly by arclength, so it can be a
+    /// path from a sampling-based planner or just `vec![start, goal_state]`.
+    pub fn optimize(&self, initial_path: &Vec<V>, goal: &TrajectoryGoal) -> TrajectoryOptimizationOutput {
+        assert!(!initial_path.is_empty());
+        let num_dofs = initial_path[0].len();
+        assert_eq!(num_dofs, self.bounds.len());
+
+        let mut init_control_points = self.initial_control_points(initial_path);
+        if let TrajectoryGoal::State(goal_state) = goal {
+            *init_control_points.last_mut().unwrap() = goal_state.clone();
+        }
+
+        let objective = Arc::new(TrajectoryObjective {
+            start: initial_path[0].clone(),
+            goal: goal.clone(),
+            basis: basis_matrix(self.representation, self.num_control_points),
+            num_dofs,
+            bounds: self.bounds.clone(),
+            clearance_function: self.clearance_function.clone(),
+            collision_margin: self.collision_margin,
+            weights: self.weights
+        });
+
+        let num_free = objective.num_free_control_points();
+        let x0 = V::from_iterator(num_free * num_dofs, init_control_points[1..=num_free].iter().flat_map(|x| x.iter().cloned()));
+        let initial_cost = objective.cost(&x0);
+
+        let engine = FunctionEngine::new(objective.clone(), TrajectoryObjectiveDerivative::new(objective.clone()));
+        let res = self.optimizer.optimize_unconstrained(self.max_iterations, &x0, &engine);
+
+        let mut x_star = res.x_star().clone();
+        for (i, x) in x_star.iter_mut().enumerate() {
+            let (lower, upper) = self.bounds[i % num_dofs];
+            *x = x.clamp(lower, upper);
+        }
+
+        TrajectoryOptimizationOutput {
+            path: objective.sample_states(&x_star),
+            control_points: objective.control_points(&x_star),
+            cost: objective.cost(&x_star),
+            initial_cost
+        }
+    }
+
+    /// Optimizes a trajectory initialized with the straight motion from `start` to `goal_state`
+    /// that has to end at `goal_state`.
+    pub fn optimize_straight_line(&self, start: &V, goal_state: &V) -> TrajectoryOptimizationOutput {
+        self.optimize(&vec![start.clone(), goal_state.clone()], &TrajectoryGoal::State(goal_state.clone()))
+    }
+
+    /// Refinement stage after sampling-based planning: optimizes a trajectory initialized with
+    /// `path` that has the same start and end.  Returns the optimized trajectory if it is
+    /// feasible according to `feasibility_checker`, and `path` otherwise.
+    pub fn refine<F: FeasibilityCheckerTrait>(&self, path: &Vec<V>, feasibility_checker: &F) -> Vec<V> {
+        let out = self.optimize(path, &TrajectoryGoal::State(path.last().expect("error").clone()));
+        if is_feasible_path(feasibility_checker, &out.path) { out.path } else { path.clone() }
+    }
+
+    fn initial_control_points(&self, path: &Vec<V>) -> Vec<V> {
+        let length = path_length(path);
+        (0..self.num_control_points).map(|i| {
+            interpolate_path_by_arclength(path, length * i as f64 / (self.num_control_points - 1) as f64).1
+        }).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TrajectoryOptimizationOutput {
+    /// the states at which costs were evaluated, from start to end.  These are the control
+    /// points themselves with `TrajectoryRepresentation::Waypoints`.
+    pub path: Vec<V>,
+    pub control_points: Vec<V>,
+    pub cost: f64,
+    pub initial_cost: f64
+}
+
+/// The objective minimized by `TrajectoryOptimizer` as a function of the free control points
+/// stacked into one vector.  With sampled states `s_0, ..., s_{n-1}` (`basis` times the control
+/// points), it is the sum of
+/// - `velocity * sum_i ||s_{i+1} - s_i||^2`,
+/// - `acceleration * sum_i ||s_{i+1} - 2 s_i + s_{i-1}||^2`,
+/// - `collision * sum_i c(clearance(s_i))`, where `c` is CHOMP's obstacle cost: zero above
+///   `collision_margin`, quadratic between zero and `collision_margin`, and linear in the
+///   penetration depth below zero,
+/// - `joint_limits * sum` of squared bound violations of the control points,
+/// - `goal * goal_cost(s_{n-1})` if the goal is a `TrajectoryGoal::Cost`.
+pub struct TrajectoryObjective {
+    pub start: V,
+    pub goal: TrajectoryGoal,
+    /// maps control points to sampled states: row `i` holds the weights of all control points
+    /// in sampled state `i`
+    pub basis: M,
+    pub num_dofs: usize,
+    pub bounds: Vec<(f64, f64)>,
+    pub clearance_function: Option<Arc<dyn Fn(&V) -> f64 + Send + Sync>>,
+    pub collision_margin: f64,
+    pub weights: TrajectoryCostWeights
+}
+impl TrajectoryObjective {
+    /// The number of control points that are decision variables, i.e., all but the start and
+    /// (if it is fixed) the goal.
+    pub fn num_free_control_points(&self) -> usize {
+        match &self.goal {
+            TrajectoryGoal::State(_) => { self.basis.ncols() - 2 }
+            TrajectoryGoal::Cost(_) => { self.basis.ncols() - 1 }
+        }
+    }
+
+    /// All control points, including the fixed ones, given the free control points `x`.
+    pub fn control_points(&self, x: &V) -> Vec<V> {
+        let mut out = vec![self.start.clone()];
+        for i in 0..self.num_free_control_points() {
+            out.push(V::from_column_slice(&x.as_slice()[i * self.num_dofs..(i + 1) * self.num_dofs]));
+        }
+        if let TrajectoryGoal::State(goal_state) = &self.goal { out.push(goal_state.clone()); }
+
+        out
+    }
+
+    /// The sampled states of the trajectory given the free control points `x`.
+    pub fn sample_states(&self, x: &V) -> Vec<V> {
+        let control_points = self.control_points(x);
+        let samples = &self.basis * M::from_fn(control_points.len(), self.num_dofs, |i, j| control_points[i][j]);
+
+        (0..samples.nrows()).map(|i| V::from_iterator(self.num_dofs, samples.row(i).iter().cloned())).collect()
+    }
+
+    pub fn cost(&self, x: &V) -> f64 {
+        self.evaluate(x, false).0
+    }
+
+    /// Returns the cost at `x` and its gradient.  The smoothness and joint limit terms are
+    /// differentiated analytically.  Each sampled state only affects its own collision and goal
+    /// costs, so those are differentiated with central finite differences per sampled state.
+    pub fn cost_and_gradient(&self, x: &V) -> (f64, V) {
+        let (cost, gradient) = self.evaluate(x, true);
+        (cost, gradient.expect("error"))
+    }
+
+    fn evaluate(&self, x: &V, compute_gradient: bool) -> (f64, Option<V>) {
+        let d = self.num_dofs;
+        let control_points = self.control_points(x);
+        let samples = self.sample_states(x);
+        let n = samples.len();
+        let mut cost = 0.0;
+        let mut sample_gradients = vec![V::zeros(d); n];
+
+        for i in 0..n - 1 {
+            let diff = &samples[i + 1] - &samples[i];
+            cost += self.weights.velocity * diff.norm_squared();
+            if compute_gradient {
+                sample_gradients[i + 1] += &diff * (2.0 * self.weights.velocity);
+                sample_gradients[i] -= &diff * (2.0 * self.weights.velocity);
+            }
+        }
+
+        for i in 1..n - 1 {
+            let diff = &samples[i + 1] - &samples[i] * 2.0 + &samples[i - 1];
+            cost += self.weights.acceleration * diff.norm_squared();
+            if compute_gradient {
+                sample_gradients[i + 1] += &diff * (2.0 * self.weights.acceleration);
+                sample_gradients[i] -= &diff * (4.0 * self.weights.acceleration);
+                sample_gradients[i - 1] += &diff * (2.0 * self.weights.acceleration);
+            }
+        }
+
+        if let Some(clearance_function) = &self.clearance_function {
+            let obstacle_cost = |state: &V| self.obstacle_cost(clearance_function(state));
+            for (i, sample) in samples.iter().enumerate() {
+                let c = obstacle_cost(sample);
+                if c <= 0.0 { continue; }
+                cost += self.weights.collision * c;
+                if compute_gradient && !self.is_fixed_sample(i, n) {
+                    sample_gradients[i] += central_difference(&obstacle_cost, sample) * self.weights.collision;
+                }
+            }
+        }
+
+        if let TrajectoryGoal::Cost(goal_cost) = &self.goal {
+            let last = samples.last().expect("error");
+            cost += self.weights.goal * goal_cost(last);
+            if compute_gradient {
+                sample_gradients[n - 1] += central_difference(&|state: &V| goal_cost(state), last) * self.weights.goal;
+            }
+        }
+
+        let mut control_point_gradients = if compute_gradient {
+            let g = self.basis.transpose() * M::from_fn(n, d, |i, j| sample_gradients[i][j]);
+            Some(g)
+        } else { None };
+
+        for (i, control_point) in control_points.iter().enumerate() {
+            for j in 0..d {
+                let (lower, upper) = self.bounds[j];
+                let violation = if control_point[j] < lower { control_point[j] - lower } else if control_point[j] > upper { control_point[j] - upper } else { 0.0 };
+                cost += self.weights.joint_limits * violation * violation;
+                if let Some(g) = &mut control_point_gradients { g[(i, j)] += 2.0 * self.weights.joint_limits * violation; }
+            }
+        }
+
+        // the free control points are rows 1..=num_free of the control point gradient.
+        let gradient = control_point_gradients.map(|g| V::from_fn(x.len(), |k, _| g[(k / d + 1, k % d)]));
+
+        (cost, gradient)
+    }
+
+    fn obstacle_cost(&self, clearance: f64) -> f64 {
+        let epsilon = self.collision_margin;
+        if clearance < 0.0 { -clearance + 0.5 * epsilon } else if clearance < epsilon { (clearance - epsilon).powi(2) / (2.0 * epsilon) } else { 0.0 }
+    }
+
+    /// Sampled states that only depend on fixed control points (the basis is clamped, so these
+    /// are exactly the start and, if it is fixed, the goal).
+    fn is_fixed_sample(&self, i: usize, n: usize) -> bool {
+        i == 0 || (i == n - 1 && matches!(self.goal, TrajectoryGoal::State(_)))
+    }
+}
+impl FunctionNalgebraTrait for TrajectoryObjective {
+    fn call_raw(&self, x: &V) -> V {
+        V::from_element(1, self.cost(x))
+    }
+
+    fn input_dim(&self) -> usize {
+        self.num_free_control_points() * self.num_dofs
+    }
+
+    fn output_dim(&self) -> usize {
+        1
+    }
+}
+
+/// Derivative method that returns `TrajectoryObjective::cost_and_gradient` of its objective,
+/// which is much cheaper than finite differences over all decision variables.  The function
+/// passed to `derivative` is ignored, so this must only be used in a `FunctionEngine` together
+/// with the same objective.
+pub struct TrajectoryObjectiveDerivative {
+    pub objective: Arc<TrajectoryObjective>
+}
+impl TrajectoryObjectiveDerivative {
+    pub fn new(objective: Arc<TrajectoryObjective>) -> Self {
+        Self { objective }
+    }
+}
+impl DerivativeMethodNalgebraTrait for TrajectoryObjectiveDerivative {
+    fn derivative(&self, _f: &Arc<dyn FunctionNalgebraTrait>, x: &V) -> (V, M) {
+        let (cost, gradient) = self.objective.cost_and_gradient(x);
+        (V::from_element(1, cost), M::from_row_slice(1, gradient.len(), gradient.as_slice()))
+    }
+}
+
+/// The matrix that maps the control points (rows) of `representation` to the sampled states.
+pub fn basis_matrix(representation: TrajectoryRepresentation, num_control_points: usize) -> M {
+    let m = num_control_points;
+    match representation {
+        TrajectoryRepresentation::Waypoints => { M::identity(m, m) }
+        TrajectoryRepresentation::BSpline { order, num_samples } => {
+            // a spline through unit vectors evaluates to the basis function values.
+            let unit_vectors = (0..m).map(|i| {
+                let mut out = V::zeros(m);
+                out[i] = 1.0;
+                out
+            }).collect();
+            let rows = BSpline::new(unit_vectors, order, true, true).interpolate_points_by_num_points(num_samples);
+            let mut out = M::from_fn(num_samples, m, |i, j| rows[i][j]);
+            // the spline is clamped, so this only removes floating point error at the ends.
+            for j in 0..m {
+                out[(0, j)] = if j == 0 { 1.0 } else { 0.0 };
+                out[(num_samples - 1, j)] = if j == m - 1 { 1.0 } else { 0.0 };
+            }
+
+            out
+        }
+    }
+}
+
+fn central_difference<F: Fn(&V) -> f64>(f: &F, x: &V) -> V {
+    let h = 1e-6;
+    V::from_fn(x.len(), |i, _| {
+        let mut xp = x.clone();
+        let mut xm = x.clone();
+        xp[i] += h;
+        xm[i] -= h;
+        (f(&xp) - f(&xm)) / (2.0 * h)
+    })
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use std::sync::Arc;
+use apollo_rust_continuous_planning::cartesian_planning::{CartesianPathFailure, CartesianPathPlanner};
+use apollo_rust_continuous_planning::feasibility_checkers::FeasibilityCheckerTrait;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_robotics_core::ChainNalgebra;
+use common::{ur5_chain, AlwaysFeasible};
+
+/// Rejects states where the given link is below a height.
+struct MinimumHeight {
+    chain: Arc<ChainNalgebra>,
+    link_idx: usize,
+    height: f64
+}
+impl FeasibilityCheckerTrait for MinimumHeight {
+    fn is_feasible_state(&self, state: &V) -> bool {
+        self.chain.fk(state)[self.link_idx].0.translation.vector.z >= self.height
+    }
+}
+
+#[test]
+fn test_ur5_cartesian_path() {
+    let chain = ur5_chain();
+    let link_idx = chain.urdf_module().links.iter().position(|x| x.name == "ee_link").expect("error");
+    let start_state = V::new(&[0.3, -1.0, 1.6, -2.0, -1.57, 0.4]);
+    let start_pose = chain.fk(&start_state)[link_idx].clone();
+
+    // move the end effector 10 cm straight down.
+    let mut goal_pose = start_pose.clone();
+    goal_pose.0.translation.vector.z -= 0.1;
+
+    let planner = CartesianPathPlanner::new(chain.clone(), link_idx, 0.005, 0.05, 0.2, 0.01);
+    let out = planner.plan(&start_state, &vec![goal_pose.clone()], &AlwaysFeasible);
+    assert!(out.is_success());
+    assert_eq!(out.fraction, 1.0);
+    assert_eq!(out.path.len(), out.poses.len() + 1);
+    assert_eq!(out.path[0], start_state);
+
+    for (state, pose) in out.path[1..].iter().zip(out.poses.iter()) {
+        // the interpolated poses are on the straight line.
+        assert!((pose.0.translation.vector.xy() - start_pose.0.translation.vector.xy()).norm() < 1e-6);
+        let reached = chain.fk(state)[link_idx].clone();
+        assert!((reached.0.translation.vector - pose.0.translation.vector).norm() < planner.ik_position_tolerance);
+        assert!(reached.0.rotation.angle_to(&pose.0.rotation) < planner.ik_rotation_tolerance);
+    }
+    assert_eq!(out.poses.last().unwrap(), &goal_pose);
+
+    // every step is a joint-space jump under a tiny threshold.
+    let strict = CartesianPathPlanner::new(chain.clone(), link_idx, 0.005, 0.05, 1e-6, 0.01);
+    let out = strict.plan(&start_state, &vec![goal_pose.clone()], &AlwaysFeasible);
+    assert_eq!(out.failure, Some(CartesianPathFailure::JointSpaceJump));
+    assert_eq!(out.fraction, 0.0);
+    assert_eq!(out.path.len(), 1);
+
+    // the path stops where the end effector gets too low.
+    let checker = MinimumHeight { chain: chain.clone(), link_idx, height: start_pose.0.translation.vector.z - 0.05 };
+    let out = planner.plan(&start_state, &vec![goal_pose], &checker);
+    assert_eq!(out.failure, Some(CartesianPathFailure::InfeasibleMotion));
+    assert!(out.fraction > 0.4 && out.fraction < 0.6);
+    assert!(out.path.iter().all(|x| checker.is_feasible_state(x)));
+}
@@ -0,0 +1,32 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use apollo_rust_continuous_planning::feasibility_checkers::FeasibilityCheckerTrait;
+use apollo_rust_linalg::V;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::ChainNalgebra;
+
+/// The ur5 chain from the bundled `ur5_urdd` directory.
+pub fn ur5_chain() -> Arc<ChainNalgebra> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    Arc::new(path.to_chain(ResourcesType::Robot))
+}
+
+/// A 2D unit square with a wall at x = 0.5 that only has a gap for 0.8 < y < 0.9.
+pub struct WallWithGap;
+impl FeasibilityCheckerTrait for WallWithGap {
+    fn is_feasible_state(&self, state: &V) -> bool {
+        if state[0] < 0.0 || state[0] > 1.0 || state[1] < 0.0 || state[1] > 1.0 { return false; }
+        if (state[0] - 0.5).abs() < 0.05 && !(state[1] > 0.8 && state[1] < 0.9) { return false; }
+        true
+    }
+}
+
+/// Accepts every state.
+pub struct AlwaysFeasible;
+impl FeasibilityCheckerTrait for AlwaysFeasible {
+    fn is_feasible_state(&self, _state: &V) -> bool { true }
+}
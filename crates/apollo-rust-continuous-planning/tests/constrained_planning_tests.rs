@@ -0,0 +1,101 @@
+mod common;
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+use apollo_rust_continuous_planning::constraints::{project_onto_constraint, ConstraintTrait, FunctionConstraint};
+use apollo_rust_continuous_planning::constraints::robot_constraints::{TaskSpaceRegion, TaskSpaceRegionConstraint};
+use apollo_rust_continuous_planning::feasibility_checkers::FeasibilityCheckerTrait;
+use apollo_rust_continuous_planning::path_processing::is_feasible_path;
+use apollo_rust_continuous_planning::planners::PlannerTrait;
+use apollo_rust_continuous_planning::planners::constrained_rrt_connect::ConstrainedRRTConnect;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_spatial::isometry3::{ApolloIsometry3Trait, I3};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use common::{ur5_chain, AlwaysFeasible};
+
+/// A 3D point that has to cross a band around the equator of the unit sphere where it only
+/// has a gap for x > 0.9.
+struct EquatorBand;
+impl FeasibilityCheckerTrait for EquatorBand {
+    fn is_feasible_state(&self, state: &V) -> bool {
+        !(state[2].abs() < 0.1 && state[0] < 0.9)
+    }
+}
+
+fn unit_sphere() -> FunctionConstraint {
+    FunctionConstraint::new(Arc::new(|x: &V| V::new(&[x.norm() - 1.0])))
+}
+
+#[test]
+fn test_constrained_rrt_connect_on_sphere() {
+    let bounds = vec![(-1.5, 1.5); 3];
+    let constraint = unit_sphere();
+
+    for i in 0..20 {
+        let state = V::new(&[0.3 * i as f64 - 2.9, 0.7, -0.4]);
+        let projected = project_onto_constraint(&constraint, &state, &bounds, 1e-6, 50).expect("error");
+        assert!((projected.norm() - 1.0).abs() <= 1e-6);
+    }
+
+    let start = V::new(&[0.0, 0.0, 1.0]);
+    let goal = V::new(&[0.0, 0.0, -1.0]);
+    let planner = ConstrainedRRTConnect::new(bounds, constraint.clone(), 0.05, 0.05, 1e-6, 50, Some(0), Duration::from_secs(30), 100_000);
+    let path = planner.plan(&start, &goal, &EquatorBand).path.expect("no path found");
+
+    assert_eq!(path[0], start);
+    assert_eq!(path.last().unwrap(), &goal);
+    assert!(path.iter().all(|x| constraint.is_satisfied(x, 1e-6)));
+    assert!(is_feasible_path(&EquatorBand, &path));
+    // every path crosses the equator through the gap.
+    assert!(path.windows(2).any(|x| x[0][2] * x[1][2] <= 0.0 && x[0][0] > 0.9));
+}
+
+#[test]
+fn test_task_space_region() {
+    let free = (-PI, PI);
+    let unbounded = (-f64::INFINITY, f64::INFINITY);
+    let pose = |translation: &[f64], euler_angles: &[f64]| ISE3q::new(I3::from_slices_euler_angles(translation, euler_angles));
+
+    // the end effector stays in the xy-plane of the region, which is 1 m up.
+    let plane = TaskSpaceRegion::new(pose(&[0.0, 0.0, 1.0], &[0.0, 0.0, 0.0]), ISE3q::identity(), [unbounded, unbounded, (0.0, 0.0), free, free, free]);
+    assert!(plane.contains(&pose(&[3.0, -2.0, 1.0], &[0.3, 0.2, 0.1]), 1e-9));
+    assert!((plane.violation(&pose(&[3.0, -2.0, 1.2], &[0.0, 0.0, 0.0])) - V::new(&[0.0, 0.0, 0.2, 0.0, 0.0, 0.0])).norm() < 1e-9);
+
+    // a held cup stays upright and can turn about its vertical axis.
+    let upright = TaskSpaceRegion::new(ISE3q::identity(), ISE3q::identity(), [unbounded, unbounded, unbounded, (0.0, 0.0), (0.0, 0.0), free]);
+    assert!(upright.contains(&pose(&[0.5, 0.5, 0.5], &[0.0, 0.0, 2.0]), 1e-9));
+    assert!((upright.violation(&pose(&[0.0, 0.0, 0.0], &[0.3, 0.0, 0.0])) - V::new(&[0.0, 0.0, 0.0, 0.3, 0.0, 0.0])).norm() < 1e-9);
+    assert!((upright.violation(&pose(&[0.0, 0.0, 0.0], &[-0.3, 0.0, 0.0])) - V::new(&[0.0, 0.0, 0.0, -0.3, 0.0, 0.0])).norm() < 1e-9);
+
+    // the grasp offset is applied before the region is checked.
+    let offset = TaskSpaceRegion::new(ISE3q::identity(), pose(&[0.0, 0.0, 0.1], &[0.0, 0.0, 0.0]), [unbounded, unbounded, (0.0, 0.0), free, free, free]);
+    assert!(offset.contains(&pose(&[0.0, 0.0, -0.1], &[0.0, 0.0, 0.0]), 1e-9));
+}
+
+#[test]
+fn test_ur5_task_space_region_planning() {
+    let chain = ur5_chain();
+    let link_idx = chain.urdf_module().links.iter().position(|x| x.name == "ee_link").expect("error");
+    let bounds = chain.bounds_module().bounds.clone();
+
+    // keep the end effector in the plane through its start pose that is normal to its z axis,
+    // with its start orientation up to rotations about that axis.
+    let start = V::new(&[0.3, -1.0, 1.6, -2.0, -1.57, 0.4]);
+    let start_pose = chain.fk(&start)[link_idx].clone();
+    let unbounded = (-f64::INFINITY, f64::INFINITY);
+    let region = TaskSpaceRegion::new(start_pose.clone(), ISE3q::identity(), [unbounded, unbounded, (0.0, 0.0), (0.0, 0.0), (0.0, 0.0), (-PI, PI)]);
+    let constraint = TaskSpaceRegionConstraint::new(chain.clone(), link_idx, region.clone());
+    assert!(constraint.is_satisfied(&start, 1e-9));
+
+    let mut goal = start.clone();
+    goal[0] += 1.0;
+    let goal = project_onto_constraint(&constraint, &goal, &bounds, 1e-6, 100).expect("error");
+    assert!(region.contains(&chain.fk(&goal)[link_idx].clone(), 1e-6));
+
+    let planner = ConstrainedRRTConnect::new(bounds, constraint.clone(), 0.05, 0.1, 1e-6, 100, Some(0), Duration::from_secs(60), 10_000);
+    let path = planner.plan(&start, &goal, &AlwaysFeasible).path.expect("no path found");
+    assert_eq!(path[0], start);
+    assert_eq!(path.last().unwrap(), &goal);
+    assert!(path.iter().all(|x| constraint.is_satisfied(x, 1e-6)));
+}
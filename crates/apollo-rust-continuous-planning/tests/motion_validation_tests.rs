@@ -0,0 +1,71 @@
+mod common;
+
+use apollo_rust_continuous_planning::feasibility_checkers::{FeasibilityCheckerTrait, MotionResolution, MotionValidationMode};
+use apollo_rust_continuous_planning::feasibility_checkers::robot_feasibility_checkers::{RobotCollisionCheckerTrait, RobotMotionBound, RobotNaiveFeasibilityChecker};
+use apollo_rust_linalg::V;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use common::ur5_chain;
+
+#[test]
+fn test_ur5_motion_bound() {
+    let chain = ur5_chain();
+    let motion_bound = RobotMotionBound::new(&chain);
+    assert_eq!(motion_bound.dof_reaches.len(), chain.num_dofs());
+
+    for _ in 0..50 {
+        let start = chain.sample_random_state();
+        let end = chain.sample_random_state();
+        let start_poses = chain.fk(&start);
+        for i in 1..=20 {
+            let state = &start + (&end - &start) * (i as f64 / 20.0);
+            let bound = motion_bound.displacement_bound(&start, &state);
+            chain.fk(&state).iter().zip(start_poses.iter()).for_each(|(x, y)| {
+                assert!((x.0.translation.vector - y.0.translation.vector).norm() <= bound + 1e-9);
+            });
+        }
+    }
+}
+
+#[test]
+fn test_ur5_motion_validation_modes() {
+    let chain = ur5_chain();
+    let checker = RobotNaiveFeasibilityChecker::new(chain.clone(), LinkShapeMode::Full, LinkShapeRep::ConvexHull);
+    let with_mode = |mode: MotionValidationMode| {
+        let mut out = checker.clone();
+        out.set_motion_validation_mode(mode);
+        out
+    };
+    let discretized = with_mode(MotionValidationMode::Discretized(MotionResolution::JointSpace(0.01)));
+    let bisection = with_mode(MotionValidationMode::Bisection(MotionResolution::JointSpace(0.01)));
+    let workspace = with_mode(MotionValidationMode::Bisection(MotionResolution::Workspace(0.01)));
+    let continuous = with_mode(MotionValidationMode::Continuous { tolerance: 0.001 });
+
+    let sample_feasible_state = || {
+        loop {
+            let state: V = chain.sample_random_state();
+            if checker.is_feasible_state(&state) { return state; }
+        }
+    };
+
+    for _ in 0..30 {
+        let start = sample_feasible_state();
+        let end = sample_feasible_state();
+
+        // both orders check exactly the same states.
+        let res = discretized.is_feasible_motion(&start, &end);
+        assert_eq!(res, bisection.is_feasible_motion(&start, &end));
+
+        // continuous checking never accepts a motion that passes through a collision.
+        if continuous.is_feasible_motion(&start, &end) {
+            assert!(res);
+            assert!(workspace.is_feasible_motion(&start, &end));
+        }
+    }
+
+    // a motion out of the joint bounds is infeasible in every mode.
+    let mut out_of_bounds = chain.zeros_state();
+    out_of_bounds[0] = chain.bounds_module().bounds[0].1 + 1.0;
+    for c in [&discretized, &bisection, &workspace, &continuous] {
+        assert!(!c.is_feasible_motion(&chain.zeros_state(), &out_of_bounds));
+    }
+}
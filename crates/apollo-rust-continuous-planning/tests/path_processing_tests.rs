@@ -0,0 +1,48 @@
+mod common;
+
+use std::time::Duration;
+use apollo_rust_continuous_planning::path_processing::{bspline_smoothing, interpolate_path_by_arclength, is_feasible_path, partial_shortcutting, random_shortcutting, resample_path};
+use apollo_rust_continuous_planning::planners::{path_length, PlannerTrait};
+use apollo_rust_continuous_planning::planners::rrt_connect::RRTConnect;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use common::WallWithGap;
+
+#[test]
+fn test_path_utilities() {
+    let path = vec![V::new(&[0.0, 0.0]), V::new(&[1.0, 0.0]), V::new(&[1.0, 1.0])];
+
+    let (i, x) = interpolate_path_by_arclength(&path, 1.5);
+    assert_eq!(i, 1);
+    assert!((x - V::new(&[1.0, 0.5])).norm() < 1e-12);
+    assert_eq!(interpolate_path_by_arclength(&path, 10.0).1, path[2]);
+
+    let resampled = resample_path(&path, 0.3);
+    assert_eq!(resampled.len(), 9);
+    assert!((path_length(&resampled) - 2.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_shortcutting_and_smoothing() {
+    let start = V::new(&[0.1, 0.1]);
+    let goal = V::new(&[0.9, 0.1]);
+    let planner = RRTConnect::new(vec![(0.0, 1.0); 2], 0.05, 0.05, Some(0), Duration::from_secs(10), 100_000);
+    let path = planner.plan(&start, &goal, &WallWithGap).path.expect("no path found");
+    let length = path_length(&path);
+
+    let shortcut = random_shortcutting(&path, &WallWithGap, 200, Some(0));
+    assert!(is_feasible_path(&WallWithGap, &shortcut));
+    assert_eq!(shortcut[0], start);
+    assert_eq!(shortcut.last().unwrap(), &goal);
+    assert!(path_length(&shortcut) < length);
+
+    let partial = partial_shortcutting(&shortcut, &WallWithGap, 200, Some(0));
+    assert!(is_feasible_path(&WallWithGap, &partial));
+    assert_eq!(partial[0], start);
+    assert_eq!(partial.last().unwrap(), &goal);
+    assert!(path_length(&partial) <= path_length(&shortcut));
+
+    let smoothed = bspline_smoothing(&partial, &WallWithGap, 4, 0.05, 0.01, 10);
+    assert!(is_feasible_path(&WallWithGap, &smoothed));
+    assert_eq!(smoothed[0], start);
+    assert_eq!(smoothed.last().unwrap(), &goal);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::time::Duration;
+use apollo_rust_continuous_planning::feasibility_checkers::{is_feasible_motion_bisection, is_feasible_motion_discretized, num_joint_space_motion_steps, FeasibilityCheckerTrait};
+use apollo_rust_continuous_planning::planners::{path_length, PlannerTrait};
+use apollo_rust_continuous_planning::planners::bit_star::BITStar;
+use apollo_rust_continuous_planning::planners::cost_functions::{PathCostTrait, WeightedJointDistanceCost};
+use apollo_rust_continuous_planning::planners::prm::{Roadmap, PRM};
+use apollo_rust_continuous_planning::planners::rrt_connect::RRTConnect;
+use apollo_rust_continuous_planning::planners::rrt_star::RRTStar;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_modules::{ResourcesSubDirectory, ResourcesType};
+use common::WallWithGap;
+
+fn check_path(path: &Vec<V>, start: &V, goal: &V) {
+    assert_eq!(&path[0], start);
+    assert_eq!(path.last().unwrap(), goal);
+    path.windows(2).for_each(|x| assert!(is_feasible_motion_discretized(&WallWithGap, &x[0], &x[1], num_joint_space_motion_steps(&x[0], &x[1], 0.001))));
+}
+
+#[test]
+fn test_motion_validation() {
+    let start = V::new(&[0.1, 0.1]);
+    let through_wall = V::new(&[0.9, 0.1]);
+    let through_gap = V::new(&[0.9, 0.95]);
+    let num_steps = num_joint_space_motion_steps(&start, &through_wall, 0.01);
+
+    assert!(!WallWithGap.is_feasible_motion(&start, &through_wall));
+    assert!(!is_feasible_motion_discretized(&WallWithGap, &start, &through_wall, num_steps));
+    assert!(!is_feasible_motion_bisection(&WallWithGap, &start, &through_wall, num_steps));
+
+    // the straight line from start crosses x = 0.5 at y = 0.525, outside of the gap.
+    assert!(!WallWithGap.is_feasible_motion(&start, &through_gap));
+    let above = V::new(&[0.1, 0.85]);
+    assert!(WallWithGap.is_feasible_motion(&above, &V::new(&[0.9, 0.85])));
+    assert!(is_feasible_motion_bisection(&WallWithGap, &above, &V::new(&[0.9, 0.85]), 1000));
+
+    // too coarse a resolution steps over the wall.
+    assert!(is_feasible_motion_discretized(&WallWithGap, &start, &through_wall, 1));
+}
+
+#[test]
+fn test_rrt_connect() {
+    let start = V::new(&[0.1, 0.1]);
+    let goal = V::new(&[0.9, 0.1]);
+    let planner = RRTConnect::new(vec![(0.0, 1.0); 2], 0.05, 0.05, Some(0), Duration::from_secs(10), 100_000);
+
+    let res = planner.plan(&start, &goal, &WallWithGap);
+    let path = res.path.expect("no path found");
+    check_path(&path, &start, &goal);
+    assert!(path.iter().any(|x| x[1] > 0.8));
+
+    // the same seed gives the same path.
+    let res2 = planner.plan(&start, &goal, &WallWithGap);
+    assert_eq!(Some(path), res2.path);
+
+    // an infeasible goal fails immediately.
+    assert!(!planner.plan(&start, &V::new(&[0.5, 0.5]), &WallWithGap).is_success());
+}
+
+#[test]
+fn test_lazy_prm_multi_query_and_persistence() {
+    let prm = PRM::new(vec![(0.0, 1.0); 2], 1000, 10, true, Some(0), Duration::from_secs(10));
+    let mut roadmap = prm.build_roadmap(&WallWithGap);
+    assert_eq!(roadmap.num_nodes(), 1000);
+
+    let queries = vec![
+        (V::new(&[0.1, 0.1]), V::new(&[0.9, 0.1])),
+        (V::new(&[0.2, 0.5]), V::new(&[0.8, 0.3])),
+        (V::new(&[0.9, 0.9]), V::new(&[0.1, 0.2])),
+    ];
+    for (start, goal) in &queries {
+        let res = roadmap.query(start, goal, &WallWithGap, Duration::from_secs(10));
+        check_path(&res.path.expect("no path found"), start, goal);
+    }
+
+    let directory = std::env::temp_dir().join("apollo_rust_continuous_planning_prm_test");
+    let s = ResourcesSubDirectory {
+        name: "test".to_string(),
+        root_directory: directory.clone(),
+        directory: directory.clone(),
+        resources_type: ResourcesType::Robot,
+    };
+    roadmap.save(&s, "wall_with_gap");
+    let mut loaded = Roadmap::load(&s, "wall_with_gap").expect("error");
+    assert_eq!(loaded.nodes, roadmap.nodes);
+
+    let (start, goal) = &queries[0];
+    check_path(&loaded.query(start, goal, &WallWithGap, Duration::from_secs(10)).path.expect("no path found"), start, goal);
+
+    // the saved roadmap is reused for the same inputs and rebuilt for other ones.
+    assert_eq!(Roadmap::load_or_build(&s, "wall_with_gap", &prm, &WallWithGap, false).inputs_hash, roadmap.inputs_hash);
+    let other_prm = PRM { num_neighbors: 5, ..prm.clone() };
+    let rebuilt = Roadmap::load_or_build(&s, "wall_with_gap", &other_prm, &WallWithGap, false);
+    assert_eq!(rebuilt.num_neighbors, 5);
+    assert_eq!(rebuilt.inputs_hash, other_prm.inputs_hash(&WallWithGap));
+    assert_ne!(rebuilt.inputs_hash, prm.inputs_hash(&WallWithGap));
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_rrt_star_and_bit_star() {
+    let start = V::new(&[0.1, 0.1]);
+    let goal = V::new(&[0.9, 0.1]);
+    let cost = WeightedJointDistanceCost::new_unweighted(2);
+    // the shortest path goes through the corner of the gap at (0.5, 0.8).
+    let lower_bound = 2.0 * (0.4f64 * 0.4 + 0.7 * 0.7).sqrt();
+
+    let rrt_star = RRTStar::new(vec![(0.0, 1.0); 2], cost.clone(), 0.1, 0.05, 1.5, Some(0), Duration::from_secs(10), 3000, false);
+    let first = RRTStar { stop_at_first_solution: true, ..rrt_star.clone() }.plan(&start, &goal, &WallWithGap);
+    let res = rrt_star.plan(&start, &goal, &WallWithGap);
+    let first_path = first.path.expect("no path found");
+    let path = res.path.expect("no path found");
+    check_path(&path, &start, &goal);
+    assert!(cost.path_cost(&path) <= cost.path_cost(&first_path) + 1e-9);
+    assert!(cost.path_cost(&path) >= lower_bound - 1e-9);
+
+    let bit_star = BITStar::new(vec![(0.0, 1.0); 2], cost.clone(), 200, 1.5, Some(0), Duration::from_secs(10), 5, false);
+    let res = bit_star.plan(&start, &goal, &WallWithGap);
+    let path = res.path.expect("no path found");
+    check_path(&path, &start, &goal);
+    assert!((cost.path_cost(&path) - path_length(&path)).abs() < 1e-9);
+    assert!(cost.path_cost(&path) >= lower_bound - 1e-9);
+    assert!(cost.path_cost(&path) < 1.5 * lower_bound);
+}
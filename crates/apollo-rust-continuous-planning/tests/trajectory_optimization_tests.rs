@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+use apollo_rust_continuous_planning::feasibility_checkers::FeasibilityCheckerTrait;
+use apollo_rust_continuous_planning::path_processing::is_feasible_path;
+use apollo_rust_continuous_planning::planners::PlannerTrait;
+use apollo_rust_continuous_planning::planners::rrt_connect::RRTConnect;
+use apollo_rust_continuous_planning::trajectory_optimization::{TrajectoryCostWeights, TrajectoryGoal, TrajectoryOptimizer, TrajectoryRepresentation};
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_optimization::line_searches::backtracking_line_search::BacktrackingLineSearch;
+use apollo_rust_optimization::optimizers::bfgs::LBFGS;
+
+/// A 2D unit square with a disk of radius 0.2 at its center.
+struct Disk;
+impl Disk {
+    fn clearance(state: &V) -> f64 {
+        (state - V::new(&[0.5, 0.5])).norm() - 0.2
+    }
+}
+impl FeasibilityCheckerTrait for Disk {
+    fn is_feasible_state(&self, state: &V) -> bool {
+        state.iter().all(|x| *x >= 0.0 && *x <= 1.0) && Disk::clearance(state) >= 0.0
+    }
+}
+
+fn trajectory_optimizer(representation: TrajectoryRepresentation, num_control_points: usize, weights: TrajectoryCostWeights) -> TrajectoryOptimizer<LBFGS> {
+    TrajectoryOptimizer::new(LBFGS::new(Arc::new(BacktrackingLineSearch::default()), 10), representation, num_control_points, vec![(0.0, 1.0); 2], Some(Arc::new(Disk::clearance)), 0.05, weights, 500)
+}
+
+#[test]
+fn test_trajectory_optimization_around_obstacle() {
+    // the straight line passes just below the center of the disk.
+    let start = V::new(&[0.1, 0.45]);
+    let goal = V::new(&[0.9, 0.45]);
+    assert!(!is_feasible_path(&Disk, &vec![start.clone(), goal.clone()]));
+
+    for (representation, num_control_points) in [(TrajectoryRepresentation::Waypoints, 30), (TrajectoryRepresentation::BSpline { order: 4, num_samples: 50 }, 12)] {
+        let out = trajectory_optimizer(representation, num_control_points, TrajectoryCostWeights::default()).optimize_straight_line(&start, &goal);
+        assert_eq!(out.path[0], start);
+        assert_eq!(out.path.last().unwrap(), &goal);
+        assert_eq!(out.control_points.len(), num_control_points);
+        assert!(out.cost < out.initial_cost);
+        assert!(is_feasible_path(&Disk, &out.path));
+    }
+}
+
+#[test]
+fn test_trajectory_optimization_goal_cost_and_joint_limits() {
+    let start = V::new(&[0.1, 0.1]);
+    let optimizer = TrajectoryOptimizer::new(LBFGS::new(Arc::new(BacktrackingLineSearch::default()), 10), TrajectoryRepresentation::Waypoints, 10, vec![(0.0, 1.0); 2], None, 0.05, TrajectoryCostWeights::new(1.0, 1.0, 0.0, 100.0, 100.0), 500);
+
+    let target = V::new(&[0.8, 0.3]);
+    let goal_cost = {
+        let target = target.clone();
+        TrajectoryGoal::Cost(Arc::new(move |x: &V| (x - &target).norm_squared()))
+    };
+    let out = optimizer.optimize(&vec![start.clone(), start.clone()], &goal_cost);
+    assert_eq!(out.path[0], start);
+    assert!((out.path.last().unwrap() - &target).norm() < 0.05);
+
+    // a target out of bounds is only reached as far as the bounds allow.
+    let target = V::new(&[1.5, 0.5]);
+    let goal_cost = TrajectoryGoal::Cost(Arc::new(move |x: &V| (x - &target).norm_squared()));
+    let out = optimizer.optimize(&vec![start.clone(), start.clone()], &goal_cost);
+    assert!(out.path.iter().all(|x| x.iter().all(|y| *y >= 0.0 && *y <= 1.0)));
+    assert!((out.path.last().unwrap()[0] - 1.0).abs() < 0.05);
+}
+
+#[test]
+fn test_trajectory_optimization_refinement() {
+    let start = V::new(&[0.1, 0.45]);
+    let goal = V::new(&[0.9, 0.45]);
+    let planner = RRTConnect::new(vec![(0.0, 1.0); 2], 0.05, 0.05, Some(0), Duration::from_secs(10), 100_000);
+    let path = planner.plan(&start, &goal, &Disk).path.expect("no path found");
+
+    let refined = trajectory_optimizer(TrajectoryRepresentation::Waypoints, 30, TrajectoryCostWeights::default()).refine(&path, &Disk);
+    assert!(is_feasible_path(&Disk, &refined));
+    assert_eq!(refined[0], start);
+    assert_eq!(refined.last().unwrap(), &goal);
+}
@@ -1,4 +1,5 @@
 pub mod splines;
+pub mod toppra;
 
 use std::marker::PhantomData;
 use nalgebra::DVector;
@@ -0,0 +1,268 @@
+use std::sync::Arc;
+use nalgebra::DVector;
+use crate::{get_interpolation_range, InterpolatorTrait, InterpolatorTraitLite};
+
+/// Bounds used in place of infinity so that the stage linear programs stay bounded.
+const MAX_PATH_ACCELERATION: f64 = 1e10;
+const MAX_SQUARED_PATH_VELOCITY: f64 = 1e10;
+
+/// Joint torque limits for `TOPPRA`.  `inverse_dynamics` maps `(q, qd, qdd)` to the joint
+/// torques, e.g., `M(q) qdd + C(q, qd) qd + g(q)`.
+#[derive(Clone)]
+pub struct TorqueLimits {
+    pub inverse_dynamics: Arc<dyn Fn(&DVector<f64>, &DVector<f64>, &DVector<f64>) -> DVector<f64> + Send + Sync>,
+    pub limits: DVector<f64>
+}
+impl TorqueLimits {
+    pub fn new(inverse_dynamics: Arc<dyn Fn(&DVector<f64>, &DVector<f64>, &DVector<f64>) -> DVector<f64> + Send + Sync>, limits: DVector<f64>) -> Self {
+        assert!(limits.iter().all(|x| *x > 0.0));
+        Self { inverse_dynamics, limits }
+    }
+}
+
+/// Time-optimal path parameterization by reachability analysis, TOPP-RA (Pham and Pham, 2018).
+///
+/// A geometric path `q(s)`, given as any interpolator over `s` in `[0, max_t]`, is
+/// discretized into `num_grid_points` stages.  With `x = sd^2` and `u = sdd`, all joint
+/// velocity, acceleration, and torque limits are linear in `(u, x)` at each stage, so a backward
+/// pass computes the set of `x` at each stage from which the end can still be reached, and a
+/// forward pass greedily picks the largest feasible `u`.  The trajectory starts and ends at
+/// rest.
+///
+/// Unlike `TimedInterpolator`, which rescales time uniformly, the path is traversed as fast as
+/// the limits allow at every point.  Limits are enforced at the grid points, so paths with
+/// sharp corners should be smoothed first (or use a dense grid).
+#[derive(Clone)]
+pub struct TOPPRA {
+    pub velocity_limits: DVector<f64>,
+    pub acceleration_limits: DVector<f64>,
+    pub torque_limits: Option<TorqueLimits>,
+    pub num_grid_points: usize
+}
+impl TOPPRA {
+    pub fn new(velocity_limits: DVector<f64>, acceleration_limits: DVector<f64>, torque_limits: Option<TorqueLimits>, num_grid_points: usize) -> Self {
+        assert_eq!(velocity_limits.len(), acceleration_limits.len());
+        assert!(velocity_limits.iter().all(|x| *x > 0.0) && acceleration_limits.iter().all(|x| *x > 0.0));
+        assert!(num_grid_points > 2);
+
+        Self { velocity_limits, acceleration_limits, torque_limits, num_grid_points }
+    }
+
+    /// Returns the time-optimal trajectory along `path`, or `None` if the path cannot be
+    /// traversed within the limits (e.g., torque limits that cannot hold the robot still).
+    pub fn parameterize<I: InterpolatorTrait>(&self, path: &I) -> Option<TimeOptimalTrajectory<I>> {
+        let n = self.num_grid_points - 1;
+        let grid: Vec<f64> = (0..=n).map(|i| path.max_t() * i as f64 / n as f64).collect();
+        let stages: Vec<Stage> = (0..=n).map(|i| self.get_stage(path, &grid, i)).collect();
+
+        // backward pass: controllable sets, from which the path end can be reached at rest.
+        let mut controllable_sets = vec![(0.0, 0.0); n + 1];
+        for i in (0..n).rev() {
+            let delta = grid[i + 1] - grid[i];
+            let (next_min, next_max) = controllable_sets[i + 1];
+            let mut constraints = stages[i].constraints.clone();
+            constraints.push((2.0 * delta, 1.0, next_min, next_max));
+
+            let x_min = solve_stage_lp(&constraints, stages[i].max_x, false)?;
+            let x_max = solve_stage_lp(&constraints, stages[i].max_x, true)?;
+            controllable_sets[i] = (x_min.max(0.0), x_max.max(x_min).max(0.0));
+        }
+        if controllable_sets[0].0 > 0.0 { return None; }
+
+        // forward pass: the largest feasible path acceleration that stays controllable.
+        let mut xs = vec![0.0; n + 1];
+        for i in 0..n {
+            let delta = grid[i + 1] - grid[i];
+            let (next_min, next_max) = controllable_sets[i + 1];
+            let mut constraints = stages[i].constraints.clone();
+            constraints.push((2.0 * delta, 1.0, next_min, next_max));
+
+            let (u_min, u_max) = feasible_u_range(&constraints, xs[i]);
+            let u = if u_max >= u_min { u_max } else { u_min };
+            xs[i + 1] = (xs[i] + 2.0 * delta * u).clamp(next_min, next_max).max(0.0);
+        }
+
+        let mut times = vec![0.0];
+        for i in 0..n {
+            let speed_sum = xs[i].sqrt() + xs[i + 1].sqrt();
+            if speed_sum <= 0.0 { return None; }
+            times.push(times[i] + 2.0 * (grid[i + 1] - grid[i]) / speed_sum);
+        }
+
+        Some(TimeOptimalTrajectory { path: path.clone(), grid, xs, times })
+    }
+
+    fn get_stage<I: InterpolatorTrait>(&self, path: &I, grid: &Vec<f64>, i: usize) -> Stage {
+        let (q, qs, qss) = path_derivatives(path, grid, i);
+        let mut constraints = vec![];
+
+        // joint accelerations are qs * u + qss * x.
+        for j in 0..q.len() {
+            let a = self.acceleration_limits[j];
+            constraints.push((qs[j], qss[j], -a, a));
+        }
+
+        if let Some(torque_limits) = &self.torque_limits {
+            let zeros = DVector::zeros(q.len());
+            let c = (torque_limits.inverse_dynamics)(&q, &zeros, &zeros);
+            let a = (torque_limits.inverse_dynamics)(&q, &zeros, &qs) - &c;
+            let b = (torque_limits.inverse_dynamics)(&q, &qs, &qss) - &c;
+            for j in 0..q.len() {
+                let limit = torque_limits.limits[j];
+                constraints.push((a[j], b[j], -limit - c[j], limit - c[j]));
+            }
+        }
+
+        // joint velocities are qs * sd, so |qs_j| * sd <= v_j.
+        let mut max_x = MAX_SQUARED_PATH_VELOCITY;
+        for j in 0..q.len() {
+            if qs[j].abs() > 1e-12 { max_x = max_x.min((self.velocity_limits[j] / qs[j]).powi(2)); }
+        }
+
+        Stage { constraints, max_x }
+    }
+}
+
+/// A trajectory returned by `TOPPRA::parameterize`.  It implements `InterpolatorTraitLite` with
+/// time as the parameter, so it can be sampled at arbitrary times in `[0, duration]`.
+#[derive(Clone)]
+pub struct TimeOptimalTrajectory<I: InterpolatorTrait> {
+    path: I,
+    grid: Vec<f64>,
+    /// squared path velocities at the grid points
+    xs: Vec<f64>,
+    /// times at which the grid points are reached
+    times: Vec<f64>
+}
+impl<I: InterpolatorTrait> TimeOptimalTrajectory<I> {
+    pub fn duration(&self) -> f64 {
+        *self.times.last().unwrap()
+    }
+
+    /// Returns the path parameter and its first and second time derivatives at time `t`.
+    pub fn path_parameter(&self, t: f64) -> (f64, f64, f64) {
+        let t = t.clamp(0.0, self.duration());
+        let i = match self.times.iter().position(|x| *x > t) {
+            None => { self.times.len() - 2 }
+            Some(idx) => { idx.max(1) - 1 }
+        };
+
+        // the path acceleration is constant between grid points.
+        let u = (self.xs[i + 1] - self.xs[i]) / (2.0 * (self.grid[i + 1] - self.grid[i]));
+        let tau = t - self.times[i];
+        let sd = self.xs[i].sqrt();
+        let s = (self.grid[i] + sd * tau + 0.5 * u * tau * tau).clamp(self.grid[i], self.grid[i + 1]);
+
+        (s, (sd + u * tau).max(0.0), u)
+    }
+
+    /// Returns the joint velocities at time `t`.
+    pub fn velocity(&self, t: f64) -> DVector<f64> {
+        let (s, sd, _) = self.path_parameter(t);
+        path_first_derivative(&self.path, s) * sd
+    }
+
+    pub fn times(&self) -> &Vec<f64> {
+        &self.times
+    }
+
+    pub fn interpolate_points_by_time_stride(&self, time_stride: f64) -> Vec<DVector<f64>> {
+        get_interpolation_range(0.0, self.duration(), time_stride).iter().map(|t| self.interpolate(*t)).collect()
+    }
+}
+impl<I: InterpolatorTrait> InterpolatorTraitLite for TimeOptimalTrajectory<I> {
+    fn interpolate(&self, t: f64) -> DVector<f64> {
+        self.path.interpolate(self.path_parameter(t).0)
+    }
+
+    fn max_t(&self) -> f64 {
+        self.duration()
+    }
+}
+
+/// The constraints `lower <= a * u + b * x <= upper`, stored as `(a, b, lower, upper)`, at one
+/// grid point, and the largest `x` allowed by the velocity limits.
+struct Stage {
+    constraints: Vec<(f64, f64, f64, f64)>,
+    max_x: f64
+}
+
+/// Finite differences of the path over the grid, so that curvature between grid points is
+/// seen by the neighboring grid points.
+fn path_derivatives<I: InterpolatorTrait>(path: &I, grid: &Vec<f64>, i: usize) -> (DVector<f64>, DVector<f64>, DVector<f64>) {
+    let n = grid.len() - 1;
+    let (a, b) = if i == 0 { (0, 2) } else if i == n { (n - 2, n) } else { (i - 1, i + 1) };
+    let q = path.interpolate(grid[i]);
+    let qa = path.interpolate(grid[a]);
+    let qm = path.interpolate(grid[a + 1]);
+    let qb = path.interpolate(grid[b]);
+
+    let h1 = grid[a + 1] - grid[a];
+    let h2 = grid[b] - grid[a + 1];
+    let qs = if i == 0 { (&qm - &qa) / h1 } else if i == n { (&qb - &qm) / h2 } else { (&qb - &qa) / (h1 + h2) };
+    let qss = ((&qb - &qm) / h2 - (&qm - &qa) / h1) / (0.5 * (h1 + h2));
+
+    (q, qs, qss)
+}
+
+fn path_first_derivative<I: InterpolatorTrait>(path: &I, s: f64) -> DVector<f64> {
+    let h = 1e-6 * path.max_t().max(1.0);
+    let a = (s - h).max(0.0);
+    let b = (s + h).min(path.max_t());
+
+    (path.interpolate(b) - path.interpolate(a)) / (b - a)
+}
+
+/// Maximizes or minimizes `x` subject to the stage constraints, `0 <= x <= max_x`, and
+/// `|u| <= MAX_PATH_ACCELERATION`.  The feasible set is a polygon, so the optimum is at one of
+/// the intersections of two constraint boundaries.
+fn solve_stage_lp(constraints: &Vec<(f64, f64, f64, f64)>, max_x: f64, maximize: bool) -> Option<f64> {
+    let mut lines: Vec<(f64, f64, f64)> = vec![(0.0, 1.0, 0.0), (0.0, 1.0, max_x), (1.0, 0.0, -MAX_PATH_ACCELERATION), (1.0, 0.0, MAX_PATH_ACCELERATION)];
+    constraints.iter().for_each(|(a, b, lower, upper)| {
+        if lower.is_finite() { lines.push((*a, *b, *lower)); }
+        if upper.is_finite() { lines.push((*a, *b, *upper)); }
+    });
+
+    let is_feasible = |u: f64, x: f64| {
+        if x < -1e-9 || x > max_x * (1.0 + 1e-9) + 1e-9 || u.abs() > MAX_PATH_ACCELERATION * (1.0 + 1e-9) { return false; }
+        constraints.iter().all(|(a, b, lower, upper)| {
+            let value = a * u + b * x;
+            let tol = 1e-9 * (1.0 + lower.abs().max(upper.abs()).min(1e12) + (a * u).abs() + (b * x).abs());
+            value >= lower - tol && value <= upper + tol
+        })
+    };
+
+    let mut best: Option<f64> = None;
+    for i in 0..lines.len() {
+        for j in i + 1..lines.len() {
+            let (a1, b1, c1) = lines[i];
+            let (a2, b2, c2) = lines[j];
+            let det = a1 * b2 - a2 * b1;
+            if det.abs() < 1e-12 { continue; }
+            let u = (c1 * b2 - c2 * b1) / det;
+            let x = (a1 * c2 - a2 * c1) / det;
+            if !is_feasible(u, x) { continue; }
+            best = match best {
+                None => { Some(x) }
+                Some(y) => { Some(if maximize { y.max(x) } else { y.min(x) }) }
+            };
+        }
+    }
+
+    best
+}
+
+/// The interval of `u` that satisfies all stage constraints for a fixed `x`.
+fn feasible_u_range(constraints: &Vec<(f64, f64, f64, f64)>, x: f64) -> (f64, f64) {
+    let mut u_min = -MAX_PATH_ACCELERATION;
+    let mut u_max = MAX_PATH_ACCELERATION;
+    constraints.iter().for_each(|(a, b, lower, upper)| {
+        if a.abs() < 1e-12 { return; }
+        let u_lower = (lower - b * x) / a;
+        let u_upper = (upper - b * x) / a;
+        u_min = u_min.max(u_lower.min(u_upper));
+        u_max = u_max.min(u_lower.max(u_upper));
+    });
+
+    (u_min, u_max)
+}
@@ -84,3 +84,42 @@ fn test_bspline_naive_vs_fast() {
         );
     }
 }
+
+#[test]
+fn test_toppra_straight_line() {
+    use apollo_rust_interpolation::toppra::{TorqueLimits, TOPPRA};
+    use std::sync::Arc;
+
+    let points = vec![DVector::from_vec(vec![0.0]), DVector::from_vec(vec![1.0])];
+    let path = InterpolatingSpline::new(points, InterpolatingSplineType::Linear);
+
+    // bang-bang: accelerate at 1 over half the path, then decelerate, for a total of 2 seconds.
+    let toppra = TOPPRA::new(DVector::from_vec(vec![2.0]), DVector::from_vec(vec![1.0]), None, 201);
+    let trajectory = toppra.parameterize(&path).expect("error");
+    assert!((trajectory.duration() - 2.0).abs() < 0.05);
+    assert!((trajectory.interpolate(0.0)[0] - 0.0).abs() < 1e-9);
+    assert!((trajectory.interpolate(trajectory.duration())[0] - 1.0).abs() < 1e-9);
+
+    // a velocity limit of 0.5 adds a cruise phase: 0.5 + 1.5 + 0.5 seconds.
+    let toppra = TOPPRA::new(DVector::from_vec(vec![0.5]), DVector::from_vec(vec![1.0]), None, 201);
+    let trajectory = toppra.parameterize(&path).expect("error");
+    assert!((trajectory.duration() - 2.5).abs() < 0.05);
+    let num_samples = 500;
+    for i in 0..=num_samples {
+        let t = trajectory.duration() * i as f64 / num_samples as f64;
+        assert!(trajectory.velocity(t)[0] <= 0.5 + 1e-6);
+        assert!(trajectory.path_parameter(t).2.abs() <= 1.0 + 1e-6);
+    }
+
+    // gravity-like torque offset: tau = qdd + 0.5 with |tau| <= 1 limits accelerating to 0.5.
+    let inverse_dynamics = Arc::new(|_q: &DVector<f64>, _qd: &DVector<f64>, qdd: &DVector<f64>| qdd.add_scalar(0.5));
+    let torque_limits = TorqueLimits::new(inverse_dynamics, DVector::from_vec(vec![1.0]));
+    let toppra = TOPPRA::new(DVector::from_vec(vec![2.0]), DVector::from_vec(vec![1.0]), Some(torque_limits), 201);
+    let trajectory = toppra.parameterize(&path).expect("error");
+    assert!(trajectory.duration() > 2.0);
+    for i in 0..=num_samples {
+        let t = trajectory.duration() * i as f64 / num_samples as f64;
+        let u = trajectory.path_parameter(t).2;
+        assert!(u <= 0.5 + 1e-6 && u >= -1.0 - 1e-6);
+    }
+}
@@ -1,4 +1,6 @@
 use apollo_rust_file::ApolloPathBufTrait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::path::PathBuf;
 
 pub mod robot_modules;
@@ -198,3 +200,26 @@ pub enum ResourcesType {
     Robot,
     Environment,
 }
+
+/// Saves `object` into `module_dir` in the layout of preprocessed modules: a `VERSION` file next
+/// to `module.json`, `module.ron`, and `module.yaml`.
+pub fn save_versioned_module<P: ApolloPathBufTrait + Clone, T: Serialize + DeserializeOwned>(module_dir: &P, version: &str, object: &T) {
+    module_dir.clone().append("VERSION").write_string_to_file(&version.to_string());
+    module_dir.clone().append("module.json").save_object_to_json_file(object);
+    module_dir.clone().append("module.ron").save_object_to_ron_file(object);
+    module_dir.clone().append("module.yaml").save_object_to_yaml_file(object);
+}
+
+/// Loads the `module.json` of an object saved with `save_versioned_module`.  Fails if nothing
+/// was saved or if it was saved with a version other than `version`.
+pub fn load_versioned_module_from_json<P: ApolloPathBufTrait + Clone, T: Serialize + DeserializeOwned>(module_dir: &P, version: &str) -> Result<T, String> {
+    let fp = module_dir.clone().append("VERSION");
+    if !fp.path_exists() {
+        return Err("Module version does not exist".to_string());
+    }
+    let saved_version = fp.read_file_contents_to_string();
+    if saved_version != version {
+        return Err(format!("Version did not match when loading module {:?}.  saved version: {:?} vs. current version: {:?}", module_dir, saved_version, version));
+    }
+    module_dir.clone().append("module.json").load_object_from_json_file_result()
+}
@@ -1,5 +1,6 @@
 use apollo_rust_linalg::{ApolloDVectorTrait, V};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -11,6 +12,10 @@ pub struct ApolloBoundsModule {
 impl ApolloBoundsModule {
     pub fn sample_random_state(&self) -> V {
         let mut rng = rand::thread_rng();
+        self.sample_random_state_with_rng(&mut rng)
+    }
+
+    pub fn sample_random_state_with_rng<R: Rng>(&self, rng: &mut R) -> V {
         V::new(
             &self
                 .bounds
@@ -19,4 +24,11 @@ impl ApolloBoundsModule {
                 .collect::<Vec<f64>>(),
         )
     }
+
+    /// Samples `num_samples` states from an RNG seeded with `seed`, so the same seed always gives
+    /// the same states.
+    pub fn sample_random_states_from_seed(&self, num_samples: usize, seed: u64) -> Vec<V> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..num_samples).map(|_| self.sample_random_state_with_rng(&mut rng)).collect()
+    }
 }
@@ -7,16 +7,54 @@ use serde::{Deserialize, Serialize};
 /// shape descriptors or collections of them.
 ///
 /// ## Fields:
+/// - `parameters`: The fitting parameters the sphere sets were built with.
 /// - `full_obbs`: A vector of optional `OBBDescriptor` representing full oriented bounding boxes.
 /// - `full_bounding_spheres`: A vector of optional `BoundingSphereDescriptor` representing full bounding spheres.
 /// - `decomposition_obbs`: A 2D vector of `OBBDescriptor` representing decomposed OBBs.
 /// - `decomposition_bounding_spheres`: A 2D vector of `BoundingSphereDescriptor` representing decomposed bounding spheres.
+/// - `full_multi_spheres`: A vector of optional sphere sets approximating each full link shape.
+/// - `decomposition_multi_spheres`: A 2D vector of sphere sets approximating each decomposed shape.
+/// - `full_capsules`: A vector of optional `CapsuleDescriptor` enclosing each full link shape.
+/// - `decomposition_capsules`: A 2D vector of `CapsuleDescriptor` enclosing each decomposed shape.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApolloLinkShapesApproximationsModule {
+    pub parameters: ApolloLinkShapesApproximationsParameters,
     pub full_obbs: Vec<Option<OBBDescriptor>>,
     pub full_bounding_spheres: Vec<Option<BoundingSphereDescriptor>>,
     pub decomposition_obbs: Vec<Vec<OBBDescriptor>>,
     pub decomposition_bounding_spheres: Vec<Vec<BoundingSphereDescriptor>>,
+    pub full_multi_spheres: Vec<Option<Vec<BoundingSphereDescriptor>>>,
+    pub decomposition_multi_spheres: Vec<Vec<Vec<BoundingSphereDescriptor>>>,
+    pub full_capsules: Vec<Option<CapsuleDescriptor>>,
+    pub decomposition_capsules: Vec<Vec<CapsuleDescriptor>>,
+}
+
+/// # ApolloLinkShapesApproximationsParameters
+///
+/// Parameters of the sphere set fits in `ApolloLinkShapesApproximationsModule`.
+///
+/// ## Fields:
+/// - `full_multi_sphere_num_spheres`: The maximum number of spheres fit to each full link shape.
+/// - `decomposition_multi_sphere_num_spheres`: The maximum number of spheres fit to each convex decomposition piece.
+/// - `multi_sphere_tightness`: Between 0 (spheres cover the shape) and 1 (tightest fit, may leave parts uncovered).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApolloLinkShapesApproximationsParameters {
+    pub full_multi_sphere_num_spheres: usize,
+    pub decomposition_multi_sphere_num_spheres: usize,
+    pub multi_sphere_tightness: f64,
+}
+impl ApolloLinkShapesApproximationsParameters {
+    pub fn new(full_multi_sphere_num_spheres: usize, decomposition_multi_sphere_num_spheres: usize, multi_sphere_tightness: f64) -> Self {
+        assert!(full_multi_sphere_num_spheres > 0 && decomposition_multi_sphere_num_spheres > 0);
+        assert!((0.0..=1.0).contains(&multi_sphere_tightness));
+
+        Self { full_multi_sphere_num_spheres, decomposition_multi_sphere_num_spheres, multi_sphere_tightness }
+    }
+}
+impl Default for ApolloLinkShapesApproximationsParameters {
+    fn default() -> Self {
+        Self::new(8, 3, 0.0)
+    }
 }
 
 /// # OBBDescriptor
@@ -48,3 +86,20 @@ pub struct BoundingSphereDescriptor {
     pub offset_xyz: [f64; 3],
     pub offset_rpy: [f64; 3]
 }
+
+/// # CapsuleDescriptor
+///
+/// This struct describes a capsule whose segment runs along the local y axis of its offset frame.
+///
+/// ## Fields:
+/// - `radius`: A `f64` representing the radius of the capsule.
+/// - `half_height`: A `f64` representing half the length of the capsule's inner segment.
+/// - `offset_xyz`: An array of three `f64` values representing the XYZ offset of the capsule.
+/// - `offset_rpy`: An array of three `f64` values representing the roll-pitch-yaw (RPY) offset of the capsule.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CapsuleDescriptor {
+    pub radius: f64,
+    pub half_height: f64,
+    pub offset_xyz: [f64; 3],
+    pub offset_rpy: [f64; 3]
+}
@@ -9,17 +9,25 @@ use serde::{Deserialize, Serialize};
 /// - `full_convex_hulls`: Distance statistics for full convex hulls.
 /// - `full_obbs`: Distance statistics for full oriented bounding boxes (OBBs).
 /// - `full_bounding_spheres`: Distance statistics for full bounding spheres.
+/// - `full_multi_spheres`: Distance statistics for full sphere sets.
+/// - `full_capsules`: Distance statistics for full capsules.
 /// - `decomposition_convex_hulls`: Distance statistics for decomposed convex hulls.
 /// - `decomposition_obbs`: Distance statistics for decomposed OBBs.
 /// - `decomposition_bounding_spheres`: Distance statistics for decomposed bounding spheres.
+/// - `decomposition_multi_spheres`: Distance statistics for decomposed sphere sets.
+/// - `decomposition_capsules`: Distance statistics for decomposed capsules.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApolloLinkShapesDistanceStatisticsModule {
     pub full_convex_hulls: LinkShapesDistanceStatistics,
     pub full_obbs: LinkShapesDistanceStatistics,
     pub full_bounding_spheres: LinkShapesDistanceStatistics,
+    pub full_multi_spheres: LinkShapesDistanceStatistics,
+    pub full_capsules: LinkShapesDistanceStatistics,
     pub decomposition_convex_hulls: LinkShapesDistanceStatistics,
     pub decomposition_obbs: LinkShapesDistanceStatistics,
     pub decomposition_bounding_spheres: LinkShapesDistanceStatistics,
+    pub decomposition_multi_spheres: LinkShapesDistanceStatistics,
+    pub decomposition_capsules: LinkShapesDistanceStatistics,
 }
 
 /// # LinkShapesDistanceStatistics
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// # ApolloLinkShapesPaddingModule
+///
+/// This struct stores safety margins (paddings) around the links of a robot.  Proximity queries
+/// treat each link as if its shapes were inflated by its padding, so the padding of a pair of
+/// links is the sum of their individual paddings unless the pair has an explicit override.
+///
+/// ## Fields:
+/// - `link_paddings`: The padding of each link, in link indices.
+/// - `link_pair_paddings`: `(link_idx_a, link_idx_b, padding)` overrides for specific pairs of links.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApolloLinkShapesPaddingModule {
+    pub link_paddings: Vec<f64>,
+    pub link_pair_paddings: Vec<(usize, usize, f64)>
+}
+impl ApolloLinkShapesPaddingModule {
+    /// Creates a module with zero padding on all `num_links` links.
+    pub fn new_zeros(num_links: usize) -> Self {
+        Self {
+            link_paddings: vec![0.0; num_links],
+            link_pair_paddings: vec![],
+        }
+    }
+}
@@ -6,18 +6,56 @@ use serde::{Deserialize, Serialize};
 /// during processing. The flags are stored as 2D vectors of `bool` values for both full and decomposed shapes.
 ///
 /// ## Fields:
+/// - `parameters`: The sampling parameters the skips were classified with.
 /// - `full_convex_hulls_skips`: A 2D vector of `bool` flags indicating if full convex hulls should be skipped.
 /// - `full_obbs_skips`: A 2D vector of `bool` flags indicating if full oriented bounding boxes (OBBs) should be skipped.
 /// - `full_bounding_spheres_skips`: A 2D vector of `bool` flags indicating if full bounding spheres should be skipped.
+/// - `full_multi_spheres_skips`: A 2D vector of `bool` flags indicating if full sphere sets should be skipped.
+/// - `full_capsules_skips`: A 2D vector of `bool` flags indicating if full capsules should be skipped.
 /// - `decomposition_convex_hulls_skips`: A 2D vector of `bool` flags indicating if decomposed convex hulls should be skipped.
 /// - `decomposition_obbs_skips`: A 2D vector of `bool` flags indicating if decomposed OBBs should be skipped.
 /// - `decomposition_bounding_spheres_skips`: A 2D vector of `bool` flags indicating if decomposed bounding spheres should be skipped.
+/// - `decomposition_multi_spheres_skips`: A 2D vector of `bool` flags indicating if decomposed sphere sets should be skipped.
+/// - `decomposition_capsules_skips`: A 2D vector of `bool` flags indicating if decomposed capsules should be skipped.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApolloLinkShapesSkipsModule {
+    pub parameters: ApolloLinkShapesSkipsParameters,
     pub full_convex_hulls_skips: Vec<Vec<bool>>,
     pub full_obbs_skips: Vec<Vec<bool>>,
     pub full_bounding_spheres_skips: Vec<Vec<bool>>,
+    pub full_multi_spheres_skips: Vec<Vec<bool>>,
+    pub full_capsules_skips: Vec<Vec<bool>>,
     pub decomposition_convex_hulls_skips: Vec<Vec<bool>>,
     pub decomposition_obbs_skips: Vec<Vec<bool>>,
-    pub decomposition_bounding_spheres_skips: Vec<Vec<bool>>
+    pub decomposition_bounding_spheres_skips: Vec<Vec<bool>>,
+    pub decomposition_multi_spheres_skips: Vec<Vec<bool>>,
+    pub decomposition_capsules_skips: Vec<Vec<bool>>
+}
+
+/// # ApolloLinkShapesSkipsParameters
+///
+/// Parameters of the sampling used to classify pairs of link shapes in `ApolloLinkShapesSkipsModule`.
+///
+/// ## Fields:
+/// - `num_samples`: The number of random states sampled.
+/// - `always_colliding_ratio`: Pairs in collision in at least this fraction of samples are classified as always colliding.
+/// - `seed`: Seed of the RNG the states are sampled with, so rebuilding the module gives the same skips.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApolloLinkShapesSkipsParameters {
+    pub num_samples: usize,
+    pub always_colliding_ratio: f64,
+    pub seed: u64,
+}
+impl ApolloLinkShapesSkipsParameters {
+    pub fn new(num_samples: usize, always_colliding_ratio: f64, seed: u64) -> Self {
+        assert!(num_samples > 0);
+        assert!(always_colliding_ratio > 0.0 && always_colliding_ratio <= 1.0);
+
+        Self { num_samples, always_colliding_ratio, seed }
+    }
+}
+impl Default for ApolloLinkShapesSkipsParameters {
+    fn default() -> Self {
+        Self::new(5000, 0.95, 0)
+    }
 }
@@ -10,4 +10,6 @@ pub mod connections_module;
 pub mod bounds_module;
 pub mod link_shapes_modules;
 pub mod link_simulation_mode_module;
-pub mod bevy_modules;
\ No newline at end of file
+pub mod bevy_modules;
+pub mod signed_distance_field_module;
+pub mod named_groups_module;
\ No newline at end of file
@@ -0,0 +1,106 @@
+use apollo_rust_linalg::V;
+use serde::{Deserialize, Serialize};
+use crate::robot_modules::dof_module::ApolloDOFModule;
+use crate::robot_modules::urdf_module::ApolloURDFModule;
+
+/// # ApolloNamedGroupsModule
+///
+/// This struct holds named joint groups, end effectors, and named configurations of a robot,
+/// as found in the `group`, `end_effector`, and `group_state` tags of an SRDF.
+///
+/// ## Fields:
+/// - `groups`: The named groups of the robot.
+/// - `end_effectors`: The named end effectors of the robot.
+/// - `named_configurations`: The named configurations (e.g., "home") of the robot.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ApolloNamedGroupsModule {
+    pub groups: Vec<ApolloNamedGroup>,
+    pub end_effectors: Vec<ApolloNamedEndEffector>,
+    pub named_configurations: Vec<ApolloNamedConfiguration>,
+}
+impl ApolloNamedGroupsModule {
+    /// Returns the group with the given name, if any.
+    pub fn get_group(&self, name: &str) -> Option<&ApolloNamedGroup> {
+        self.groups.iter().find(|x| x.name == name)
+    }
+
+    /// Returns the end effector with the given name, if any.
+    pub fn get_end_effector(&self, name: &str) -> Option<&ApolloNamedEndEffector> {
+        self.end_effectors.iter().find(|x| x.name == name)
+    }
+
+    /// Returns the named configuration with the given name, if any.
+    pub fn get_named_configuration(&self, name: &str) -> Option<&ApolloNamedConfiguration> {
+        self.named_configurations.iter().find(|x| x.name == name)
+    }
+}
+
+/// # ApolloNamedGroup
+///
+/// A named group of the robot.  Like in an SRDF, a group is the union of its listed links,
+/// joints, kinematic chains, and subgroups.
+///
+/// ## Fields:
+/// - `name`: The name of the group.
+/// - `link_names`: Links listed directly in the group.
+/// - `joint_names`: Joints listed directly in the group.
+/// - `chains`: `(base_link, tip_link)` pairs of kinematic chains in the group.
+/// - `subgroup_names`: Names of other groups included in this group.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApolloNamedGroup {
+    pub name: String,
+    pub link_names: Vec<String>,
+    pub joint_names: Vec<String>,
+    pub chains: Vec<(String, String)>,
+    pub subgroup_names: Vec<String>,
+}
+
+/// # ApolloNamedEndEffector
+///
+/// A named end effector, i.e., a group attached to a parent link.
+///
+/// ## Fields:
+/// - `name`: The name of the end effector.
+/// - `group`: The group of links that make up the end effector.
+/// - `parent_link`: The link the end effector is attached to.
+/// - `parent_group`: The group containing the parent link, if specified.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApolloNamedEndEffector {
+    pub name: String,
+    pub group: String,
+    pub parent_link: String,
+    pub parent_group: Option<String>,
+}
+
+/// # ApolloNamedConfiguration
+///
+/// A named configuration of a group.
+///
+/// ## Fields:
+/// - `name`: The name of the configuration.
+/// - `group`: The group the configuration belongs to.
+/// - `joint_values`: `(joint_name, values)` pairs, with one value per DOF of the joint.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApolloNamedConfiguration {
+    pub name: String,
+    pub group: String,
+    pub joint_values: Vec<(String, Vec<f64>)>,
+}
+impl ApolloNamedConfiguration {
+    /// Converts the configuration into a full robot state.  DOFs of joints not listed in the
+    /// configuration keep their values from `default_state`.
+    pub fn to_state(&self, urdf_module: &ApolloURDFModule, dof_module: &ApolloDOFModule, default_state: &V) -> V {
+        assert_eq!(default_state.len(), dof_module.num_dofs);
+
+        let mut out = default_state.clone();
+        self.joint_values.iter().for_each(|(joint_name, values)| {
+            let joint_idx = urdf_module.joints.iter().position(|x| &x.name == joint_name);
+            if let Some(joint_idx) = joint_idx {
+                let dof_idxs = &dof_module.joint_idx_to_dof_idxs_mapping[joint_idx];
+                dof_idxs.iter().zip(values.iter()).for_each(|(dof_idx, value)| out[*dof_idx] = *value);
+            }
+        });
+
+        out
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// # ApolloSignedDistanceFieldModule
+///
+/// A voxelized signed distance field of the combined convex decomposition shapes of a static
+/// environment at its zero state, expressed in the environment's world frame.
+///
+/// ## Fields:
+/// - `parameters`: The resolution parameters the field was built with.
+/// - `origin`: The world position of the center of voxel `(0, 0, 0)`.
+/// - `voxel_size`: The edge length of a voxel.
+/// - `dims`: The number of voxels along x, y, and z.
+/// - `distances`: Signed distances at voxel centers (negative inside), indexed x-fastest, i.e., `i + dims[0] * (j + dims[1] * k)`.
+/// - `gradients`: Spatial gradients of the distance at voxel centers, indexed like `distances`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApolloSignedDistanceFieldModule {
+    pub parameters: ApolloSignedDistanceFieldParameters,
+    pub origin: [f64; 3],
+    pub voxel_size: f64,
+    pub dims: [usize; 3],
+    pub distances: Vec<f64>,
+    pub gradients: Vec<[f64; 3]>,
+}
+
+/// # ApolloSignedDistanceFieldParameters
+///
+/// Resolution of a signed distance field.
+///
+/// ## Fields:
+/// - `min_voxel_size`: The finest voxel size used for the field.
+/// - `max_num_voxels`: The voxel size is coarsened for large environments so that the field stays below this many voxels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApolloSignedDistanceFieldParameters {
+    pub min_voxel_size: f64,
+    pub max_num_voxels: usize,
+}
+impl ApolloSignedDistanceFieldParameters {
+    pub fn new(min_voxel_size: f64, max_num_voxels: usize) -> Self {
+        assert!(min_voxel_size > 0.0);
+        assert!(max_num_voxels > 0);
+
+        Self { min_voxel_size, max_num_voxels }
+    }
+}
+impl Default for ApolloSignedDistanceFieldParameters {
+    fn default() -> Self {
+        Self::new(0.02, 2_000_000)
+    }
+}
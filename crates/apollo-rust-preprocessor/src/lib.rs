@@ -1,5 +1,6 @@
 pub mod process_functions;
 pub mod robot_modules_preprocessor;
+pub mod srdf;
 pub mod standalone_preprocessor;
 pub mod utils;
 
@@ -7,7 +8,7 @@ use std::path::PathBuf;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use apollo_rust_file::ApolloPathBufTrait;
-use apollo_rust_modules::{ResourcesRootDirectory, ResourcesSubDirectory};
+use apollo_rust_modules::{load_versioned_module_from_json, save_versioned_module, ResourcesRootDirectory, ResourcesSubDirectory};
 use apollo_rust_modules::robot_modules::bevy_modules::first_look_vis_module::ApolloFirstLookVisModule;
 use apollo_rust_modules::robot_modules::bounds_module::ApolloBoundsModule;
 use apollo_rust_modules::robot_modules::chain_module::ApolloChainModule;
@@ -18,10 +19,12 @@ use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_distanc
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_max_distance_from_origin_module::ApolloLinkShapesMaxDistanceFromOriginModule;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_simple_skips_module::ApolloLinkShapesSimpleSkipsModule;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_skips_module::ApolloLinkShapesSkipsModule;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_padding_module::ApolloLinkShapesPaddingModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::original_meshes_module::ApolloOriginalMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::plain_meshes_module::ApolloPlainMeshesModule;
+use apollo_rust_modules::robot_modules::named_groups_module::ApolloNamedGroupsModule;
 use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFModule;
 use crate::utils::progress_bar::ProgressBarWrapper;
 
@@ -129,14 +132,7 @@ where
     }
 
     fn load_from_json(s: &ResourcesSubDirectory<P>) -> Result<Self, String> {
-        if !Self::full_path_to_module_version(s).path_exists() {
-            return Err("Module version does not exist".to_string());
-        }
-        let saved_version = Self::full_path_to_module_version(s).read_file_contents_to_string();
-        if saved_version != Self::current_version() {
-            return Err(format!("Version did not match when loading module {:?}.  saved version: {:?} vs. current version: {:?}", Self::relative_file_path_str_from_sub_dir_to_module_dir(), saved_version, Self::current_version()));
-        }
-        Self::full_path_module_json(s).load_object_from_json_file_result()
+        load_versioned_module_from_json(&Self::full_path_to_module_dir(s), &Self::current_version())
     }
 
     fn load_from_ron(s: &ResourcesSubDirectory<P>) -> Result<Self, String> {
@@ -162,10 +158,7 @@ where
     }
 
     fn save(&self, s: &ResourcesSubDirectory<P>) {
-        Self::full_path_to_module_version(s).write_string_to_file(&Self::current_version());
-        Self::full_path_module_json(s).save_object_to_json_file(self);
-        Self::full_path_module_ron(s).save_object_to_ron_file(self);
-        Self::full_path_module_yaml(s).save_object_to_yaml_file(self);
+        save_versioned_module(&Self::full_path_to_module_dir(s), &Self::current_version(), self);
     }
 
     fn load_or_build(s: &ResourcesSubDirectory<P>, force_build: bool) -> Result<Self, String> {
@@ -196,6 +189,48 @@ where
     }
 }
 
+/// A `PreprocessorModule` whose build depends on user-chosen parameters.  The parameters are
+/// stored in the module, and `load_or_build_with_parameters` rebuilds a saved module that was
+/// built with different ones.  `build_raw` should build with the default parameters.
+pub trait PreprocessorModuleWithParameters<P = PathBuf>: PreprocessorModule<P>
+where
+    P: ApolloPathBufTrait + Clone,
+{
+    type Parameters: Clone + PartialEq + Default;
+
+    fn parameters(&self) -> &Self::Parameters;
+
+    fn build_raw_with_parameters(
+        s: &ResourcesSubDirectory<P>,
+        parameters: &Self::Parameters,
+        progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String>;
+
+    fn build_with_parameters(s: &ResourcesSubDirectory<P>, parameters: &Self::Parameters) -> Result<Self, String> {
+        let mut pb = ProgressBarWrapper::new(
+            &s.name(),
+            &Self::relative_file_path_str_from_sub_dir_to_module_dir(),
+        );
+        let o = Self::build_raw_with_parameters(s, parameters, &mut pb)?;
+        o.save(s);
+
+        Ok(o)
+    }
+
+    fn load_or_build_with_parameters(s: &ResourcesSubDirectory<P>, parameters: &Self::Parameters, force_build: bool) -> Result<Self, String> {
+        if !force_build {
+            if let Ok(loaded) = Self::load_from_json(s) {
+                if loaded.parameters() == parameters {
+                    return Ok(loaded);
+                }
+                println!("Parameters did not match when loading module {:?}.  I will rebuild this module.", Self::relative_file_path_str_from_sub_dir_to_module_dir());
+            }
+        }
+
+        Self::build_with_parameters(s, parameters)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 pub trait ResourcesRootDirectoryTrait<P = PathBuf>
@@ -250,6 +285,8 @@ impl<P: ApolloPathBufTrait + Clone> ResourcesSubDirectoryTrait<P> for ResourcesS
         ApolloLinkShapesApproximationsModule::load_or_build(self, force_build_on_all)
             .expect("error");
         ApolloLinkShapesSkipsModule::load_or_build(self, force_build_on_all).expect("error");
+        ApolloLinkShapesPaddingModule::load_or_build(self, force_build_on_all).expect("error");
+        ApolloNamedGroupsModule::load_or_build(self, force_build_on_all).expect("error");
     }
 
     fn preprocess_environment(&self, force_build_on_all: bool) {
@@ -272,5 +309,6 @@ impl<P: ApolloPathBufTrait + Clone> ResourcesSubDirectoryTrait<P> for ResourcesS
         ApolloLinkShapesApproximationsModule::load_or_build(self, force_build_on_all)
             .expect("error");
         ApolloLinkShapesSkipsModule::load_or_build(self, force_build_on_all).expect("error");
+        ApolloLinkShapesPaddingModule::load_or_build(self, force_build_on_all).expect("error");
     }
 }
@@ -67,6 +67,7 @@ pub fn run_link_shapes_skips_module_process(chain_name: &str) {
         s,
         &convex_hull_meshes_module,
         &convex_decomposition_meshes_module,
+        &link_shapes_approximations_module,
     );
     let link_shapes_simple_skips_module =
         ApolloLinkShapesSimpleSkipsModule::load_or_build(s, false).expect("error");
@@ -214,10 +215,13 @@ pub fn run_link_shapes_skips_module_process(chain_name: &str) {
                 let ss_convex_decomposition_meshes_module =
                     ApolloConvexDecompositionMeshesModule::load_or_build(&ss, false)
                         .expect("error");
+                let ss_link_shapes_approximations_module =
+                    ApolloLinkShapesApproximationsModule::load_or_build(&ss, false).expect("error");
                 let ss_link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
                     &ss,
                     &ss_convex_hull_meshes_module,
                     &ss_convex_decomposition_meshes_module,
+                    &ss_link_shapes_approximations_module,
                 );
                 let ss_link_shapes_skips_module =
                     ApolloLinkShapesSkipsModule::load_or_build(&ss, false).expect("error");
@@ -362,14 +366,14 @@ pub fn run_link_shapes_skips_module_process(chain_name: &str) {
         let skips = match &link_shape_mode_a {
             LinkShapeMode::Full => {
                 match &link_shape_rep_a {
-                    LinkShapeRep::ConvexHull => { &mut full_convex_hulls_skips }
+                    LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => { &mut full_convex_hulls_skips }
                     LinkShapeRep::OBB => { &mut full_obbs_skips }
                     LinkShapeRep::BoundingSphere => { &mut full_bounding_spheres_skips }
                 }
             }
             LinkShapeMode::Decomposition => {
                 match &link_shape_rep_a {
-                    LinkShapeRep::ConvexHull => { &mut decomposition_convex_hulls_skips }
+                    LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => { &mut decomposition_convex_hulls_skips }
                     LinkShapeRep::OBB => { &mut decomposition_obbs_skips }
                     LinkShapeRep::BoundingSphere => { &mut decomposition_bounding_spheres_skips }
                 }
@@ -378,14 +382,14 @@ pub fn run_link_shapes_skips_module_process(chain_name: &str) {
         let original_skips = match &link_shape_mode_a {
             LinkShapeMode::Full => {
                 match &link_shape_rep_a {
-                    LinkShapeRep::ConvexHull => { &original_full_convex_hulls_skips }
+                    LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => { &original_full_convex_hulls_skips }
                     LinkShapeRep::OBB => { &original_full_obbs_skips }
                     LinkShapeRep::BoundingSphere => { &original_full_bounding_spheres_skips }
                 }
             }
             LinkShapeMode::Decomposition => {
                 match &link_shape_rep_a {
-                    LinkShapeRep::ConvexHull => { &original_decomposition_convex_hulls_skips }
+                    LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => { &original_decomposition_convex_hulls_skips }
                     LinkShapeRep::OBB => { &original_decomposition_obbs_skips }
                     LinkShapeRep::BoundingSphere => { &original_decomposition_bounding_spheres_skips }
                 }
@@ -454,7 +458,7 @@ pub fn run_link_shapes_skips_module_process(chain_name: &str) {
                 let i = link_shapes_module_clone.get_shape_idx_from_link_idx_and_subcomponent_idx(x.0, x.1, &link_shape_mode_a).expect("error");
                 let j = link_shapes_module_clone.get_shape_idx_from_link_idx_and_subcomponent_idx(y.0, y.1, &link_shape_mode_a).expect("error");
                 let link_poses = RobotKinematicsFunctions::fk(&chain_state_a.state, &urdf_nalgebra_module, &chain_module, &dof_module);
-                let res = RobotProximityFunctions::self_contact(&link_shapes_module, &link_poses, link_shape_mode_a, link_shape_rep_a, None, false, f64::INFINITY);
+                let res = RobotProximityFunctions::self_contact(&link_shapes_module, &link_poses, link_shape_mode_a, link_shape_rep_a, None, None, false, f64::INFINITY);
                 let idx = res.shape_idxs.iter().position(|x| x.0 == i && x.1 == j).unwrap();
                 let cutoff_distance = res.outputs[idx].unwrap().dist;
                 res.outputs.iter().zip(res.shape_idxs.iter()).for_each(|(x, y)| {
@@ -472,7 +476,7 @@ pub fn run_link_shapes_skips_module_process(chain_name: &str) {
                 let i = link_shapes_module_clone.get_shape_idx_from_link_idx_and_subcomponent_idx(x.0, x.1, &link_shape_mode_a).expect("error");
                 let j = link_shapes_module_clone.get_shape_idx_from_link_idx_and_subcomponent_idx(y.0, y.1, &link_shape_mode_a).expect("error");
                 let link_poses = RobotKinematicsFunctions::fk(&chain_state_a.state, &urdf_nalgebra_module, &chain_module, &dof_module);
-                let res = RobotProximityFunctions::self_contact(&link_shapes_module, &link_poses, link_shape_mode_a, link_shape_rep_a, None, false, f64::INFINITY);
+                let res = RobotProximityFunctions::self_contact(&link_shapes_module, &link_poses, link_shape_mode_a, link_shape_rep_a, None, None, false, f64::INFINITY);
                 let res = res.to_average_distances(&averages);
                 let idx = res.shape_idxs.iter().position(|x| x.0 == i && x.1 == j).unwrap();
                 let cutoff_distance = res.outputs[idx].unwrap().dist;
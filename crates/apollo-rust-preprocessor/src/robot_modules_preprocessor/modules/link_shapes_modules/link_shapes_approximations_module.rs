@@ -1,14 +1,21 @@
 use crate::utils::progress_bar::ProgressBarWrapper;
-use crate::PreprocessorModule;
+use crate::{PreprocessorModule, PreprocessorModuleWithParameters};
 use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_mesh_utils::stl::load_stl_file;
+use apollo_rust_mesh_utils::trimesh::ToTriMesh;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::{
-    ApolloLinkShapesApproximationsModule, BoundingSphereDescriptor, OBBDescriptor,
+    ApolloLinkShapesApproximationsModule, ApolloLinkShapesApproximationsParameters, BoundingSphereDescriptor, CapsuleDescriptor, OBBDescriptor,
 };
 use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
+use apollo_rust_modules::robot_modules::mesh_modules::plain_meshes_module::ApolloPlainMeshesModule;
 use apollo_rust_modules::ResourcesSubDirectory;
+use apollo_rust_proximity_parry::offset_shape::{
+    to_offset_shape_bounding_sphere, to_offset_shape_capsule, to_offset_shape_multi_sphere, to_offset_shape_obb, OffsetShape,
+};
 use apollo_rust_robotics_core::modules_runtime::link_shapes_module::ApolloLinkShapesModule;
-use parry3d_f64::shape::TypedShape;
+use apollo_rust_spatial::vectors::V3;
+use parry3d_f64::shape::{ConvexPolyhedron, TypedShape};
 
 impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesApproximationsModule {
     // type SubDirectoryType = ResourcesSingleRobotDirectory;
@@ -18,124 +25,205 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesAp
     }
 
     fn current_version() -> String {
-        "0.0.3".to_string()
+        "0.0.7".to_string()
     }
 
     fn build_raw(
         s: &ResourcesSubDirectory<P>,
         progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String> {
+        Self::build_raw_with_parameters(s, &ApolloLinkShapesApproximationsParameters::default(), progress_bar)
+    }
+}
+
+impl<P: ApolloPathBufTrait + Clone> PreprocessorModuleWithParameters<P> for ApolloLinkShapesApproximationsModule {
+    type Parameters = ApolloLinkShapesApproximationsParameters;
+
+    fn parameters(&self) -> &Self::Parameters {
+        &self.parameters
+    }
+
+    fn build_raw_with_parameters(
+        s: &ResourcesSubDirectory<P>,
+        parameters: &Self::Parameters,
+        progress_bar: &mut ProgressBarWrapper,
     ) -> Result<Self, String> {
         let convex_hull_meshes_module =
             ApolloConvexHullMeshesModule::<P>::load_or_build(s, false).expect("error");
         let convex_decomposition_meshes_module =
             ApolloConvexDecompositionMeshesModule::<P>::load_or_build(s, false).expect("error");
-        let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
+        let (full_convex_polyhedra, decomposition_convex_polyhedra) = ApolloLinkShapesModule::convex_polyhedra_from_mesh_modules(
             s,
             &convex_hull_meshes_module,
             &convex_decomposition_meshes_module,
         );
 
+        // capsules of full links are fit to the vertices of the plain meshes rather than their convex hulls.
+        let plain_meshes_module = ApolloPlainMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let full_mesh_points: Vec<Option<Vec<V3>>> = plain_meshes_module
+            .stl_link_mesh_relative_paths
+            .iter()
+            .map(|x| x.as_ref().map(|path_buf| {
+                let full_path = s.resolve_path(path_buf);
+                let tm = load_stl_file(&full_path)
+                    .unwrap_or_else(|e| panic!("error: {:?}, {:?}", full_path, e))
+                    .to_trimesh();
+                tm.points().iter().map(|x| V3::new(x[0], x[1], x[2])).collect()
+            }))
+            .collect();
+
+        let num_shapes = full_convex_polyhedra.iter().flatten().count() + decomposition_convex_polyhedra.iter().flatten().count();
+        progress_bar.set_max_increment(num_shapes);
+
         let mut full_obbs = vec![];
         let mut full_bounding_spheres = vec![];
-        link_shapes_module
-            .link_idx_to_full_shape_idx
-            .iter()
-            .for_each(|i| match i {
-                None => {
-                    full_obbs.push(None);
-                    full_bounding_spheres.push(None);
-                }
-                Some(i) => {
-                    let full_obb = &link_shapes_module.full_obbs[*i];
-                    let ts = full_obb.shape().as_typed_shape();
-                    let h = match ts {
-                        TypedShape::Cuboid(c) => c.half_extents.data.as_slice(),
-                        _ => {
-                            unreachable!()
-                        }
-                    };
-                    let offset = full_obb.offset().as_ref().unwrap();
-                    let xyz = offset.0.translation.vector.as_slice();
-                    let rpy = offset.0.rotation.euler_angles();
-                    full_obbs.push(Some(OBBDescriptor {
-                        half_extents: [h[0], h[1], h[2]],
-                        offset_xyz: [xyz[0], xyz[1], xyz[2]],
-                        offset_rpy: [rpy.0, rpy.1, rpy.2],
-                    }));
-
-                    let full_bounding_sphere = &link_shapes_module.full_bounding_spheres[*i];
-                    let ts = full_bounding_sphere.shape().as_typed_shape();
-                    let r = match ts {
-                        TypedShape::Ball(b) => b.radius,
-                        _ => {
-                            unreachable!()
-                        }
-                    };
-                    let offset = full_bounding_sphere.offset().as_ref().unwrap();
-                    let xyz = offset.0.translation.vector.as_slice();
-                    let rpy = offset.0.rotation.euler_angles();
-                    full_bounding_spheres.push(Some(BoundingSphereDescriptor {
-                        radius: r,
-                        offset_xyz: [xyz[0], xyz[1], xyz[2]],
-                        offset_rpy: [rpy.0, rpy.1, rpy.2],
-                    }));
-                }
-            });
+        let mut full_multi_spheres = vec![];
+        let mut full_capsules = vec![];
+        full_convex_polyhedra.iter().enumerate().for_each(|(link_idx, cp)| match cp {
+            None => {
+                full_obbs.push(None);
+                full_bounding_spheres.push(None);
+                full_multi_spheres.push(None);
+                full_capsules.push(None);
+            }
+            Some(cp) => {
+                full_obbs.push(Some(get_obb_descriptor(&to_offset_shape_obb(cp))));
+                full_bounding_spheres.push(Some(get_bounding_sphere_descriptor(&to_offset_shape_bounding_sphere(cp))));
+                full_multi_spheres.push(Some(get_multi_sphere_descriptors(&to_offset_shape_multi_sphere(
+                    cp,
+                    parameters.full_multi_sphere_num_spheres,
+                    parameters.multi_sphere_tightness,
+                ))));
+                let points = match full_mesh_points.get(link_idx) {
+                    Some(Some(points)) if !points.is_empty() => points.clone(),
+                    _ => convex_polyhedron_points(cp),
+                };
+                full_capsules.push(Some(get_capsule_descriptor(&to_offset_shape_capsule(&points))));
+                progress_bar.increment();
+            }
+        });
 
         let mut decomposition_obbs = vec![];
         let mut decomposition_bounding_spheres = vec![];
-        link_shapes_module
-            .link_idx_to_decomposition_shape_idxs
-            .iter()
-            .for_each(|x| {
-                let mut tmp1 = vec![];
-                let mut tmp2 = vec![];
-                x.iter().for_each(|i| {
-                    let decomposition_obb = &link_shapes_module.decomposition_obbs[*i];
-                    let ts = decomposition_obb.shape().as_typed_shape();
-                    let h = match ts {
-                        TypedShape::Cuboid(c) => c.half_extents.data.as_slice(),
-                        _ => {
-                            unreachable!()
-                        }
-                    };
-                    let offset = decomposition_obb.offset().as_ref().unwrap();
-                    let xyz = offset.0.translation.vector.as_slice();
-                    let rpy = offset.0.rotation.euler_angles();
-                    tmp1.push(OBBDescriptor {
-                        half_extents: [h[0], h[1], h[2]],
-                        offset_xyz: [xyz[0], xyz[1], xyz[2]],
-                        offset_rpy: [rpy.0, rpy.1, rpy.2],
-                    });
-
-                    let decomposition_bounding_sphere =
-                        &link_shapes_module.decomposition_bounding_spheres[*i];
-                    let ts = decomposition_bounding_sphere.shape().as_typed_shape();
-                    let r = match ts {
-                        TypedShape::Ball(b) => b.radius,
-                        _ => {
-                            unreachable!()
-                        }
-                    };
-                    let offset = decomposition_bounding_sphere.offset().as_ref().unwrap();
-                    let xyz = offset.0.translation.vector.as_slice();
-                    let rpy = offset.0.rotation.euler_angles();
-                    tmp2.push(BoundingSphereDescriptor {
-                        radius: r,
-                        offset_xyz: [xyz[0], xyz[1], xyz[2]],
-                        offset_rpy: [rpy.0, rpy.1, rpy.2],
-                    });
-                });
-                decomposition_obbs.push(tmp1);
-                decomposition_bounding_spheres.push(tmp2);
+        let mut decomposition_multi_spheres = vec![];
+        let mut decomposition_capsules = vec![];
+        decomposition_convex_polyhedra.iter().for_each(|x| {
+            let mut tmp1 = vec![];
+            let mut tmp2 = vec![];
+            let mut tmp3 = vec![];
+            let mut tmp4 = vec![];
+            x.iter().for_each(|cp| {
+                tmp1.push(get_obb_descriptor(&to_offset_shape_obb(cp)));
+                tmp2.push(get_bounding_sphere_descriptor(&to_offset_shape_bounding_sphere(cp)));
+                tmp3.push(get_multi_sphere_descriptors(&to_offset_shape_multi_sphere(
+                    cp,
+                    parameters.decomposition_multi_sphere_num_spheres,
+                    parameters.multi_sphere_tightness,
+                )));
+                tmp4.push(get_capsule_descriptor(&to_offset_shape_capsule(&convex_polyhedron_points(cp))));
+                progress_bar.increment();
             });
+            decomposition_obbs.push(tmp1);
+            decomposition_bounding_spheres.push(tmp2);
+            decomposition_multi_spheres.push(tmp3);
+            decomposition_capsules.push(tmp4);
+        });
 
         progress_bar.done_preset();
         Ok(Self {
+            parameters: parameters.clone(),
             full_obbs,
             full_bounding_spheres,
             decomposition_obbs,
             decomposition_bounding_spheres,
+            full_multi_spheres,
+            decomposition_multi_spheres,
+            full_capsules,
+            decomposition_capsules,
         })
     }
 }
+
+fn convex_polyhedron_points(cp: &ConvexPolyhedron) -> Vec<V3> {
+    cp.points().iter().map(|x| x.coords).collect()
+}
+
+/// Converts an axis-aligned cuboid with an offset into an OBB descriptor.
+fn get_obb_descriptor(obb: &OffsetShape) -> OBBDescriptor {
+    let h = match obb.shape().as_typed_shape() {
+        TypedShape::Cuboid(c) => c.half_extents.data.as_slice(),
+        _ => {
+            unreachable!()
+        }
+    };
+    let offset = obb.offset().as_ref().unwrap();
+    let xyz = offset.0.translation.vector.as_slice();
+    let rpy = offset.0.rotation.euler_angles();
+    OBBDescriptor {
+        half_extents: [h[0], h[1], h[2]],
+        offset_xyz: [xyz[0], xyz[1], xyz[2]],
+        offset_rpy: [rpy.0, rpy.1, rpy.2],
+    }
+}
+
+/// Converts a ball with an offset into a bounding sphere descriptor.
+fn get_bounding_sphere_descriptor(bounding_sphere: &OffsetShape) -> BoundingSphereDescriptor {
+    let r = match bounding_sphere.shape().as_typed_shape() {
+        TypedShape::Ball(b) => b.radius,
+        _ => {
+            unreachable!()
+        }
+    };
+    let offset = bounding_sphere.offset().as_ref().unwrap();
+    let xyz = offset.0.translation.vector.as_slice();
+    let rpy = offset.0.rotation.euler_angles();
+    BoundingSphereDescriptor {
+        radius: r,
+        offset_xyz: [xyz[0], xyz[1], xyz[2]],
+        offset_rpy: [rpy.0, rpy.1, rpy.2],
+    }
+}
+
+/// Converts a sphere set (a compound of balls in the link frame) into sphere descriptors.
+fn get_multi_sphere_descriptors(multi_sphere: &OffsetShape) -> Vec<BoundingSphereDescriptor> {
+    let compound = match multi_sphere.shape().as_typed_shape() {
+        TypedShape::Compound(c) => c,
+        _ => {
+            unreachable!()
+        }
+    };
+
+    compound
+        .shapes()
+        .iter()
+        .map(|(pose, part)| {
+            let r = part.as_ball().expect("error").radius;
+            let xyz = pose.translation.vector.as_slice();
+            BoundingSphereDescriptor {
+                radius: r,
+                offset_xyz: [xyz[0], xyz[1], xyz[2]],
+                offset_rpy: [0.0, 0.0, 0.0],
+            }
+        })
+        .collect()
+}
+
+/// Converts a y-aligned capsule with an offset into a capsule descriptor.
+fn get_capsule_descriptor(capsule: &OffsetShape) -> CapsuleDescriptor {
+    let c = match capsule.shape().as_typed_shape() {
+        TypedShape::Capsule(c) => c,
+        _ => {
+            unreachable!()
+        }
+    };
+
+    let offset = capsule.offset().as_ref().unwrap();
+    let xyz = offset.0.translation.vector.as_slice();
+    let rpy = offset.0.rotation.euler_angles();
+    CapsuleDescriptor {
+        radius: c.radius,
+        half_height: c.half_height(),
+        offset_xyz: [xyz[0], xyz[1], xyz[2]],
+        offset_rpy: [rpy.0, rpy.1, rpy.2],
+    }
+}
@@ -1,4 +1,5 @@
 use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::ApolloLinkShapesApproximationsModule;
 use apollo_rust_modules::robot_modules::bounds_module::ApolloBoundsModule;
 use apollo_rust_modules::robot_modules::chain_module::ApolloChainModule;
 use apollo_rust_modules::robot_modules::dof_module::ApolloDOFModule;
@@ -24,7 +25,7 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
     }
 
     fn current_version() -> String {
-        "0.0.3".to_string()
+        "0.0.4".to_string()
     }
 
     fn build_raw(
@@ -38,10 +39,13 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
             ApolloConvexHullMeshesModule::<P>::load_or_build(s, false).expect("error");
         let convex_decomposition_meshes_module =
             ApolloConvexDecompositionMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let link_shapes_approximations_module =
+            ApolloLinkShapesApproximationsModule::load_or_build(s, false).expect("error");
         let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
             s,
             &convex_hull_meshes_module,
             &convex_decomposition_meshes_module,
+            &link_shapes_approximations_module,
         );
         let dof_module = ApolloDOFModule::load_or_build(s, false).expect("error");
         let bounds_module = ApolloBoundsModule::load_or_build(s, false).expect("error");
@@ -51,18 +55,24 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
             LinkShapeRep::ConvexHull,
             LinkShapeRep::OBB,
             LinkShapeRep::BoundingSphere,
+            LinkShapeRep::MultiSphere,
+            LinkShapeRep::Capsule,
         ];
 
         let mut full_convex_hulls = LinkShapesDistanceStatistics::default();
         let mut full_obbs = LinkShapesDistanceStatistics::default();
         let mut full_bounding_spheres = LinkShapesDistanceStatistics::default();
+        let mut full_multi_spheres = LinkShapesDistanceStatistics::default();
+        let mut full_capsules = LinkShapesDistanceStatistics::default();
         let mut decomposition_convex_hulls = LinkShapesDistanceStatistics::default();
         let mut decomposition_obbs = LinkShapesDistanceStatistics::default();
         let mut decomposition_bounding_spheres = LinkShapesDistanceStatistics::default();
+        let mut decomposition_multi_spheres = LinkShapesDistanceStatistics::default();
+        let mut decomposition_capsules = LinkShapesDistanceStatistics::default();
 
         let num_samples = 1000_usize;
 
-        progress_bar.set_max_increment(num_samples * 6);
+        progress_bar.set_max_increment(num_samples * 10);
 
         link_shape_modes.iter().for_each(|link_shape_mode| {
             link_shape_reps.iter().for_each(|link_shape_rep| {
@@ -94,6 +104,7 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
                         *link_shape_mode,
                         *link_shape_rep,
                         None,
+                        None,
                         false,
                         1000000.0,
                     );
@@ -132,11 +143,15 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
                         LinkShapeRep::ConvexHull => full_convex_hulls = stats,
                         LinkShapeRep::OBB => full_obbs = stats,
                         LinkShapeRep::BoundingSphere => full_bounding_spheres = stats,
+                        LinkShapeRep::MultiSphere => full_multi_spheres = stats,
+                        LinkShapeRep::Capsule => full_capsules = stats,
                     },
                     LinkShapeMode::Decomposition => match link_shape_rep {
                         LinkShapeRep::ConvexHull => decomposition_convex_hulls = stats,
                         LinkShapeRep::OBB => decomposition_obbs = stats,
                         LinkShapeRep::BoundingSphere => decomposition_bounding_spheres = stats,
+                        LinkShapeRep::MultiSphere => decomposition_multi_spheres = stats,
+                        LinkShapeRep::Capsule => decomposition_capsules = stats,
                     },
                 }
             });
@@ -147,9 +162,13 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
             full_convex_hulls,
             full_obbs,
             full_bounding_spheres,
+            full_multi_spheres,
+            full_capsules,
             decomposition_convex_hulls,
             decomposition_obbs,
             decomposition_bounding_spheres,
+            decomposition_multi_spheres,
+            decomposition_capsules,
         });
     }
 }
@@ -1,6 +1,7 @@
 /*
 use apollo_rust_proximity::proxima::proxima2::{get_lladis_taylor_series_error_dataset_shape_pair_parallel, LieAlgMode, PolynomialFit, quantile_optimization};
 use apollo_rust_robot_modules::{ResourcesSubDirectory, ResourcesType};
+use apollo_rust_robot_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::ApolloLinkShapesApproximationsModule;
 use apollo_rust_robot_modules::robot_modules::link_shapes_modules::link_shapes_lie_alg_error_models_module::ApolloLinkShapesLieAlgErrorModelsModule;
 use apollo_rust_robot_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
 use apollo_rust_robot_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
@@ -42,7 +43,8 @@ impl PreprocessorModule for ApolloLinkShapesLieAlgErrorModelsModule {
 
         let convex_hull_meshes_module = ApolloConvexHullMeshesModule::load_or_build(s, false).expect("error");
         let convex_decomposition_meshes_module = ApolloConvexDecompositionMeshesModule::load_or_build(s, false).expect("error");
-        let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(s, &convex_hull_meshes_module, &convex_decomposition_meshes_module);
+        let link_shapes_approximations_module = ApolloLinkShapesApproximationsModule::load_or_build(s, false).expect("error");
+        let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(s, &convex_hull_meshes_module, &convex_decomposition_meshes_module, &link_shapes_approximations_module);
         let num_full_shapes = link_shapes_module.full_convex_hulls.len();
         let num_decomposition_shapes = link_shapes_module.decomposition_convex_hulls.len();
 
@@ -1,5 +1,6 @@
 use apollo_rust_file::ApolloPathBufTrait;
 use apollo_rust_modules::ResourcesSubDirectory;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::ApolloLinkShapesApproximationsModule;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_max_distance_from_origin_module::ApolloLinkShapesMaxDistanceFromOriginModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
@@ -28,10 +29,13 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P>
             ApolloConvexHullMeshesModule::<P>::load_or_build(s, false).expect("error");
         let convex_decomposition_meshes_module =
             ApolloConvexDecompositionMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let link_shapes_approximations_module =
+            ApolloLinkShapesApproximationsModule::load_or_build(s, false).expect("error");
         let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
             s,
             &convex_hull_meshes_module,
             &convex_decomposition_meshes_module,
+            &link_shapes_approximations_module,
         );
 
         let mut full_convex_hulls_maximum_distances = vec![];
@@ -0,0 +1,28 @@
+use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_modules::ResourcesSubDirectory;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_padding_module::ApolloLinkShapesPaddingModule;
+use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFModule;
+use crate::PreprocessorModule;
+use crate::utils::progress_bar::ProgressBarWrapper;
+
+impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesPaddingModule {
+    fn relative_file_path_str_from_sub_dir_to_module_dir() -> String {
+        "link_shapes_modules/link_shapes_padding_module".to_string()
+    }
+
+    fn current_version() -> String {
+        "0.0.1".to_string()
+    }
+
+    /// Builds a module without any padding.  Paddings are meant to be edited in the saved module
+    /// or set at runtime on the chain.
+    fn build_raw(
+        s: &ResourcesSubDirectory<P>,
+        progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String> {
+        let urdf_module = ApolloURDFModule::load_or_build(s, false).expect("error");
+
+        progress_bar.done_preset();
+        Ok(Self::new_zeros(urdf_module.links.len()))
+    }
+}
@@ -1,5 +1,6 @@
 use apollo_rust_file::ApolloPathBufTrait;
 use apollo_rust_modules::{ResourcesSubDirectory};
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::ApolloLinkShapesApproximationsModule;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_distance_statistics_module::{ApolloLinkShapesDistanceStatisticsModule, LinkShapesDistanceStatistics};
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_simple_skips_module::ApolloLinkShapesSimpleSkipsModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
@@ -16,7 +17,7 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesSi
     }
 
     fn current_version() -> String {
-        "0.0.5".to_string()
+        "0.0.6".to_string()
     }
 
     fn build_raw(
@@ -29,10 +30,13 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesSi
             ApolloConvexHullMeshesModule::<P>::load_or_build(s, false).expect("error");
         let convex_decomposition_meshes_module =
             ApolloConvexDecompositionMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let link_shapes_approximations_module =
+            ApolloLinkShapesApproximationsModule::load_or_build(s, false).expect("error");
         let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
             s,
             &convex_hull_meshes_module,
             &convex_decomposition_meshes_module,
+            &link_shapes_approximations_module,
         );
 
         let full_convex_hulls_simple_skips = get_simple_skips_from_link_shapes_distance_stats(
@@ -50,6 +54,16 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesSi
             &link_shapes_module,
             LinkShapeMode::Full,
         );
+        let full_multi_spheres_simple_skips = get_simple_skips_from_link_shapes_distance_stats(
+            &link_shapes_distance_stats_module.full_multi_spheres,
+            &link_shapes_module,
+            LinkShapeMode::Full,
+        );
+        let full_capsules_simple_skips = get_simple_skips_from_link_shapes_distance_stats(
+            &link_shapes_distance_stats_module.full_capsules,
+            &link_shapes_module,
+            LinkShapeMode::Full,
+        );
         let decomposition_convex_hulls_simple_skips =
             get_simple_skips_from_link_shapes_distance_stats(
                 &link_shapes_distance_stats_module.decomposition_convex_hulls,
@@ -67,15 +81,30 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesSi
                 &link_shapes_module,
                 LinkShapeMode::Decomposition,
             );
+        let decomposition_multi_spheres_simple_skips =
+            get_simple_skips_from_link_shapes_distance_stats(
+                &link_shapes_distance_stats_module.decomposition_multi_spheres,
+                &link_shapes_module,
+                LinkShapeMode::Decomposition,
+            );
+        let decomposition_capsules_simple_skips = get_simple_skips_from_link_shapes_distance_stats(
+            &link_shapes_distance_stats_module.decomposition_capsules,
+            &link_shapes_module,
+            LinkShapeMode::Decomposition,
+        );
 
         progress_bar.done_preset();
         Ok(Self {
             full_convex_hulls_simple_skips,
             full_obbs_simple_skips,
             full_bounding_spheres_simple_skips,
+            full_multi_spheres_simple_skips,
+            full_capsules_simple_skips,
             decomposition_convex_hulls_simple_skips,
             decomposition_obbs_simple_skips,
             decomposition_bounding_spheres_simple_skips,
+            decomposition_multi_spheres_simple_skips,
+            decomposition_capsules_simple_skips,
         })
     }
 }
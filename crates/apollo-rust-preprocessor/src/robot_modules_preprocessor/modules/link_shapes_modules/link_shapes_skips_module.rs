@@ -1,9 +1,23 @@
+use crate::srdf::ApolloSRDF;
 use crate::utils::progress_bar::ProgressBarWrapper;
-use crate::PreprocessorModule;
-use apollo_rust_file::traits::FromJsonString;
+use crate::{PreprocessorModule, PreprocessorModuleWithParameters};
 use apollo_rust_file::ApolloPathBufTrait;
-use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_skips_module::ApolloLinkShapesSkipsModule;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::ApolloLinkShapesApproximationsModule;
+use apollo_rust_modules::robot_modules::bounds_module::ApolloBoundsModule;
+use apollo_rust_modules::robot_modules::chain_module::ApolloChainModule;
+use apollo_rust_modules::robot_modules::dof_module::ApolloDOFModule;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_skips_module::{ApolloLinkShapesSkipsModule, ApolloLinkShapesSkipsParameters};
+use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
+use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
+use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFModule;
 use apollo_rust_modules::ResourcesSubDirectory;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+use apollo_rust_robotics_core::modules_runtime::urdf_nalgebra_module::ApolloURDFNalgebraModule;
+use apollo_rust_robotics_core::robot_functions::robot_kinematics_functions::RobotKinematicsFunctions;
+use apollo_rust_robotics_core::robot_functions::robot_proximity_functions::RobotProximityFunctions;
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use nalgebra::DMatrix;
 
 impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesSkipsModule {
     fn relative_file_path_str_from_sub_dir_to_module_dir() -> String {
@@ -11,90 +25,264 @@ impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloLinkShapesSk
     }
 
     fn current_version() -> String {
-        "0.0.2".to_string()
+        "0.0.5".to_string()
     }
 
     fn build_raw(
         s: &ResourcesSubDirectory<P>,
         progress_bar: &mut ProgressBarWrapper,
     ) -> Result<Self, String> {
-        progress_bar.update_with_percentage_preset(0.0);
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use std::process::{Command, Stdio};
-            let fp =
-                P::new_from_documents_dir().append("apollo-rust/crates/apollo-rust-preprocessor");
-            let output = Command::new("cargo")
-                .current_dir(fp.to_path_buf().to_str().expect("error"))
-                .arg("run")
-                .arg("--bin")
-                .arg("link_shapes_skips_module_process")
-                .arg("--")
-                .arg(&s.name)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output()
-                .expect("Failed to execute cargo command");
-
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                let split = stdout.split("\n");
-                let ss: Vec<String> = split.map(|x| x.to_string()).collect();
-
-                let l = ss.len();
-                let full_convex_hulls_skips_string = ss[l - 6].trim().to_string();
-                let full_obbs_skips_string = ss[l - 5].trim().to_string();
-                let full_bounding_spheres_skips_string = ss[l - 4].trim().to_string();
-                let decomposition_convex_hulls_skips_string = ss[l - 3].trim().to_string();
-                let decomposition_obbs_skips_string = ss[l - 2].trim().to_string();
-                let decomposition_bounding_spheres_skips_string = ss[l - 1].trim().to_string();
-
-                let full_convex_hulls_skips: Vec<Vec<bool>> =
-                    FromJsonString::from_json_string(&full_convex_hulls_skips_string);
-                let full_obbs_skips: Vec<Vec<bool>> =
-                    FromJsonString::from_json_string(&full_obbs_skips_string);
-                let full_bounding_spheres_skips: Vec<Vec<bool>> =
-                    FromJsonString::from_json_string(&full_bounding_spheres_skips_string);
-                let decomposition_convex_hulls_skips: Vec<Vec<bool>> =
-                    FromJsonString::from_json_string(&decomposition_convex_hulls_skips_string);
-                let decomposition_obbs_skips: Vec<Vec<bool>> =
-                    FromJsonString::from_json_string(&decomposition_obbs_skips_string);
-                let decomposition_bounding_spheres_skips: Vec<Vec<bool>> =
-                    FromJsonString::from_json_string(&decomposition_bounding_spheres_skips_string);
-
-                progress_bar.done_preset();
-
-                return Ok(Self {
-                    full_convex_hulls_skips,
-                    full_obbs_skips,
-                    full_bounding_spheres_skips,
-                    decomposition_convex_hulls_skips,
-                    decomposition_obbs_skips,
-                    decomposition_bounding_spheres_skips,
-                });
-            } else {
-                println!(
-                    "--- STDOUT ---\n{}",
-                    String::from_utf8_lossy(&output.stdout)
-                );
-                eprintln!(
-                    "--- STDERR ---\n{}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
+        Self::build_raw_with_parameters(s, &ApolloLinkShapesSkipsParameters::default(), progress_bar)
+    }
+}
+
+impl<P: ApolloPathBufTrait + Clone> PreprocessorModuleWithParameters<P> for ApolloLinkShapesSkipsModule {
+    type Parameters = ApolloLinkShapesSkipsParameters;
+
+    fn parameters(&self) -> &Self::Parameters {
+        &self.parameters
+    }
 
-            return Err(format!(
-                "link shapes skips module for chain {:?} could not be built.",
-                s.name
-            ));
+    fn build_raw_with_parameters(
+        s: &ResourcesSubDirectory<P>,
+        parameters: &Self::Parameters,
+        progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String> {
+        let urdf_module = ApolloURDFModule::load_or_build(s, false).expect("error");
+        let urdf_nalgebra_module = ApolloURDFNalgebraModule::from_urdf_module(&urdf_module);
+        let chain_module = ApolloChainModule::load_or_build(s, false).expect("error");
+        let dof_module = ApolloDOFModule::load_or_build(s, false).expect("error");
+        let bounds_module = ApolloBoundsModule::load_or_build(s, false).expect("error");
+        let convex_hull_meshes_module =
+            ApolloConvexHullMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let convex_decomposition_meshes_module =
+            ApolloConvexDecompositionMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let link_shapes_approximations_module =
+            ApolloLinkShapesApproximationsModule::load_or_build(s, false).expect("error");
+        let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
+            s,
+            &convex_hull_meshes_module,
+            &convex_decomposition_meshes_module,
+            &link_shapes_approximations_module,
+        );
+
+        // an srdf next to the urdf (e.g., from a MoveIt config) takes precedence over sampling.
+        if let Some(srdf) = ApolloSRDF::from_resources_sub_directory(s) {
+            let mut out = srdf?.to_link_shapes_skips_module(&urdf_module, &link_shapes_module);
+            out.parameters = parameters.clone();
+            progress_bar.done_preset();
+            return Ok(out);
         }
 
-        #[cfg(target_arch = "wasm32")]
-        {
-            return Err(
-                "WASM execution for LinkShapesSkipsModule is not yet implemented".to_string(),
+        progress_bar.set_max_increment(parameters.num_samples * 10);
+
+        // the same samples are used for all shape modes and representations.
+        let sample_link_poses = sample_link_poses(
+            &urdf_nalgebra_module,
+            &chain_module,
+            &dof_module,
+            &bounds_module,
+            parameters.num_samples,
+            parameters.seed,
+        );
+
+        let mut f = |link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep| {
+            let classes = classify_link_shape_pairs(
+                &link_shapes_module,
+                &urdf_nalgebra_module,
+                &chain_module,
+                &dof_module,
+                &bounds_module,
+                link_shape_mode,
+                link_shape_rep,
+                &sample_link_poses,
+                parameters.always_colliding_ratio,
+                progress_bar,
             );
+            classes
+                .iter()
+                .map(|x| x.iter().map(|y| y.is_skip()).collect())
+                .collect::<Vec<Vec<bool>>>()
+        };
+
+        let full_convex_hulls_skips = f(LinkShapeMode::Full, LinkShapeRep::ConvexHull);
+        let full_obbs_skips = f(LinkShapeMode::Full, LinkShapeRep::OBB);
+        let full_bounding_spheres_skips = f(LinkShapeMode::Full, LinkShapeRep::BoundingSphere);
+        let full_multi_spheres_skips = f(LinkShapeMode::Full, LinkShapeRep::MultiSphere);
+        let full_capsules_skips = f(LinkShapeMode::Full, LinkShapeRep::Capsule);
+        let decomposition_convex_hulls_skips =
+            f(LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull);
+        let decomposition_obbs_skips = f(LinkShapeMode::Decomposition, LinkShapeRep::OBB);
+        let decomposition_bounding_spheres_skips =
+            f(LinkShapeMode::Decomposition, LinkShapeRep::BoundingSphere);
+        let decomposition_multi_spheres_skips =
+            f(LinkShapeMode::Decomposition, LinkShapeRep::MultiSphere);
+        let decomposition_capsules_skips = f(LinkShapeMode::Decomposition, LinkShapeRep::Capsule);
+
+        progress_bar.done_preset();
+
+        Ok(Self {
+            parameters: parameters.clone(),
+            full_convex_hulls_skips,
+            full_obbs_skips,
+            full_bounding_spheres_skips,
+            full_multi_spheres_skips,
+            full_capsules_skips,
+            decomposition_convex_hulls_skips,
+            decomposition_obbs_skips,
+            decomposition_bounding_spheres_skips,
+            decomposition_multi_spheres_skips,
+            decomposition_capsules_skips,
+        })
+    }
+}
+
+/// The classification of a pair of link shapes for self-collision checking, following MoveIt's
+/// setup assistant.  All pairs except those classified as `Check` are skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkShapePairClass {
+    /// Both shapes belong to the same link (or it is the diagonal of the matrix).
+    SameLink,
+    /// The links are connected by a joint, possibly through links without geometry.
+    Adjacent,
+    /// The shapes are in collision at the default state.
+    DefaultColliding,
+    /// The shapes are in collision in (almost) all samples.
+    AlwaysColliding,
+    /// The shapes are never in collision in any sample.
+    NeverColliding,
+    /// The shapes are sometimes in collision and have to be checked.
+    Check,
+}
+impl LinkShapePairClass {
+    /// Returns `true` if collisions between the pair should not be checked.
+    pub fn is_skip(&self) -> bool {
+        *self != LinkShapePairClass::Check
+    }
+}
+
+/// Returns the link poses at `num_samples` random states sampled with the given seed, to be
+/// shared between calls to `classify_link_shape_pairs`.
+pub fn sample_link_poses(
+    urdf_nalgebra_module: &ApolloURDFNalgebraModule,
+    chain_module: &ApolloChainModule,
+    dof_module: &ApolloDOFModule,
+    bounds_module: &ApolloBoundsModule,
+    num_samples: usize,
+    seed: u64,
+) -> Vec<Vec<ISE3q>> {
+    bounds_module
+        .sample_random_states_from_seed(num_samples, seed)
+        .iter()
+        .map(|x| RobotKinematicsFunctions::fk(x, urdf_nalgebra_module, chain_module, dof_module))
+        .collect()
+}
+
+/// Classifies all pairs of link shapes of a robot as same-link, adjacent, default-colliding,
+/// always-colliding, never-colliding, or to be checked, from sampled states.
+///
+/// # Arguments
+/// - `sample_link_poses`: The link poses at each sampled state (see `sample_link_poses`).
+/// - `always_colliding_ratio`: Pairs colliding in at least this fraction of samples are always colliding.
+///
+/// # Returns
+/// A symmetric matrix of `LinkShapePairClass` in shape indices.
+pub fn classify_link_shape_pairs(
+    link_shapes_module: &ApolloLinkShapesModule,
+    urdf_nalgebra_module: &ApolloURDFNalgebraModule,
+    chain_module: &ApolloChainModule,
+    dof_module: &ApolloDOFModule,
+    bounds_module: &ApolloBoundsModule,
+    link_shape_mode: LinkShapeMode,
+    link_shape_rep: LinkShapeRep,
+    sample_link_poses: &Vec<Vec<ISE3q>>,
+    always_colliding_ratio: f64,
+    progress_bar: &mut ProgressBarWrapper,
+) -> Vec<Vec<LinkShapePairClass>> {
+    let num_shapes = link_shapes_module
+        .get_shapes(link_shape_mode, link_shape_rep)
+        .len();
+    let mut out = vec![vec![LinkShapePairClass::Check; num_shapes]; num_shapes];
+
+    let set = |out: &mut Vec<Vec<LinkShapePairClass>>, i: usize, j: usize, c: LinkShapePairClass| {
+        out[i][j] = c;
+        out[j][i] = c;
+    };
+
+    let has_geometry = |link_idx: usize| match link_shape_mode {
+        LinkShapeMode::Full => link_shapes_module.link_idx_to_full_shape_idx[link_idx].is_some(),
+        LinkShapeMode::Decomposition => {
+            !link_shapes_module.link_idx_to_decomposition_shape_idxs[link_idx].is_empty()
+        }
+    };
+    // the closest ancestor with geometry, so links joined through massless frames count as adjacent.
+    let geometric_parent = |link_idx: usize| {
+        let mut curr = chain_module.links_in_chain[link_idx].parent_link_idx;
+        while let Some(p) = curr {
+            if has_geometry(p) {
+                return Some(p);
+            }
+            curr = chain_module.links_in_chain[p].parent_link_idx;
+        }
+        None
+    };
+
+    for i in 0..num_shapes {
+        let (link_i, _) = link_shapes_module.get_link_idx_and_subcomponent_idx_from_shape_idx(i, &link_shape_mode);
+        for j in i..num_shapes {
+            let (link_j, _) = link_shapes_module.get_link_idx_and_subcomponent_idx_from_shape_idx(j, &link_shape_mode);
+            if link_i == link_j {
+                set(&mut out, i, j, LinkShapePairClass::SameLink);
+            } else if geometric_parent(link_i) == Some(link_j) || geometric_parent(link_j) == Some(link_i) {
+                set(&mut out, i, j, LinkShapePairClass::Adjacent);
+            }
         }
     }
+
+    let fk = |state: &V| RobotKinematicsFunctions::fk(state, urdf_nalgebra_module, chain_module, dof_module);
+    let get_skips = |out: &Vec<Vec<LinkShapePairClass>>| {
+        DMatrix::from_fn(num_shapes, num_shapes, |i, j| out[i][j] != LinkShapePairClass::Check)
+    };
+
+    // default state: zeros, clamped into the joint bounds.
+    let default_state = V::new(
+        &bounds_module
+            .bounds
+            .iter()
+            .map(|(lower, upper)| 0.0_f64.max(*lower).min(*upper))
+            .collect::<Vec<f64>>(),
+    );
+    let skips = get_skips(&out);
+    let res = RobotProximityFunctions::self_intersect(link_shapes_module, &fk(&default_state), link_shape_mode, link_shape_rep, Some(&skips), None, false);
+    res.outputs.iter().zip(res.shape_idxs.iter()).for_each(|(intersect, (i, j))| {
+        if *intersect {
+            set(&mut out, *i, *j, LinkShapePairClass::DefaultColliding);
+        }
+    });
+
+    let skips = get_skips(&out);
+    let mut collision_counts = vec![vec![0_usize; num_shapes]; num_shapes];
+    for link_poses in sample_link_poses {
+        let res = RobotProximityFunctions::self_intersect(link_shapes_module, link_poses, link_shape_mode, link_shape_rep, Some(&skips), None, false);
+        res.outputs.iter().zip(res.shape_idxs.iter()).for_each(|(intersect, (i, j))| {
+            if *intersect {
+                collision_counts[*i][*j] += 1;
+            }
+        });
+        progress_bar.increment();
+    }
+
+    for i in 0..num_shapes {
+        for j in 0..num_shapes {
+            if out[i][j] != LinkShapePairClass::Check { continue; }
+            let count = collision_counts[i][j].max(collision_counts[j][i]);
+            if count == 0 {
+                set(&mut out, i, j, LinkShapePairClass::NeverColliding);
+            } else if count as f64 >= always_colliding_ratio * sample_link_poses.len() as f64 {
+                set(&mut out, i, j, LinkShapePairClass::AlwaysColliding);
+            }
+        }
+    }
+
+    out
 }
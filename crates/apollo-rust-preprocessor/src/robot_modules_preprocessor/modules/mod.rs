@@ -5,4 +5,6 @@ pub mod dof_module;
 pub mod urdf_module;
 pub mod link_shapes_modules;
 pub mod mesh_modules;
-pub mod bevy_modules;
\ No newline at end of file
+pub mod bevy_modules;
+pub mod signed_distance_field_module;
+pub mod named_groups_module;
\ No newline at end of file
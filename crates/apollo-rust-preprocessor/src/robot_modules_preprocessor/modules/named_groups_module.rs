@@ -0,0 +1,31 @@
+use crate::srdf::ApolloSRDF;
+use crate::utils::progress_bar::ProgressBarWrapper;
+use crate::PreprocessorModule;
+use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_modules::robot_modules::named_groups_module::ApolloNamedGroupsModule;
+use apollo_rust_modules::ResourcesSubDirectory;
+
+impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloNamedGroupsModule {
+    fn relative_file_path_str_from_sub_dir_to_module_dir() -> String {
+        "named_groups_module".to_string()
+    }
+
+    fn current_version() -> String {
+        "0.0.1".to_string()
+    }
+
+    /// Reads the named groups from the SRDF in the robot directory, if there is one.  Otherwise,
+    /// the module is empty.
+    fn build_raw(
+        s: &ResourcesSubDirectory<P>,
+        progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String> {
+        let out = match ApolloSRDF::from_resources_sub_directory(s) {
+            None => Self::default(),
+            Some(srdf) => srdf?.named_groups_module,
+        };
+
+        progress_bar.done_preset();
+        Ok(out)
+    }
+}
@@ -0,0 +1,175 @@
+use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_linalg::V;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::ApolloLinkShapesApproximationsModule;
+use apollo_rust_modules::robot_modules::chain_module::ApolloChainModule;
+use apollo_rust_modules::robot_modules::dof_module::ApolloDOFModule;
+use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
+use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
+use apollo_rust_modules::robot_modules::signed_distance_field_module::{ApolloSignedDistanceFieldModule, ApolloSignedDistanceFieldParameters};
+use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFModule;
+use apollo_rust_modules::ResourcesSubDirectory;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+use apollo_rust_robotics_core::modules_runtime::urdf_nalgebra_module::ApolloURDFNalgebraModule;
+use apollo_rust_robotics_core::robot_functions::robot_kinematics_functions::RobotKinematicsFunctions;
+use parry3d_f64::bounding_volume::{Aabb, BoundingVolume};
+use parry3d_f64::math::Point;
+use parry3d_f64::query::PointQuery;
+use crate::{PreprocessorModule, PreprocessorModuleWithParameters};
+use crate::utils::progress_bar::ProgressBarWrapper;
+
+/// Free space added around the environment's bounding box.
+const SDF_PADDING: f64 = 0.25;
+/// Distance stored for environments without any shapes.
+const SDF_EMPTY_DISTANCE: f64 = 1_000_000.0;
+
+/// Signed distance fields are expensive to build, so they are not part of
+/// `preprocess_environment` and are built on first use.
+impl<P: ApolloPathBufTrait + Clone> PreprocessorModule<P> for ApolloSignedDistanceFieldModule {
+    fn relative_file_path_str_from_sub_dir_to_module_dir() -> String {
+        "signed_distance_field_module".to_string()
+    }
+
+    fn current_version() -> String {
+        "0.0.2".to_string()
+    }
+
+    fn build_raw(
+        s: &ResourcesSubDirectory<P>,
+        progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String> {
+        Self::build_raw_with_parameters(s, &ApolloSignedDistanceFieldParameters::default(), progress_bar)
+    }
+}
+
+impl<P: ApolloPathBufTrait + Clone> PreprocessorModuleWithParameters<P> for ApolloSignedDistanceFieldModule {
+    type Parameters = ApolloSignedDistanceFieldParameters;
+
+    fn parameters(&self) -> &Self::Parameters {
+        &self.parameters
+    }
+
+    fn build_raw_with_parameters(
+        s: &ResourcesSubDirectory<P>,
+        parameters: &Self::Parameters,
+        progress_bar: &mut ProgressBarWrapper,
+    ) -> Result<Self, String> {
+        let urdf_module = ApolloURDFModule::load_or_build(s, false).expect("error");
+        let urdf_nalgebra_module = ApolloURDFNalgebraModule::from_urdf_module(&urdf_module);
+        let chain_module = ApolloChainModule::load_or_build(s, false).expect("error");
+        let dof_module = ApolloDOFModule::load_or_build(s, false).expect("error");
+        let convex_hull_meshes_module =
+            ApolloConvexHullMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let convex_decomposition_meshes_module =
+            ApolloConvexDecompositionMeshesModule::<P>::load_or_build(s, false).expect("error");
+        let link_shapes_approximations_module =
+            ApolloLinkShapesApproximationsModule::load_or_build(s, false).expect("error");
+        let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
+            s,
+            &convex_hull_meshes_module,
+            &convex_decomposition_meshes_module,
+            &link_shapes_approximations_module,
+        );
+
+        let state = V::from_column_slice(&vec![0.0; dof_module.num_dofs]);
+        let link_poses = RobotKinematicsFunctions::fk(&state, &urdf_nalgebra_module, &chain_module, &dof_module);
+        let shapes = link_shapes_module.get_shapes(LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull);
+        let poses: Vec<_> = link_shapes_module
+            .link_poses_to_shape_poses(&link_poses, LinkShapeMode::Decomposition)
+            .iter()
+            .zip(shapes.iter())
+            .map(|(pose, shape)| shape.get_transform(pose).into_owned())
+            .collect();
+
+        if shapes.is_empty() {
+            progress_bar.done_preset();
+            return Ok(ApolloSignedDistanceFieldModule {
+                parameters: parameters.clone(),
+                origin: [0.0; 3],
+                voxel_size: parameters.min_voxel_size,
+                dims: [1, 1, 1],
+                distances: vec![SDF_EMPTY_DISTANCE],
+                gradients: vec![[0.0; 3]],
+            });
+        }
+
+        let mut aabb = Aabb::new_invalid();
+        shapes.iter().zip(poses.iter()).for_each(|(shape, pose)| {
+            aabb.merge(&shape.shape().compute_aabb(&pose.0));
+        });
+        let aabb = aabb.loosened(SDF_PADDING);
+
+        let extents = aabb.extents();
+        let volume = extents[0] * extents[1] * extents[2];
+        let voxel_size = parameters.min_voxel_size.max((volume / parameters.max_num_voxels as f64).cbrt());
+        let dims = [
+            (extents[0] / voxel_size).ceil() as usize + 1,
+            (extents[1] / voxel_size).ceil() as usize + 1,
+            (extents[2] / voxel_size).ceil() as usize + 1,
+        ];
+        let origin = [aabb.mins[0], aabb.mins[1], aabb.mins[2]];
+
+        let mut distances = vec![0.0; dims[0] * dims[1] * dims[2]];
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    let point = Point::new(
+                        origin[0] + i as f64 * voxel_size,
+                        origin[1] + j as f64 * voxel_size,
+                        origin[2] + k as f64 * voxel_size,
+                    );
+
+                    // union of convex pieces: outside all pieces, the distance is the smallest
+                    // distance to any piece.  inside, it is the deepest penetration into a single
+                    // piece, which slightly underestimates depth where pieces overlap.
+                    let mut min_outside = f64::INFINITY;
+                    let mut max_inside = 0.0_f64;
+                    let mut is_inside = false;
+                    shapes.iter().zip(poses.iter()).for_each(|(shape, pose)| {
+                        let projection = shape.shape().project_point(&pose.0, &point, false);
+                        let d = (projection.point - point).norm();
+                        if projection.is_inside {
+                            is_inside = true;
+                            max_inside = max_inside.max(d);
+                        } else {
+                            min_outside = min_outside.min(d);
+                        }
+                    });
+
+                    distances[i + dims[0] * (j + dims[1] * k)] = if is_inside { -max_inside } else { min_outside };
+                }
+            }
+            progress_bar.update_with_percentage_preset(100.0 * (k + 1) as f64 / dims[2] as f64);
+        }
+
+        let idx = |i: usize, j: usize, k: usize| i + dims[0] * (j + dims[1] * k);
+        let mut gradients = vec![[0.0; 3]; distances.len()];
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    let ijk = [i, j, k];
+                    let mut gradient = [0.0; 3];
+                    for axis in 0..3 {
+                        if dims[axis] < 2 { continue; }
+                        let mut lo = ijk;
+                        let mut hi = ijk;
+                        if ijk[axis] > 0 { lo[axis] -= 1; }
+                        if ijk[axis] < dims[axis] - 1 { hi[axis] += 1; }
+                        let h = (hi[axis] - lo[axis]) as f64 * voxel_size;
+                        gradient[axis] = (distances[idx(hi[0], hi[1], hi[2])] - distances[idx(lo[0], lo[1], lo[2])]) / h;
+                    }
+                    gradients[idx(i, j, k)] = gradient;
+                }
+            }
+        }
+
+        progress_bar.done_preset();
+        Ok(ApolloSignedDistanceFieldModule {
+            parameters: parameters.clone(),
+            origin,
+            voxel_size,
+            dims,
+            distances,
+            gradients,
+        })
+    }
+}
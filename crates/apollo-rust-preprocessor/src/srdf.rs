@@ -0,0 +1,284 @@
+use crate::robot_modules_preprocessor::modules::link_shapes_modules::link_shapes_skips_module::LinkShapePairClass;
+use apollo_rust_file::ApolloPathBufTrait;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_skips_module::{ApolloLinkShapesSkipsModule, ApolloLinkShapesSkipsParameters};
+use apollo_rust_modules::robot_modules::named_groups_module::{ApolloNamedConfiguration, ApolloNamedEndEffector, ApolloNamedGroup, ApolloNamedGroupsModule};
+use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFModule;
+use apollo_rust_modules::ResourcesSubDirectory;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+
+/// A disabled collision pair from an SRDF `disable_collisions` tag.
+#[derive(Clone, Debug)]
+pub struct SRDFDisabledCollision {
+    pub link1: String,
+    pub link2: String,
+    /// e.g., "Adjacent", "Never", "Always", "Default", or "User".
+    pub reason: String,
+}
+
+/// The subset of an SRDF (semantic robot description format) file used by apollo: groups, end
+/// effectors, group states, and disabled collisions.  Other tags (e.g., `virtual_joint` or
+/// `passive_joint`) are ignored on import.
+#[derive(Clone, Debug, Default)]
+pub struct ApolloSRDF {
+    pub robot_name: String,
+    pub named_groups_module: ApolloNamedGroupsModule,
+    pub disabled_collisions: Vec<SRDFDisabledCollision>,
+    /// Links from `disable_default_collisions` tags, which are not checked against any other link.
+    pub disabled_default_collision_links: Vec<String>,
+}
+impl ApolloSRDF {
+    /// Parses an SRDF from its XML string.
+    pub fn from_srdf_string(s: &str) -> Result<Self, String> {
+        let doc = roxmltree::Document::parse(s).map_err(|e| format!("Unable to parse srdf.  Error: {:?}", e))?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "robot" {
+            return Err("srdf root element must be <robot>".to_string());
+        }
+
+        let attribute = |node: &roxmltree::Node, name: &str| -> Result<String, String> {
+            node.attribute(name)
+                .map(|x| x.to_string())
+                .ok_or(format!("srdf <{}> tag is missing attribute {:?}", node.tag_name().name(), name))
+        };
+
+        let mut out = Self {
+            robot_name: root.attribute("name").unwrap_or("").to_string(),
+            ..Default::default()
+        };
+        let mut enabled_collisions = vec![];
+
+        for node in root.children().filter(|x| x.is_element()) {
+            match node.tag_name().name() {
+                "group" => {
+                    let mut group = ApolloNamedGroup {
+                        name: attribute(&node, "name")?,
+                        link_names: vec![],
+                        joint_names: vec![],
+                        chains: vec![],
+                        subgroup_names: vec![],
+                    };
+                    for child in node.children().filter(|x| x.is_element()) {
+                        match child.tag_name().name() {
+                            "link" => group.link_names.push(attribute(&child, "name")?),
+                            "joint" => group.joint_names.push(attribute(&child, "name")?),
+                            "chain" => group.chains.push((attribute(&child, "base_link")?, attribute(&child, "tip_link")?)),
+                            "group" => group.subgroup_names.push(attribute(&child, "name")?),
+                            _ => {}
+                        }
+                    }
+                    out.named_groups_module.groups.push(group);
+                }
+                "end_effector" => {
+                    out.named_groups_module.end_effectors.push(ApolloNamedEndEffector {
+                        name: attribute(&node, "name")?,
+                        group: attribute(&node, "group")?,
+                        parent_link: attribute(&node, "parent_link")?,
+                        parent_group: node.attribute("parent_group").map(|x| x.to_string()),
+                    });
+                }
+                "group_state" => {
+                    let mut joint_values = vec![];
+                    for child in node.children().filter(|x| x.is_element() && x.tag_name().name() == "joint") {
+                        let value_string = attribute(&child, "value")?;
+                        let values = value_string
+                            .split_whitespace()
+                            .map(|x| x.parse::<f64>().map_err(|e| format!("invalid srdf joint value {:?}: {:?}", x, e)))
+                            .collect::<Result<Vec<f64>, String>>()?;
+                        joint_values.push((attribute(&child, "name")?, values));
+                    }
+                    out.named_groups_module.named_configurations.push(ApolloNamedConfiguration {
+                        name: attribute(&node, "name")?,
+                        group: attribute(&node, "group")?,
+                        joint_values,
+                    });
+                }
+                "disable_collisions" => {
+                    out.disabled_collisions.push(SRDFDisabledCollision {
+                        link1: attribute(&node, "link1")?,
+                        link2: attribute(&node, "link2")?,
+                        reason: node.attribute("reason").unwrap_or("").to_string(),
+                    });
+                }
+                "disable_default_collisions" => {
+                    out.disabled_default_collision_links.push(attribute(&node, "link")?);
+                }
+                "enable_collisions" => {
+                    enabled_collisions.push((attribute(&node, "link1")?, attribute(&node, "link2")?));
+                }
+                _ => {}
+            }
+        }
+
+        // explicitly enabled pairs override disabled ones, as in MoveIt.
+        out.disabled_collisions.retain(|x| {
+            !enabled_collisions.iter().any(|(a, b)| (a == &x.link1 && b == &x.link2) || (a == &x.link2 && b == &x.link1))
+        });
+
+        Ok(out)
+    }
+
+    /// Reads and parses an SRDF file.
+    pub fn from_srdf_path<P: ApolloPathBufTrait>(path: &P) -> Result<Self, String> {
+        let content = path.read_file_contents_to_string_result()?;
+        Self::from_srdf_string(&content)
+    }
+
+    /// Looks for a `.srdf` file at the top level of the given robot directory and parses it.
+    ///
+    /// # Returns
+    /// `None` if the directory does not contain an SRDF file.
+    pub fn from_resources_sub_directory<P: ApolloPathBufTrait + Clone>(s: &ResourcesSubDirectory<P>) -> Option<Result<Self, String>> {
+        let files = s.directory.clone().get_all_items_in_directory(false, false, true, false);
+        for file in files {
+            if file.path_exists() && file.path_extension().map(|x| x == "srdf").unwrap_or(false) {
+                return Some(Self::from_srdf_path(&file));
+            }
+        }
+
+        None
+    }
+
+    /// Creates an SRDF from apollo modules.  A pair of links is written as a disabled collision
+    /// with the reason of its classification (e.g., "Adjacent" or "Never"), where
+    /// `link_shape_pair_classes` is the output of `classify_link_shape_pairs` in full shape
+    /// indices.  Pairs that are only skipped in the full convex hull skips (e.g., skips set by
+    /// hand) are written with reason "User".
+    pub fn from_modules(urdf_module: &ApolloURDFModule, named_groups_module: &ApolloNamedGroupsModule, link_shapes_module: &ApolloLinkShapesModule, link_shapes_skips_module: &ApolloLinkShapesSkipsModule, link_shape_pair_classes: &Vec<Vec<LinkShapePairClass>>) -> Self {
+        let mut disabled_collisions = vec![];
+
+        let num_links = urdf_module.links.len();
+        for link1 in 0..num_links {
+            for link2 in link1 + 1..num_links {
+                let i = link_shapes_module.link_idx_to_full_shape_idx[link1];
+                let j = link_shapes_module.link_idx_to_full_shape_idx[link2];
+                if let (Some(i), Some(j)) = (i, j) {
+                    let reason = match link_shape_pair_classes[i][j] {
+                        LinkShapePairClass::Adjacent => { Some("Adjacent") }
+                        LinkShapePairClass::DefaultColliding => { Some("Default") }
+                        LinkShapePairClass::AlwaysColliding => { Some("Always") }
+                        LinkShapePairClass::NeverColliding => { Some("Never") }
+                        LinkShapePairClass::SameLink | LinkShapePairClass::Check => {
+                            if link_shapes_skips_module.full_convex_hulls_skips[i][j] { Some("User") } else { None }
+                        }
+                    };
+                    if let Some(reason) = reason {
+                        disabled_collisions.push(SRDFDisabledCollision {
+                            link1: urdf_module.links[link1].name.clone(),
+                            link2: urdf_module.links[link2].name.clone(),
+                            reason: reason.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Self {
+            robot_name: urdf_module.name.clone(),
+            named_groups_module: named_groups_module.clone(),
+            disabled_collisions,
+            disabled_default_collision_links: vec![],
+        }
+    }
+
+    /// Writes the SRDF as an XML string.
+    pub fn to_srdf_string(&self) -> String {
+        let mut out = String::new();
+        out += "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n";
+        out += &format!("<robot name=\"{}\">\n", escape_xml(&self.robot_name));
+
+        for group in &self.named_groups_module.groups {
+            out += &format!("    <group name=\"{}\">\n", escape_xml(&group.name));
+            group.link_names.iter().for_each(|x| out += &format!("        <link name=\"{}\"/>\n", escape_xml(x)));
+            group.joint_names.iter().for_each(|x| out += &format!("        <joint name=\"{}\"/>\n", escape_xml(x)));
+            group.chains.iter().for_each(|(a, b)| out += &format!("        <chain base_link=\"{}\" tip_link=\"{}\"/>\n", escape_xml(a), escape_xml(b)));
+            group.subgroup_names.iter().for_each(|x| out += &format!("        <group name=\"{}\"/>\n", escape_xml(x)));
+            out += "    </group>\n";
+        }
+
+        for configuration in &self.named_groups_module.named_configurations {
+            out += &format!("    <group_state name=\"{}\" group=\"{}\">\n", escape_xml(&configuration.name), escape_xml(&configuration.group));
+            configuration.joint_values.iter().for_each(|(joint_name, values)| {
+                let values: Vec<String> = values.iter().map(|x| x.to_string()).collect();
+                out += &format!("        <joint name=\"{}\" value=\"{}\"/>\n", escape_xml(joint_name), values.join(" "));
+            });
+            out += "    </group_state>\n";
+        }
+
+        for end_effector in &self.named_groups_module.end_effectors {
+            out += &format!("    <end_effector name=\"{}\" parent_link=\"{}\" group=\"{}\"", escape_xml(&end_effector.name), escape_xml(&end_effector.parent_link), escape_xml(&end_effector.group));
+            if let Some(parent_group) = &end_effector.parent_group {
+                out += &format!(" parent_group=\"{}\"", escape_xml(parent_group));
+            }
+            out += "/>\n";
+        }
+
+        for link in &self.disabled_default_collision_links {
+            out += &format!("    <disable_default_collisions link=\"{}\"/>\n", escape_xml(link));
+        }
+
+        for x in &self.disabled_collisions {
+            out += &format!("    <disable_collisions link1=\"{}\" link2=\"{}\" reason=\"{}\"/>\n", escape_xml(&x.link1), escape_xml(&x.link2), escape_xml(&x.reason));
+        }
+
+        out += "</robot>\n";
+        out
+    }
+
+    /// Writes the SRDF to a file.
+    pub fn write_srdf_file<P: ApolloPathBufTrait>(&self, path: &P) {
+        path.write_string_to_file(&self.to_srdf_string());
+    }
+
+    /// Returns `true` if collisions between the two links are disabled.
+    pub fn is_collision_disabled(&self, link1: &str, link2: &str) -> bool {
+        if self.disabled_default_collision_links.iter().any(|x| x == link1 || x == link2) { return true; }
+        self.disabled_collisions.iter().any(|x| (x.link1 == link1 && x.link2 == link2) || (x.link1 == link2 && x.link2 == link1))
+    }
+
+    /// Maps the disabled collisions onto a skips matrix in the shape indices of the given mode and
+    /// representation, through the link of each shape.  Shapes of the same link are always skipped.
+    pub fn get_link_shapes_skips(&self, urdf_module: &ApolloURDFModule, link_shapes_module: &ApolloLinkShapesModule, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> Vec<Vec<bool>> {
+        let num_shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep).len();
+        let shape_idx_to_link_idx: Vec<usize> = (0..num_shapes)
+            .map(|i| link_shapes_module.get_link_idx_and_subcomponent_idx_from_shape_idx(i, &link_shape_mode).0)
+            .collect();
+
+        let num_links = urdf_module.links.len();
+        let mut link_skips = vec![vec![false; num_links]; num_links];
+        for link1 in 0..num_links {
+            for link2 in 0..num_links {
+                link_skips[link1][link2] = link1 == link2 || self.is_collision_disabled(&urdf_module.links[link1].name, &urdf_module.links[link2].name);
+            }
+        }
+
+        (0..num_shapes)
+            .map(|i| (0..num_shapes).map(|j| link_skips[shape_idx_to_link_idx[i]][shape_idx_to_link_idx[j]]).collect())
+            .collect()
+    }
+
+    /// Maps the disabled collisions onto a skips module, for every link shape mode and
+    /// representation.  No sampling is done, so the module records default sampling parameters.
+    pub fn to_link_shapes_skips_module(&self, urdf_module: &ApolloURDFModule, link_shapes_module: &ApolloLinkShapesModule) -> ApolloLinkShapesSkipsModule {
+        let f = |link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep| {
+            self.get_link_shapes_skips(urdf_module, link_shapes_module, link_shape_mode, link_shape_rep)
+        };
+
+        ApolloLinkShapesSkipsModule {
+            parameters: ApolloLinkShapesSkipsParameters::default(),
+            full_convex_hulls_skips: f(LinkShapeMode::Full, LinkShapeRep::ConvexHull),
+            full_obbs_skips: f(LinkShapeMode::Full, LinkShapeRep::OBB),
+            full_bounding_spheres_skips: f(LinkShapeMode::Full, LinkShapeRep::BoundingSphere),
+            full_multi_spheres_skips: f(LinkShapeMode::Full, LinkShapeRep::MultiSphere),
+            full_capsules_skips: f(LinkShapeMode::Full, LinkShapeRep::Capsule),
+            decomposition_convex_hulls_skips: f(LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull),
+            decomposition_obbs_skips: f(LinkShapeMode::Decomposition, LinkShapeRep::OBB),
+            decomposition_bounding_spheres_skips: f(LinkShapeMode::Decomposition, LinkShapeRep::BoundingSphere),
+            decomposition_multi_spheres_skips: f(LinkShapeMode::Decomposition, LinkShapeRep::MultiSphere),
+            decomposition_capsules_skips: f(LinkShapeMode::Decomposition, LinkShapeRep::Capsule),
+        }
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
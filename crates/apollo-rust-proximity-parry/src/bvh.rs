@@ -1,11 +1,11 @@
-use nalgebra::Point3;
+use nalgebra::{Matrix3, Point3, Rotation3, SymmetricEigen, UnitQuaternion};
 use parry3d_f64::bounding_volume::{Aabb, BoundingSphere, BoundingVolume};
-use parry3d_f64::query::{contact};
+use parry3d_f64::query::{contact, intersection_test};
 use parry3d_f64::shape::{Ball, Cuboid};
 use apollo_rust_algs::combinations_of_n;
 use apollo_rust_spatial::isometry3::{ApolloIsometry3Trait, I3};
 use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
-use apollo_rust_spatial::vectors::V3;
+use apollo_rust_spatial::vectors::{ApolloVector3Trait2, V3};
 use crate::offset_shape::OffsetShape;
 
 pub trait BvhShape : Sized + Clone {
@@ -194,6 +194,117 @@ impl BvhShapeBuilder for BvhShapeBuilderBoundingSphere {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Oriented bounding box node.  Leaf boxes are aligned with the local frame of their shape, and
+/// combined boxes are fit to the corners of their children using principal component analysis.
+#[derive(Clone, Debug)]
+pub struct BvhShapeOBB {
+    cuboid: Cuboid,
+    pose: ISE3q
+}
+impl BvhShapeOBB {
+    fn new_from_local_aabb(local_aabb: &Aabb, shape_pose: &ISE3q) -> Self {
+        let center = shape_pose.map_point(&local_aabb.center().coords);
+        let pose = ISE3q::new(I3::from_parts(center.to_translation(), shape_pose.0.rotation.clone()));
+
+        Self {
+            cuboid: Cuboid::new(local_aabb.half_extents()),
+            pose,
+        }
+    }
+
+    fn new_from_points(points: &Vec<V3>) -> Self {
+        assert!(points.len() > 0);
+
+        let n = points.len() as f64;
+        let mut mean = V3::zeros();
+        points.iter().for_each(|x| mean += x);
+        mean /= n;
+
+        let mut covariance = Matrix3::zeros();
+        points.iter().for_each(|x| {
+            let d = x - &mean;
+            covariance += &d * d.transpose();
+        });
+        covariance /= n;
+
+        let mut axes = SymmetricEigen::new(covariance).eigenvectors;
+        if axes.determinant() < 0.0 {
+            let flipped = -axes.column(2).into_owned();
+            axes.set_column(2, &flipped);
+        }
+
+        let mut mins = V3::repeat(f64::INFINITY);
+        let mut maxs = V3::repeat(f64::NEG_INFINITY);
+        points.iter().for_each(|x| {
+            let local = axes.transpose() * (x - &mean);
+            mins = mins.inf(&local);
+            maxs = maxs.sup(&local);
+        });
+
+        let center = &mean + &axes * (0.5 * (&mins + &maxs));
+        let half_extents = 0.5 * (&maxs - &mins);
+        let rotation = UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(axes));
+        let pose = ISE3q::new(I3::from_parts(center.to_translation(), rotation));
+
+        Self {
+            cuboid: Cuboid::new(half_extents),
+            pose,
+        }
+    }
+
+    fn corners(&self) -> Vec<V3> {
+        let h = &self.cuboid.half_extents;
+        let mut out = vec![];
+        for sx in [-1.0, 1.0] {
+            for sy in [-1.0, 1.0] {
+                for sz in [-1.0, 1.0] {
+                    out.push(self.pose.map_point(&V3::new(sx * h.x, sy * h.y, sz * h.z)));
+                }
+            }
+        }
+        out
+    }
+}
+impl BvhShape for BvhShapeOBB {
+    fn new_from_offset_shapes(offset_shapes: &Vec<OffsetShape>, poses: &Vec<ISE3q>) -> Self {
+        assert_eq!(offset_shapes.len(), poses.len());
+
+        let leaves: Vec<BvhShapeOBB> = offset_shapes.iter().zip(poses).map(|(x, y)| {
+            let pose = x.get_transform(y);
+            BvhShapeOBB::new_from_local_aabb(&x.shape().compute_local_aabb(), pose.as_ref())
+        }).collect();
+
+        if leaves.len() == 1 { return leaves[0].clone(); }
+        BvhShapeOBB::new_from_combined(&leaves)
+    }
+
+    fn new_from_combined(bvh_shapes: &Vec<Self>) -> Self {
+        let mut points = vec![];
+        bvh_shapes.iter().for_each(|x| points.extend(x.corners()));
+        BvhShapeOBB::new_from_points(&points)
+    }
+
+    #[inline(always)]
+    fn volume(&self) -> f64 {
+        let h = &self.cuboid.half_extents;
+        8.0 * h.x * h.y * h.z
+    }
+
+    #[inline(always)]
+    fn intersect(&self, other: &Self) -> bool {
+        intersection_test(&self.pose.0, &self.cuboid, &other.pose.0, &other.cuboid).expect("error")
+    }
+
+    #[inline(always)]
+    fn signed_distance(&self, other: &Self) -> f64 {
+        // cuboid-cuboid contact is supported, and with an infinite prediction distance there is
+        // always a contact.
+        contact(&self.pose.0, &self.cuboid, &other.pose.0, &other.cuboid, f64::INFINITY).ok().flatten().expect("cuboid-cuboid contact with infinite prediction distance").dist
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
 #[derive(Clone, Debug)]
 pub struct Bvh<B: BvhShape> {
     pub layers: Vec<BvhLayer<B>>,
@@ -213,6 +324,53 @@ impl<B: BvhShape> Bvh<B> {
         }
     }
 
+    /// Builds a binary hierarchy top-down, choosing each split with the surface area heuristic.
+    ///
+    /// Every node is split along the axis and position that minimize the summed surface area of
+    /// the AABBs of the two children weighted by their number of shapes.  The bounding volumes of
+    /// type `B` are only built for the chosen nodes.  Shorter branches are padded with
+    /// single-child nodes so the result keeps the layered layout used by all queries, and the
+    /// last layer holds one node per shape.
+    pub fn build_sah(shapes: &Vec<OffsetShape>, poses: &Vec<ISE3q>) -> Self {
+        assert_eq!(shapes.len(), poses.len());
+        assert!(shapes.len() > 0);
+
+        let leaf_shapes: Vec<B> = shapes.iter().zip(poses.iter()).map(|(x, y)| B::new_from_offset_shapes(&vec![x.clone()], &vec![y.clone()])).collect();
+        let leaf_aabbs: Vec<Aabb> = shapes.iter().zip(poses.iter()).map(|(x, y)| {
+            let pose = x.get_transform(y);
+            x.shape().compute_aabb(&pose.as_ref().0)
+        }).collect();
+        let mut nodes = vec![];
+        let root = sah_build_recursive(&leaf_shapes, &leaf_aabbs, (0..leaf_shapes.len()).collect(), &mut nodes);
+        let layers = sah_nodes_to_layers(&nodes, root);
+
+        Self {
+            layers,
+            branch_factor: 2,
+        }
+    }
+
+    /// Recomputes every bounding volume bottom-up for new poses without changing the topology.
+    pub fn refit(&mut self, shapes: &Vec<OffsetShape>, poses: &Vec<ISE3q>) {
+        assert_eq!(shapes.len(), poses.len());
+
+        let leaf_shapes: Vec<B> = shapes.iter().zip(poses.iter()).map(|(x, y)| B::new_from_offset_shapes(&vec![x.clone()], &vec![y.clone()])).collect();
+        let num_layers = self.layers.len();
+        for curr_idx in (0..num_layers).rev() {
+            let updated_shapes: Vec<B> = self.layers[curr_idx].parent_indices.iter().map(|parent_idxs| {
+                let parent_shapes: Vec<B> = if curr_idx == num_layers - 1 {
+                    parent_idxs.iter().map(|x| leaf_shapes[*x].clone()).collect()
+                } else {
+                    parent_idxs.iter().map(|x| self.layers[curr_idx + 1].bvh_shapes[*x].clone()).collect()
+                };
+                if parent_shapes.len() == 1 { parent_shapes[0].clone() } else { B::new_from_combined(&parent_shapes) }
+            }).collect();
+            self.layers[curr_idx].bvh_shapes = updated_shapes;
+        }
+    }
+
+    /// Regroups the last layer for new poses and recomputes the layers above it.  This can change
+    /// the grouping of the last layer, so use `refit` for hierarchies built with `build_sah`.
     pub fn update(&mut self, shapes: &Vec<OffsetShape>, poses: &Vec<ISE3q>) {
         let bvh_shapes = shapes.iter().zip(poses.iter()).map(|(x, y)| B::new_from_offset_shapes(&vec![x.clone()], &vec![y.clone()])).collect();
         *self.layers.last_mut().unwrap() = BvhLayer::build(&bvh_shapes, self.branch_factor);
@@ -233,71 +391,89 @@ impl<B: BvhShape> Bvh<B> {
     }
 
     pub fn intersection_filter(&self, other: &Bvh<B>) -> Vec<(usize, usize)> {
-        let self_num_layers = self.layers.len();
-        let other_num_layers = other.layers.len();
+        self.pair_filter(other, |a, b| a.intersect(b))
+    }
 
-        let mut self_curr_layer = 0;
-        let mut other_curr_layer = 0;
+    pub fn distance_filter(&self, other: &Bvh<B>, distance_threshold: f64) -> Vec<(usize, usize)> {
+        self.pair_filter(other, |a, b| a.signed_distance(b) < distance_threshold)
+    }
 
-        let mut out_list = vec![(0,0)];
+    /// Returns candidate pairs `(i, j)` with `i < j` of shapes in this hierarchy whose bounding
+    /// volumes intersect.  Unlike `self.intersection_filter(&self)`, a subtree is never tested
+    /// against itself and each unordered pair is reported once.
+    pub fn self_intersection_filter(&self) -> Vec<(usize, usize)> {
+        self.self_pair_filter(|a, b| a.intersect(b))
+    }
 
-        loop {
-            let out_list_clone = out_list.clone();
-            let mut curr_out_list = vec![];
-
-            out_list_clone.iter().for_each(|(i,j)| {
-                let self_shape = &self.layers[self_curr_layer].bvh_shapes[*i];
-                let other_shape = &other.layers[other_curr_layer].bvh_shapes[*j];
-                let intersect = self_shape.intersect(other_shape);
-                if intersect {
-                    for self_idx in &self.layers[self_curr_layer].parent_indices[*i] {
-                        for other_idx in &other.layers[other_curr_layer].parent_indices[*j] {
-                            curr_out_list.push( (*self_idx, *other_idx) );
-                        }
+    /// Returns candidate pairs `(i, j)` with `i < j` of shapes in this hierarchy whose bounding
+    /// volumes are closer than `distance_threshold`.
+    pub fn self_distance_filter(&self, distance_threshold: f64) -> Vec<(usize, usize)> {
+        self.self_pair_filter(|a, b| a.signed_distance(b) < distance_threshold)
+    }
+
+    fn pair_filter<F: Fn(&B, &B) -> bool>(&self, other: &Bvh<B>, test: F) -> Vec<(usize, usize)> {
+        let self_last_layer = self.layers.len() - 1;
+        let other_last_layer = other.layers.len() - 1;
+
+        let mut out_list = vec![];
+        let mut stack = vec![(0, 0, 0, 0)];
+
+        while let Some((self_layer, i, other_layer, j)) = stack.pop() {
+            if !test(&self.layers[self_layer].bvh_shapes[i], &other.layers[other_layer].bvh_shapes[j]) { continue; }
+
+            let self_parent_idxs = &self.layers[self_layer].parent_indices[i];
+            let other_parent_idxs = &other.layers[other_layer].parent_indices[j];
+            match (self_layer == self_last_layer, other_layer == other_last_layer) {
+                (true, true) => {
+                    for self_idx in self_parent_idxs {
+                        for other_idx in other_parent_idxs { out_list.push((*self_idx, *other_idx)); }
                     }
                 }
-            });
-
-            out_list = curr_out_list;
-            if self_curr_layer == self_num_layers - 1 && other_curr_layer == other_num_layers - 1 { return out_list; }
-            self_curr_layer = (self_curr_layer + 1).min(self_num_layers - 1);
-            other_curr_layer = (other_curr_layer + 1).min(other_num_layers - 1);
+                (true, false) => {
+                    for other_idx in other_parent_idxs { stack.push((self_layer, i, other_layer + 1, *other_idx)); }
+                }
+                (false, true) => {
+                    for self_idx in self_parent_idxs { stack.push((self_layer + 1, *self_idx, other_layer, j)); }
+                }
+                (false, false) => {
+                    for self_idx in self_parent_idxs {
+                        for other_idx in other_parent_idxs { stack.push((self_layer + 1, *self_idx, other_layer + 1, *other_idx)); }
+                    }
+                }
+            }
         }
 
+        out_list
     }
 
-    pub fn distance_filter(&self, other: &Bvh<B>, distance_threshold: f64) -> Vec<(usize, usize)> {
-        let self_num_layers = self.layers.len();
-        let other_num_layers = other.layers.len();
-
-        let mut self_curr_layer = 0;
-        let mut other_curr_layer = 0;
+    fn self_pair_filter<F: Fn(&B, &B) -> bool>(&self, test: F) -> Vec<(usize, usize)> {
+        let last_layer = self.layers.len() - 1;
 
-        let mut out_list = vec![(0,0)];
+        let mut out_list = vec![];
+        let mut self_stack = vec![(0, 0)];
+        let mut cross_stack = vec![];
 
-        loop {
-            let out_list_clone = out_list.clone();
-            let mut curr_out_list = vec![];
-
-            out_list_clone.iter().for_each(|(i,j)| {
-                let self_shape = &self.layers[self_curr_layer].bvh_shapes[*i];
-                let other_shape = &other.layers[other_curr_layer].bvh_shapes[*j];
-                let distance = self_shape.signed_distance(other_shape);
-                if distance < distance_threshold {
-                    for self_idx in &self.layers[self_curr_layer].parent_indices[*i] {
-                        for other_idx in &other.layers[other_curr_layer].parent_indices[*j] {
-                            curr_out_list.push( (*self_idx, *other_idx) );
-                        }
-                    }
+        while let Some((layer, i)) = self_stack.pop() {
+            let parent_idxs = &self.layers[layer].parent_indices[i];
+            for (k, a) in parent_idxs.iter().enumerate() {
+                for b in &parent_idxs[k + 1..] {
+                    if layer == last_layer { out_list.push(((*a).min(*b), (*a).max(*b))); } else { cross_stack.push((layer + 1, *a, *b)); }
                 }
-            });
+                if layer != last_layer { self_stack.push((layer + 1, *a)); }
+            }
+        }
+
+        while let Some((layer, a, b)) = cross_stack.pop() {
+            if !test(&self.layers[layer].bvh_shapes[a], &self.layers[layer].bvh_shapes[b]) { continue; }
 
-            out_list = curr_out_list;
-            if self_curr_layer == self_num_layers - 1 && other_curr_layer == other_num_layers - 1 { return out_list; }
-            self_curr_layer = (self_curr_layer + 1).min(self_num_layers - 1);
-            other_curr_layer = (other_curr_layer + 1).min(other_num_layers - 1);
+            for x in &self.layers[layer].parent_indices[a] {
+                for y in &self.layers[layer].parent_indices[b] {
+                    if layer == last_layer { out_list.push(((*x).min(*y), (*x).max(*y))); } else { cross_stack.push((layer + 1, *x, *y)); }
+                }
+            }
         }
 
+        out_list
     }
 }
 
@@ -359,3 +535,105 @@ pub fn get_bvh_layer_info<B: BvhShape>(inputs: &Vec<B>, branch_factor: usize) ->
     (out_shapes, out_idxs)
 }
 
+
+struct SahBuildNode<B: BvhShape> {
+    bvh_shape: B,
+    content: SahBuildNodeContent
+}
+
+enum SahBuildNodeContent {
+    Leaf(usize),
+    Internal(usize, usize)
+}
+
+fn sah_build_recursive<B: BvhShape>(leaf_shapes: &Vec<B>, leaf_aabbs: &Vec<Aabb>, idxs: Vec<usize>, nodes: &mut Vec<SahBuildNode<B>>) -> usize {
+    if idxs.len() == 1 {
+        nodes.push(SahBuildNode { bvh_shape: leaf_shapes[idxs[0]].clone(), content: SahBuildNodeContent::Leaf(idxs[0]) });
+        return nodes.len() - 1;
+    }
+
+    let n = idxs.len();
+    // (cost, axis, split) of the best split so far.
+    let mut best: Option<(f64, usize, usize)> = None;
+    let mut sorted_by_axis = vec![];
+    for axis in 0..3 {
+        let mut sorted = idxs.clone();
+        sorted.sort_by(|x, y| leaf_aabbs[*x].center()[axis].total_cmp(&leaf_aabbs[*y].center()[axis]));
+
+        let mut left_areas = vec![0.0; n];
+        let mut acc = leaf_aabbs[sorted[0]];
+        left_areas[0] = aabb_surface_area(&acc);
+        for k in 1..n {
+            acc.merge(&leaf_aabbs[sorted[k]]);
+            left_areas[k] = aabb_surface_area(&acc);
+        }
+
+        let mut right_areas = vec![0.0; n];
+        let mut acc = leaf_aabbs[sorted[n - 1]];
+        right_areas[n - 1] = aabb_surface_area(&acc);
+        for k in (0..n - 1).rev() {
+            acc.merge(&leaf_aabbs[sorted[k]]);
+            right_areas[k] = aabb_surface_area(&acc);
+        }
+
+        for split in 1..n {
+            let cost = left_areas[split - 1] * split as f64 + right_areas[split] * (n - split) as f64;
+            let better = match &best {
+                None => { true }
+                Some((best_cost, _, _)) => { cost < *best_cost }
+            };
+            if better { best = Some((cost, axis, split)); }
+        }
+        sorted_by_axis.push(sorted);
+    }
+
+    let (_, axis, split) = best.expect("a node with at least two shapes has a split");
+    let sorted = &sorted_by_axis[axis];
+    let left = sah_build_recursive(leaf_shapes, leaf_aabbs, sorted[..split].to_vec(), nodes);
+    let right = sah_build_recursive(leaf_shapes, leaf_aabbs, sorted[split..].to_vec(), nodes);
+    let tmp: Vec<B> = sorted.iter().map(|x| leaf_shapes[*x].clone()).collect();
+    nodes.push(SahBuildNode { bvh_shape: B::new_from_combined(&tmp), content: SahBuildNodeContent::Internal(left, right) });
+
+    nodes.len() - 1
+}
+
+fn aabb_surface_area(aabb: &Aabb) -> f64 {
+    let e = aabb.extents();
+    2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+}
+
+fn sah_nodes_to_layers<B: BvhShape>(nodes: &Vec<SahBuildNode<B>>, root: usize) -> Vec<BvhLayer<B>> {
+    let mut layers = vec![];
+    let mut curr_node_idxs = vec![root];
+
+    loop {
+        let all_leaves = curr_node_idxs.iter().all(|x| matches!(nodes[*x].content, SahBuildNodeContent::Leaf(_)));
+
+        let mut bvh_shapes = vec![];
+        let mut parent_indices = vec![];
+        let mut next_node_idxs = vec![];
+        for node_idx in &curr_node_idxs {
+            let node = &nodes[*node_idx];
+            bvh_shapes.push(node.bvh_shape.clone());
+            match &node.content {
+                SahBuildNodeContent::Leaf(shape_idx) => {
+                    if all_leaves {
+                        parent_indices.push(vec![*shape_idx]);
+                    } else {
+                        parent_indices.push(vec![next_node_idxs.len()]);
+                        next_node_idxs.push(*node_idx);
+                    }
+                }
+                SahBuildNodeContent::Internal(left, right) => {
+                    parent_indices.push(vec![next_node_idxs.len(), next_node_idxs.len() + 1]);
+                    next_node_idxs.push(*left);
+                    next_node_idxs.push(*right);
+                }
+            }
+        }
+
+        layers.push(BvhLayer { bvh_shapes, parent_indices });
+        if all_leaves { return layers; }
+        curr_node_idxs = next_node_idxs;
+    }
+}
@@ -1,6 +1,8 @@
 use std::cmp::Ordering;
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use nalgebra::DMatrix;
+use rayon::prelude::*;
 use parry3d_f64::query::{Contact, contact, distance, intersection_test};
 use parry3d_f64::shape::Shape;
 use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
@@ -24,6 +26,19 @@ impl<T: Clone + Debug> DoubleGroupProximityQueryOutput<T> {
     pub fn new(outputs: Vec<T>, shape_idxs: Vec<(usize, usize)>, num_ground_truth_checks: usize) -> Self {
         Self { outputs, shape_idxs, num_ground_truth_checks }
     }
+
+    /// Maps every shape index pair of the output, e.g., to offset indices into a larger group.
+    pub fn map_shape_idxs<F: Fn((usize, usize)) -> (usize, usize)>(mut self, f: F) -> Self {
+        self.shape_idxs.iter_mut().for_each(|x| *x = f(*x));
+        self
+    }
+
+    /// Appends the outputs and ground truth checks of another query output.
+    pub fn extend(&mut self, other: DoubleGroupProximityQueryOutput<T>) {
+        self.outputs.extend(other.outputs);
+        self.shape_idxs.extend(other.shape_idxs);
+        self.num_ground_truth_checks += other.num_ground_truth_checks;
+    }
 }
 
 impl ToIntersectionResult for DoubleGroupProximityQueryOutput<bool> {
@@ -215,7 +230,7 @@ macro_rules! create_double_group_query {
                 let ppa = sa.get_transform(pa);
                 let ppb = sb.get_transform(pb);
 
-                let res = $query_func_code(&*ppa, &**sa.shape(), &*ppb, &**sb.shape(), &extra_args);
+                let res = $query_func_code(&*ppa, &**sa.shape(), &*ppb, &**sb.shape(), (i, j), &extra_args);
                 if $push_code(&res) {
                     outputs.push(res);
                     shape_idxs.push((i,j));
@@ -270,10 +285,87 @@ macro_rules! create_double_group_query {
     };
 }
 
+/// Lists the pairs visited by a query in the order the sequential queries visit them, leaving out
+/// pairs marked in `skips`.
+pub fn get_double_group_query_pairs(num_a: usize, num_b: usize, query_mode: &DoubleGroupProximityQueryMode, skips: Option<&DMatrix<bool>>) -> Vec<(usize, usize)> {
+    let is_skipped = |i: usize, j: usize| -> bool {
+        match skips {
+            None => { false }
+            Some(skips) => { skips[(i, j)] }
+        }
+    };
+
+    let mut out = vec![];
+    match query_mode {
+        DoubleGroupProximityQueryMode::AllPossiblePairs => {
+            for i in 0..num_a {
+                for j in 0..num_b { if !is_skipped(i, j) { out.push((i, j)); } }
+            }
+        }
+        DoubleGroupProximityQueryMode::SkipSymmetricalPairs => {
+            for i in 0..num_a {
+                for j in 0..num_b { if i < j && !is_skipped(i, j) { out.push((i, j)); } }
+            }
+        }
+        DoubleGroupProximityQueryMode::SubsetOfPairs(v) => {
+            v.iter().for_each(|(i, j)| { if !is_skipped(*i, *j) { out.push((*i, *j)); } });
+        }
+    }
+
+    out
+}
+
+/// Same as `create_double_group_query`, but evaluates the pairs in parallel with rayon.
+///
+/// Outputs are always reported in the order of the sequential query.  With `early_stop`, work on
+/// pairs that come after the first stopping pair is cancelled, and the output is truncated at that
+/// pair, so the result is identical to the one of the sequential query.
+macro_rules! create_double_group_query_parallel {
+    ($func_name: ident, $output_type: ty, $query_func_code: expr, $push_code: expr, $early_stop_code: expr, $extra_args: ty) => {
+
+        pub fn $func_name(group_a: &Vec<OffsetShape>, poses_a: &Vec<ISE3q>, group_b: &Vec<OffsetShape>, poses_b: &Vec<ISE3q>, query_mode: &DoubleGroupProximityQueryMode, skips: Option<&DMatrix<bool>>, early_stop: bool, extra_args: $extra_args) -> DoubleGroupProximityQueryOutput<$output_type> {
+            assert_eq!(group_a.len(), poses_a.len());
+            assert_eq!(group_b.len(), poses_b.len());
+
+            let pairs = get_double_group_query_pairs(group_a.len(), group_b.len(), query_mode, skips);
+            let first_stop_idx = AtomicUsize::new(usize::MAX);
+
+            let results: Vec<Option<$output_type>> = pairs.par_iter().enumerate().map(|(k, (i, j))| {
+                if early_stop && k > first_stop_idx.load(AtomicOrdering::Relaxed) { return None; }
+
+                let sa = &group_a[*i];
+                let sb = &group_b[*j];
+                let ppa = sa.get_transform(&poses_a[*i]);
+                let ppb = sb.get_transform(&poses_b[*j]);
+
+                let res = $query_func_code(&*ppa, &**sa.shape(), &*ppb, &**sb.shape(), (*i, *j), &extra_args);
+                if early_stop && $early_stop_code(&res) { first_stop_idx.fetch_min(k, AtomicOrdering::Relaxed); }
+
+                Some(res)
+            }).collect();
+
+            let num_ground_truth_checks = if early_stop { first_stop_idx.load(AtomicOrdering::Relaxed).saturating_add(1).min(pairs.len()) } else { pairs.len() };
+
+            let mut outputs = vec![];
+            let mut shape_idxs = vec![];
+            results.into_iter().take(num_ground_truth_checks).enumerate().for_each(|(k, res)| {
+                // every pair before the first stopping pair is guaranteed to have been evaluated.
+                let res = res.expect("error");
+                if $push_code(&res) {
+                    outputs.push(res);
+                    shape_idxs.push(pairs[k]);
+                }
+            });
+
+            DoubleGroupProximityQueryOutput::new(outputs, shape_idxs, num_ground_truth_checks)
+        }
+    };
+}
+
 create_double_group_query!(
     pairwise_group_query_intersection,
     bool,
-    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _extra_args: &()|
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _pair: (usize, usize), _extra_args: &()|
         {
             intersection_test(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error")
         },
@@ -291,7 +383,7 @@ create_double_group_query!(
 create_double_group_query!(
     pairwise_group_query_distance,
     f64,
-    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _extra_args: &()|
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _pair: (usize, usize), _extra_args: &()|
         {
             distance(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error")
         },
@@ -309,7 +401,7 @@ create_double_group_query!(
 create_double_group_query!(
     pairwise_group_query_contact,
     Option<Contact>,
-    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, extra_args: &f64|
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _pair: (usize, usize), extra_args: &f64|
         {
             contact(&pose_a.0, shape_a, &pose_b.0, shape_b, *extra_args).expect("error")
         },
@@ -328,6 +420,128 @@ create_double_group_query!(
 );
 
 
+create_double_group_query_parallel!(
+    pairwise_group_query_intersection_parallel,
+    bool,
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _pair: (usize, usize), _extra_args: &()|
+        {
+            intersection_test(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error")
+        },
+    |res: &bool|
+        {
+            return if *res { true } else { false }
+        },
+    |res: &bool |
+        {
+            return if *res { true } else { false }
+        },
+    ()
+);
+
+create_double_group_query_parallel!(
+    pairwise_group_query_distance_parallel,
+    f64,
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _pair: (usize, usize), _extra_args: &()|
+        {
+            distance(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error")
+        },
+    |_res: &f64|
+        {
+            return true;
+        },
+    |res: &f64 |
+        {
+            return if *res <= 0.0 { true } else { false }
+        },
+    ()
+);
+
+create_double_group_query_parallel!(
+    pairwise_group_query_contact_parallel,
+    Option<Contact>,
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, _pair: (usize, usize), extra_args: &f64|
+        {
+            contact(&pose_a.0, shape_a, &pose_b.0, shape_b, *extra_args).expect("error")
+        },
+    |_res: &Option<Contact> |
+        {
+            return true;
+        },
+    |res: &Option<Contact> |
+        {
+            if let Some(c) = res {
+                if c.dist <= 0.0 { return true; }
+            }
+            return false;
+        },
+    f64
+);
+
+// Padded queries take a matrix with a clearance per pair of shapes.  Each pair is treated as if
+// the shapes were inflated by that clearance, i.e., it is subtracted from all reported distances.
+
+create_double_group_query!(
+    pairwise_group_query_intersection_padded,
+    bool,
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, pair: (usize, usize), extra_args: &&DMatrix<f64>|
+        {
+            let padding = extra_args[pair];
+            if padding == 0.0 { intersection_test(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error") }
+            else { distance(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error") <= padding }
+        },
+    |res: &bool|
+        {
+            return if *res { true } else { false }
+        },
+    |res: &bool |
+        {
+            return if *res { true } else { false }
+        },
+    &DMatrix<f64>
+);
+
+create_double_group_query!(
+    pairwise_group_query_distance_padded,
+    f64,
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, pair: (usize, usize), extra_args: &&DMatrix<f64>|
+        {
+            distance(&pose_a.0, shape_a, &pose_b.0, shape_b).expect("error") - extra_args[pair]
+        },
+    |_res: &f64|
+        {
+            return true;
+        },
+    |res: &f64 |
+        {
+            return if *res <= 0.0 { true } else { false }
+        },
+    &DMatrix<f64>
+);
+
+create_double_group_query!(
+    pairwise_group_query_contact_padded,
+    Option<Contact>,
+    |pose_a: &ISE3q, shape_a: &dyn Shape, pose_b: &ISE3q, shape_b: &dyn Shape, pair: (usize, usize), extra_args: &(&DMatrix<f64>, f64)|
+        {
+            let padding = extra_args.0[pair];
+            let mut res = contact(&pose_a.0, shape_a, &pose_b.0, shape_b, extra_args.1 + padding).expect("error");
+            if let Some(c) = &mut res { c.dist -= padding; }
+            res
+        },
+    |_res: &Option<Contact> |
+        {
+            return true;
+        },
+    |res: &Option<Contact> |
+        {
+            if let Some(c) = res {
+                if c.dist <= 0.0 { return true; }
+            }
+            return false;
+        },
+    (&DMatrix<f64>, f64)
+);
+
 /*
 pub trait ToAverageDistancesF64 {
     fn to_average_distances(&self, average_distances: &DMatrix<f64>) -> Vec<((usize, usize), f64)>;
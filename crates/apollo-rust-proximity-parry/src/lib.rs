@@ -1,20 +1,129 @@
 use std::fmt::Debug;
+use nalgebra::{convert, RealField};
 
 pub mod double_group_queries;
 pub mod offset_shape;
 pub mod single_group_queries;
 pub mod proxima;
 pub mod bvh;
+pub mod sweep_and_prune;
+pub mod ray_casting;
 
+/// Loss applied to each pairwise distance before aggregation with a p-norm.
+///
+/// All variants are non-negative and non-increasing in the distance.  Except for `Hinge`, each
+/// variant is continuously differentiable, which makes it suitable for gradient-based
+/// optimization.  The barrier variants return infinity for non-positive distances.
+#[derive(Clone, Debug)]
 pub enum ProximityLossFunction {
-    Hinge { threshold: f64 }
+    /// `max(threshold - d, 0)`.
+    Hinge { threshold: f64 },
+    /// Softplus relaxation of the hinge, `ln(1 + exp(sharpness * (threshold - d))) / sharpness`.
+    /// Approaches `Hinge` as `sharpness` grows.
+    SmoothHinge { threshold: f64, sharpness: f64 },
+    /// Clamped log barrier, `-(d - threshold)^2 * ln(d / threshold)` for `0 < d < threshold`.
+    /// Value and slope are zero at the threshold.
+    LogBarrier { threshold: f64 },
+    /// Clamped inverse barrier, `(1/d - 1/threshold)^2` for `0 < d < threshold`.
+    /// Value and slope are zero at the threshold.
+    InverseBarrier { threshold: f64 },
+    /// `exp((threshold - d) / scale)`, equal to one at the threshold.
+    Exponential { threshold: f64, scale: f64 },
+    /// Gaussian bump `exp(-(d - threshold)^2 / (2 sigma^2))` above the threshold, mirrored below
+    /// it as `2 - exp(-(d - threshold)^2 / (2 sigma^2))` so the loss keeps rising with penetration.
+    Gaussian { threshold: f64, sigma: f64 }
 }
 impl ProximityLossFunction {
-    pub fn loss(&self, value: f64) ->  f64 {
+    /// The loss of a distance.  Generic over the scalar type, so the same loss configuration can
+    /// be used with `f64` and with automatic differentiation types inside differentiable
+    /// objectives.
+    pub fn loss<T: RealField + Copy>(&self, value: T) -> T {
+        let c = |x: f64| -> T { convert(x) };
+        let zero = c(0.0);
+        let one = c(1.0);
+        let two = c(2.0);
+
         return match self {
             ProximityLossFunction::Hinge { threshold } => {
-                if value <= *threshold { *threshold - value } else { 0.0 }
+                let threshold = c(*threshold);
+                if value <= threshold { threshold - value } else { zero }
+            }
+            ProximityLossFunction::SmoothHinge { threshold, sharpness } => {
+                let sharpness = c(*sharpness);
+                let x = sharpness * (c(*threshold) - value);
+                if x > zero { (x + (-x).exp().ln_1p()) / sharpness } else { x.exp().ln_1p() / sharpness }
+            }
+            ProximityLossFunction::LogBarrier { threshold } => {
+                let threshold = c(*threshold);
+                if value <= zero { c(f64::INFINITY) } else if value >= threshold { zero } else { -(value - threshold).powi(2) * (value / threshold).ln() }
+            }
+            ProximityLossFunction::InverseBarrier { threshold } => {
+                let threshold = c(*threshold);
+                if value <= zero { c(f64::INFINITY) } else if value >= threshold { zero } else { (one / value - one / threshold).powi(2) }
+            }
+            ProximityLossFunction::Exponential { threshold, scale } => {
+                ((c(*threshold) - value) / c(*scale)).exp()
             }
+            ProximityLossFunction::Gaussian { threshold, sigma } => {
+                let threshold = c(*threshold);
+                let sigma = c(*sigma);
+                let g = (-(value - threshold).powi(2) / (two * sigma * sigma)).exp();
+                if value >= threshold { g } else { two - g }
+            }
+        }
+    }
+
+    /// Derivative of `loss` with respect to the distance.  Generic over the scalar type in the
+    /// same way as `loss`.
+    pub fn derivative<T: RealField + Copy>(&self, value: T) -> T {
+        let c = |x: f64| -> T { convert(x) };
+        let zero = c(0.0);
+        let one = c(1.0);
+        let two = c(2.0);
+
+        return match self {
+            ProximityLossFunction::Hinge { threshold } => {
+                if value <= c(*threshold) { -one } else { zero }
+            }
+            ProximityLossFunction::SmoothHinge { threshold, sharpness } => {
+                let x = c(*sharpness) * (c(*threshold) - value);
+                if x > zero { -one / (one + (-x).exp()) } else { -x.exp() / (one + x.exp()) }
+            }
+            ProximityLossFunction::LogBarrier { threshold } => {
+                let threshold = c(*threshold);
+                if value <= zero { c(f64::NEG_INFINITY) } else if value >= threshold { zero } else {
+                    let d = value - threshold;
+                    -two * d * (value / threshold).ln() - d * d / value
+                }
+            }
+            ProximityLossFunction::InverseBarrier { threshold } => {
+                let threshold = c(*threshold);
+                if value <= zero { c(f64::NEG_INFINITY) } else if value >= threshold { zero } else {
+                    -two * (one / value - one / threshold) / (value * value)
+                }
+            }
+            ProximityLossFunction::Exponential { threshold, scale } => {
+                let scale = c(*scale);
+                -((c(*threshold) - value) / scale).exp() / scale
+            }
+            ProximityLossFunction::Gaussian { threshold, sigma } => {
+                let d = value - c(*threshold);
+                let sigma = c(*sigma);
+                let dg = -d / (sigma * sigma) * (-d * d / (two * sigma * sigma)).exp();
+                if d >= zero { dg } else { -dg }
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn threshold(&self) -> f64 {
+        return match self {
+            ProximityLossFunction::Hinge { threshold } => { *threshold }
+            ProximityLossFunction::SmoothHinge { threshold, .. } => { *threshold }
+            ProximityLossFunction::LogBarrier { threshold } => { *threshold }
+            ProximityLossFunction::InverseBarrier { threshold } => { *threshold }
+            ProximityLossFunction::Exponential { threshold, .. } => { *threshold }
+            ProximityLossFunction::Gaussian { threshold, .. } => { *threshold }
         }
     }
 }
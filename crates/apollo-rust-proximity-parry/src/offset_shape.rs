@@ -4,10 +4,12 @@ use std::borrow::Cow;
 use nalgebra::Point3;
 use parry3d_f64::na::UnitQuaternion;
 use parry3d_f64::query::{Contact, contact, distance, intersection_test};
-use parry3d_f64::shape::{Ball, Cuboid, Shape, TypedShape};
+use parry3d_f64::math::{Isometry, Point};
+use parry3d_f64::query::PointQuery;
+use parry3d_f64::shape::{Ball, Capsule, Compound, ConvexPolyhedron, Cuboid, Shape, SharedShape, TypedShape};
 use apollo_rust_spatial::isometry3::I3;
 use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
-use apollo_rust_spatial::vectors::ApolloVector3Trait2;
+use apollo_rust_spatial::vectors::{ApolloVector3Trait2, V3};
 use apollo_rust_lie::LieGroupElement;
 
 pub struct OffsetShape {
@@ -42,23 +44,7 @@ impl OffsetShape {
     }
 
     pub fn calculate_max_dis_from_origin_to_point_on_shape(&self) -> f64 {
-        let ts = self.shape.as_typed_shape();
-
-        let num_subdivisions = 50;
-        let (points, _) = match ts {
-            TypedShape::Ball(shape) => { shape.to_trimesh(num_subdivisions, num_subdivisions) }
-            TypedShape::Cuboid(shape) => { shape.to_trimesh() }
-            TypedShape::Capsule(shape) => { shape.to_trimesh(num_subdivisions, num_subdivisions) }
-            TypedShape::TriMesh(shape) => {
-                let points = shape.vertices();
-                let out_points: Vec<Point3<f64>> = points.iter().map(|x| Point3::new(x[0], x[1], x[2])).collect();
-                (out_points, vec![])
-            }
-            TypedShape::ConvexPolyhedron(shape) => { shape.to_trimesh() }
-            TypedShape::Cylinder(shape) => { shape.to_trimesh(num_subdivisions) }
-            TypedShape::Cone(shape) => { shape.to_trimesh(num_subdivisions) }
-            _ => { panic!("shape type unsupported"); }
-        };
+        let points = shape_to_points(&*self.shape);
 
         let transformed_points = match &self.offset {
             None => { points.clone() }
@@ -122,3 +108,242 @@ pub fn to_offset_shape_obb<S: Shape>(shape: &S) -> OffsetShape {
     OffsetShape::new(cuboid, Some(offset))
 }
 
+
+/// Fits a capsule enclosing the given points, e.g., the vertices of a link mesh.
+///
+/// The capsule is aligned with the local y axis of its offset, matching `Capsule::new_y`.
+pub fn to_offset_shape_capsule(points: &Vec<V3>) -> OffsetShape {
+    let (center, axis, half_length, radius) = fit_capsule(points);
+
+    let rotation = UnitQuaternion::rotation_between(&V3::y(), &axis)
+        .unwrap_or(UnitQuaternion::from_axis_angle(&V3::x_axis(), std::f64::consts::PI));
+    let offset = ISE3q::new(I3::from_parts(center.to_translation(), rotation));
+
+    OffsetShape::new(Capsule::new_y(half_length, radius), Some(offset))
+}
+
+/// Fits a capsule enclosing all given points, returned as `(center, unit axis, half_length, radius)`.
+///
+/// The axis starts as the principal axis of the points and is refined by tilting it in small,
+/// shrinking steps while the capsule volume decreases.
+pub fn fit_capsule(points: &Vec<V3>) -> (V3, V3, f64, f64) {
+    assert!(!points.is_empty());
+    let mean = points.iter().fold(V3::zeros(), |acc, x| acc + x) / points.len() as f64;
+
+    let mut cov = nalgebra::Matrix3::zeros();
+    points.iter().for_each(|x| { let d = x - mean; cov += d * d.transpose(); });
+    let eigen = cov.symmetric_eigen();
+    let principal_idx = eigen.eigenvalues.imax();
+    let axis = eigen.eigenvectors.column(principal_idx).into_owned();
+
+    let (mut best, mut best_volume) = fit_capsule_along_axis(points, &mean, &axis);
+    let mut step = 0.1;
+    for _ in 0..20 {
+        let axis = best.1;
+        let u = if axis.x.abs() < 0.9 { V3::x() } else { V3::y() }.cross(&axis).normalize();
+        let v = axis.cross(&u);
+
+        let mut improved = false;
+        for dir in [u, -u, v, -v] {
+            let (candidate, volume) = fit_capsule_along_axis(points, &mean, &(axis + dir * step));
+            if volume < best_volume {
+                best = candidate;
+                best_volume = volume;
+                improved = true;
+            }
+        }
+
+        if !improved {
+            step /= 2.0;
+            if step < 1e-3 { break; }
+        }
+    }
+
+    best
+}
+
+/// Fits a capsule with the given axis enclosing all points, returned with its volume.
+///
+/// The radial center is the approximate minimum enclosing circle of the points projected onto
+/// the plane orthogonal to the axis, and a few radii above the smallest feasible one are tried,
+/// since a fatter capsule can be shorter.
+fn fit_capsule_along_axis(points: &Vec<V3>, mean: &V3, axis: &V3) -> ((V3, V3, f64, f64), f64) {
+    let axis = axis.normalize();
+    let radial = |x: &V3| { let d = x - mean; d - axis * d.dot(&axis) };
+
+    // Badoiu-Clarkson iterations for the minimum enclosing circle in the orthogonal plane.
+    let mut c = V3::zeros();
+    for k in 0..100 {
+        let far = points.iter().map(|x| radial(x)).max_by(|a, b| (a - c).norm().partial_cmp(&(b - c).norm()).unwrap()).expect("error");
+        c += (far - c) / (k + 2) as f64;
+    }
+    let min_radius = points.iter().map(|x| (radial(x) - c).norm()).fold(0.0_f64, |acc, x| acc.max(x));
+
+    let mut best = (*mean, axis, 0.0, f64::INFINITY);
+    let mut best_volume = f64::INFINITY;
+    for scale in [1.0, 1.05, 1.1, 1.2, 1.35, 1.5, 1.75, 2.0] {
+        let radius = (min_radius * scale).max(f64::EPSILON);
+        // each point constrains the segment to reach within its spherical cap.
+        let mut t_max = f64::NEG_INFINITY;
+        let mut t_min = f64::INFINITY;
+        points.iter().for_each(|x| {
+            let t = (x - mean).dot(&axis);
+            let perp = (radial(x) - c).norm();
+            let slack = (radius * radius - perp * perp).max(0.0).sqrt();
+            t_max = t_max.max(t - slack);
+            t_min = t_min.min(t + slack);
+        });
+        let (t_center, half_length) = if t_max > t_min { ((t_max + t_min) / 2.0, (t_max - t_min) / 2.0) } else { ((t_max + t_min) / 2.0, 0.0) };
+
+        let volume = std::f64::consts::PI * radius * radius * (2.0 * half_length) + 4.0 / 3.0 * std::f64::consts::PI * radius.powi(3);
+        if volume < best_volume {
+            best_volume = volume;
+            best = (mean + c + axis * t_center, axis, half_length, radius);
+        }
+    }
+
+    (best, best_volume)
+}
+
+/// Approximates a convex polyhedron with a set of at most `num_spheres` balls, stored as a compound shape.
+///
+/// See `fit_multi_spheres` for the meaning of `tightness`.
+pub fn to_offset_shape_multi_sphere(shape: &ConvexPolyhedron, num_spheres: usize, tightness: f64) -> OffsetShape {
+    let spheres = fit_multi_spheres(shape, num_spheres, tightness);
+    let parts = spheres.iter().map(|(center, radius)| {
+        (Isometry::translation(center[0], center[1], center[2]), SharedShape::ball(*radius))
+    }).collect();
+
+    OffsetShape::new(Compound::new(parts), None)
+}
+
+/// Fits at most `num_spheres` spheres to a convex polyhedron, returned as `(center, radius)` pairs
+/// in the shape's local frame.
+///
+/// Points are sampled on the surface and in the interior of the polyhedron and grouped with
+/// k-means, starting from a deterministic farthest-point initialization.  Each group gets a sphere
+/// centered at its mean.  With `tightness = 0`, each sphere is grown to reach the farthest sample
+/// assigned to it, so every vertex of the polyhedron is guaranteed to lie in some sphere, and the
+/// rest of the polyhedron is covered up to sampling resolution.  Raising `tightness` toward 1
+/// shrinks each radius toward the mean distance to its group, trading coverage for a tighter fit.
+pub fn fit_multi_spheres(shape: &ConvexPolyhedron, num_spheres: usize, tightness: f64) -> Vec<(V3, f64)> {
+    assert!(num_spheres > 0);
+    let tightness = tightness.clamp(0.0, 1.0);
+
+    let (vertices, indices) = shape.to_trimesh();
+    let mut samples: Vec<V3> = vertices.iter().map(|x| x.coords).collect();
+    indices.iter().for_each(|t| {
+        let a = vertices[t[0] as usize].coords;
+        let b = vertices[t[1] as usize].coords;
+        let c = vertices[t[2] as usize].coords;
+        samples.push((a + b + c) / 3.0);
+        samples.push((a + b) / 2.0);
+        samples.push((b + c) / 2.0);
+        samples.push((a + c) / 2.0);
+    });
+
+    let num_grid_cells = 8;
+    let aabb = shape.local_aabb();
+    let extents = aabb.extents();
+    for i in 0..num_grid_cells {
+        for j in 0..num_grid_cells {
+            for k in 0..num_grid_cells {
+                let p = Point::new(
+                    aabb.mins[0] + extents[0] * (i as f64 + 0.5) / num_grid_cells as f64,
+                    aabb.mins[1] + extents[1] * (j as f64 + 0.5) / num_grid_cells as f64,
+                    aabb.mins[2] + extents[2] * (k as f64 + 0.5) / num_grid_cells as f64,
+                );
+                if shape.contains_local_point(&p) { samples.push(p.coords); }
+            }
+        }
+    }
+
+    let k = num_spheres.min(samples.len());
+    let mean = samples.iter().fold(V3::zeros(), |acc, x| acc + x) / samples.len() as f64;
+
+    // farthest point initialization
+    let mut centers: Vec<V3> = vec![];
+    let first = samples.iter().max_by(|a, b| (*a - mean).norm().partial_cmp(&(*b - mean).norm()).unwrap()).expect("error");
+    centers.push(first.clone());
+    let mut min_dis: Vec<f64> = samples.iter().map(|x| (x - first).norm()).collect();
+    while centers.len() < k {
+        let (idx, _) = min_dis.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).expect("error");
+        let c = samples[idx].clone();
+        samples.iter().enumerate().for_each(|(i, x)| { min_dis[i] = min_dis[i].min((x - c).norm()); });
+        centers.push(c);
+    }
+
+    let mut assignments = vec![0usize; samples.len()];
+    for _ in 0..30 {
+        let mut changed = false;
+        samples.iter().enumerate().for_each(|(i, x)| {
+            let mut best = 0;
+            let mut best_dis = f64::INFINITY;
+            centers.iter().enumerate().for_each(|(j, c)| {
+                let d = (x - c).norm_squared();
+                if d < best_dis { best_dis = d; best = j; }
+            });
+            if assignments[i] != best { assignments[i] = best; changed = true; }
+        });
+
+        let mut sums = vec![V3::zeros(); k];
+        let mut counts = vec![0usize; k];
+        samples.iter().zip(assignments.iter()).for_each(|(x, a)| { sums[*a] += x; counts[*a] += 1; });
+        for j in 0..k {
+            if counts[j] > 0 { centers[j] = sums[j] / counts[j] as f64; }
+        }
+
+        if !changed { break; }
+    }
+
+    // the centers moved after the last assignment, so assign every sample to its final nearest center.
+    samples.iter().enumerate().for_each(|(i, x)| {
+        let mut best = 0;
+        let mut best_dis = f64::INFINITY;
+        centers.iter().enumerate().for_each(|(j, c)| {
+            let d = (x - c).norm_squared();
+            if d < best_dis { best_dis = d; best = j; }
+        });
+        assignments[i] = best;
+    });
+
+    let mut out = vec![];
+    for j in 0..k {
+        let distances: Vec<f64> = samples.iter().zip(assignments.iter()).filter(|(_, a)| **a == j).map(|(x, _)| (x - centers[j]).norm()).collect();
+        if distances.is_empty() { continue; }
+        let max = distances.iter().fold(0.0_f64, |acc, x| acc.max(*x));
+        let avg = distances.iter().sum::<f64>() / distances.len() as f64;
+        let radius = ((1.0 - tightness) * max + tightness * avg).max(f64::EPSILON);
+        out.push((centers[j].clone(), radius));
+    }
+
+    out
+}
+
+/// Returns points on the surface of the given shape in its local frame.
+fn shape_to_points(shape: &dyn Shape) -> Vec<Point3<f64>> {
+    let num_subdivisions = 50;
+    let (points, _) = match shape.as_typed_shape() {
+        TypedShape::Ball(shape) => { shape.to_trimesh(num_subdivisions, num_subdivisions) }
+        TypedShape::Cuboid(shape) => { shape.to_trimesh() }
+        TypedShape::Capsule(shape) => { shape.to_trimesh(num_subdivisions, num_subdivisions) }
+        TypedShape::TriMesh(shape) => {
+            let points = shape.vertices();
+            let out_points: Vec<Point3<f64>> = points.iter().map(|x| Point3::new(x[0], x[1], x[2])).collect();
+            (out_points, vec![])
+        }
+        TypedShape::ConvexPolyhedron(shape) => { shape.to_trimesh() }
+        TypedShape::Cylinder(shape) => { shape.to_trimesh(num_subdivisions) }
+        TypedShape::Cone(shape) => { shape.to_trimesh(num_subdivisions) }
+        TypedShape::Compound(shape) => {
+            let mut out_points = vec![];
+            shape.shapes().iter().for_each(|(pose, part)| {
+                shape_to_points(&**part).iter().for_each(|x| out_points.push(pose * x));
+            });
+            (out_points, vec![])
+        }
+        _ => { panic!("shape type unsupported"); }
+    };
+
+    points
+}
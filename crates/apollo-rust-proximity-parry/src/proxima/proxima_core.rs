@@ -16,7 +16,9 @@ pub trait ProximaTrait {
     fn get_cache_mut(&mut self) -> &mut Self::CacheType;
     fn get_cache_immut(&self) -> &Self::CacheType;
     fn approximate_distance_and_bounds(cache_element: &Self::CacheElementType, pose_a_k: &ISE3q, pose_b_k: &ISE3q, cutoff_distance: f64, extra_args: &Self::ExtraArgs) -> Option<(f64, f64, f64)>;
-    fn f(&self, cache: &Self::CacheType, i: usize, pa: &ISE3q, j: usize, pb: &ISE3q, skips: Option<&DMatrix<bool>>, cutoff_distance: f64) -> Option<ProximaPairwiseOutput> {
+    /// `paddings`, if given, holds a clearance per pair of shapes that is subtracted from all
+    /// distances, i.e., the pair is treated as if both shapes were inflated by that amount.
+    fn f(&self, cache: &Self::CacheType, i: usize, pa: &ISE3q, j: usize, pb: &ISE3q, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, cutoff_distance: f64) -> Option<ProximaPairwiseOutput> {
         match skips {
             None => {  }
             Some(skips) => {
@@ -24,9 +26,14 @@ pub trait ProximaTrait {
             }
         }
 
+        let padding = match paddings {
+            None => { 0.0 }
+            Some(paddings) => { paddings[(i,j)] }
+        };
+
         let cache_element = &cache.elements()[(i,j)];
         let extra_args = self.get_extra_args(i, j);
-        let res = Self::approximate_distance_and_bounds(cache_element, pa, pb, cutoff_distance, extra_args.as_ref());
+        let res = Self::approximate_distance_and_bounds(cache_element, pa, pb, cutoff_distance + padding, extra_args.as_ref());
 
         match res {
             None => { None }
@@ -34,14 +41,14 @@ pub trait ProximaTrait {
                 Some(ProximaPairwiseOutput {
                     shape_indices: (i, j),
                     distance_mode: DistanceMode::RawDistance,
-                    approximate_distance: res.0,
-                    lower_bound_distance: res.1,
-                    upper_bound_distance: res.2,
+                    approximate_distance: res.0 - padding,
+                    lower_bound_distance: res.1 - padding,
+                    upper_bound_distance: res.2 - padding,
                 })
             }
         }
     }
-    fn get_all_proxima_outputs_for_proximity(&self, poses_a: &Vec<ISE3q>, poses_b: &Vec<ISE3q>, query_mode: &DoubleGroupProximityQueryMode, skips: Option<&DMatrix<bool>>, average_distances: Option<&DMatrix<f64>>, cutoff_distance: f64) -> Vec<ProximaPairwiseOutput> {
+    fn get_all_proxima_outputs_for_proximity(&self, poses_a: &Vec<ISE3q>, poses_b: &Vec<ISE3q>, query_mode: &DoubleGroupProximityQueryMode, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, average_distances: Option<&DMatrix<f64>>, cutoff_distance: f64) -> Vec<ProximaPairwiseOutput> {
         let mut out = vec![];
 
         let cache = self.get_cache_immut();
@@ -49,7 +56,7 @@ pub trait ProximaTrait {
             DoubleGroupProximityQueryMode::AllPossiblePairs => {
                 for (i, pa) in poses_a.iter().enumerate() {
                     for (j, pb) in poses_b.iter().enumerate() {
-                        let output = self.f(cache, i, pa, j, pb, skips, cutoff_distance);
+                        let output = self.f(cache, i, pa, j, pb, skips, paddings, cutoff_distance);
                         match output {
                             None => {}
                             Some(output) => { out.push(output); }
@@ -61,7 +68,7 @@ pub trait ProximaTrait {
                 for (i, pa) in poses_a.iter().enumerate() {
                     'l: for (j, pb) in poses_b.iter().enumerate() {
                         if i >= j { continue 'l; }
-                        let output = self.f(cache, i, pa, j, pb, skips, cutoff_distance);
+                        let output = self.f(cache, i, pa, j, pb, skips, paddings, cutoff_distance);
                         match output {
                             None => {}
                             Some(output) => { out.push(output); }
@@ -73,7 +80,7 @@ pub trait ProximaTrait {
                 for (i,j) in v {
                     let pa = &poses_a[*i];
                     let pb = &poses_b[*j];
-                    let output = self.f(cache, *i, pa, *j, pb, skips, cutoff_distance);
+                    let output = self.f(cache, *i, pa, *j, pb, skips, paddings, cutoff_distance);
                     match output {
                         None => {}
                         Some(output) => { out.push(output); }
@@ -89,7 +96,7 @@ pub trait ProximaTrait {
 
         out
     }
-    fn get_all_proxima_outputs_for_intersection(&self, poses_a: &Vec<ISE3q>, poses_b: &Vec<ISE3q>, query_mode: &DoubleGroupProximityQueryMode, skips: Option<&DMatrix<bool>>, cutoff_distance: f64) -> Option<Vec<ProximaPairwiseOutput>> {
+    fn get_all_proxima_outputs_for_intersection(&self, poses_a: &Vec<ISE3q>, poses_b: &Vec<ISE3q>, query_mode: &DoubleGroupProximityQueryMode, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, cutoff_distance: f64) -> Option<Vec<ProximaPairwiseOutput>> {
         let mut out = vec![];
 
         let cache = self.get_cache_immut();
@@ -97,7 +104,7 @@ pub trait ProximaTrait {
             DoubleGroupProximityQueryMode::AllPossiblePairs => {
                 for (i, pa) in poses_a.iter().enumerate() {
                     for (j, pb) in poses_b.iter().enumerate() {
-                        let output = self.f(cache, i, pa, j, pb, skips, cutoff_distance);
+                        let output = self.f(cache, i, pa, j, pb, skips, paddings, cutoff_distance);
                         match output {
                             None => { }
                             Some(output) => {
@@ -112,7 +119,7 @@ pub trait ProximaTrait {
                 for (i, pa) in poses_a.iter().enumerate() {
                     'l: for (j, pb) in poses_b.iter().enumerate() {
                         if i >= j { continue 'l; }
-                        let output = self.f(cache, i, pa, j, pb, skips, cutoff_distance);
+                        let output = self.f(cache, i, pa, j, pb, skips, paddings, cutoff_distance);
                         match output {
                             None => { }
                             Some(output) => {
@@ -127,7 +134,7 @@ pub trait ProximaTrait {
                 for (i,j) in v {
                     let pa = &poses_a[*i];
                     let pb = &poses_b[*j];
-                    let output = self.f(cache, *i, pa, *j, pb, skips, cutoff_distance);
+                    let output = self.f(cache, *i, pa, *j, pb, skips, paddings, cutoff_distance);
                     match output {
                         None => { }
                         Some(output) => {
@@ -152,10 +159,11 @@ pub trait ProximaTrait {
                              p_norm: f64,
                              cutoff_distance: f64,
                              skips: Option<&DMatrix<bool>>,
+                             paddings: Option<&DMatrix<f64>>,
                              average_distances: Option<&DMatrix<f64>>,
                              frozen: bool) -> ProximaOutput<f64> {
         let start = Instant::now();
-        let mut proxima_outputs = self.get_all_proxima_outputs_for_proximity(poses_a, poses_b, query_mode, skips, average_distances, cutoff_distance);
+        let mut proxima_outputs = self.get_all_proxima_outputs_for_proximity(poses_a, poses_b, query_mode, skips, paddings, average_distances, cutoff_distance);
 
         if frozen { return ProximaOutput { result: proxima_outputs.to_proximity_value(loss_function, p_norm), ground_truth_checks: vec![] } }
 
@@ -186,6 +194,7 @@ pub trait ProximaTrait {
             let pb = &poses_b[j];
 
             let mut new_distance = self.get_cache_mut().update_element_with_ground_truth(i, j, sa, pa, sb, pb);
+            if let Some(paddings) = paddings { new_distance -= paddings[(i, j)]; }
 
             match average_distances {
                 None => { }
@@ -221,8 +230,9 @@ pub trait ProximaTrait {
                                 poses_b: &Vec<ISE3q>,
                                 query_mode: &DoubleGroupProximityQueryMode,
                                 skips: Option<&DMatrix<bool>>,
+                                paddings: Option<&DMatrix<f64>>,
                                 frozen: bool) -> ProximaOutput<bool> {
-        let proxima_outputs = self.get_all_proxima_outputs_for_intersection(poses_a, poses_b, query_mode, skips, 0.0);
+        let proxima_outputs = self.get_all_proxima_outputs_for_intersection(poses_a, poses_b, query_mode, skips, paddings, 0.0);
 
         return match proxima_outputs {
             None => {
@@ -279,7 +289,8 @@ pub trait ProximaTrait {
                     let pa = &poses_a[i];
                     let pb = &poses_b[j];
 
-                    let new_distance = self.get_cache_mut().update_element_with_ground_truth(i, j, sa, pa, sb, pb);
+                    let mut new_distance = self.get_cache_mut().update_element_with_ground_truth(i, j, sa, pa, sb, pb);
+                    if let Some(paddings) = paddings { new_distance -= paddings[(i, j)]; }
                     ground_truth_checks.push((i,j));
 
                     if new_distance <= 0.0 { return ProximaOutput {
@@ -0,0 +1,77 @@
+use parry3d_f64::bounding_volume::Aabb;
+use parry3d_f64::query::{Ray, RayCast};
+use rayon::prelude::*;
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V3;
+use crate::offset_shape::OffsetShape;
+
+/// The closest hit of a ray against a group of shapes.
+#[derive(Clone, Debug)]
+pub struct GroupRayCastHit {
+    /// Index of the hit shape in the group.
+    pub shape_idx: usize,
+    /// Distance from the ray origin to the hit point, in world units (independent of the
+    /// length of the ray direction).
+    pub distance: f64,
+    /// Hit point in the world frame.
+    pub point: V3,
+    /// Outward surface normal at the hit point in the world frame.  Zero if the ray starts
+    /// inside the hit shape.
+    pub normal: V3
+}
+
+/// Casts a single ray against every shape in the group and returns the closest hit, if any
+/// hit lies within `max_distance` of the ray origin.
+///
+/// Shapes are treated as solid, so a ray starting inside a shape hits it at distance zero.
+pub fn group_ray_cast(group: &Vec<OffsetShape>, poses: &Vec<ISE3q>, ray: &Ray, max_distance: f64) -> Option<GroupRayCastHit> {
+    assert_eq!(group.len(), poses.len());
+
+    let transforms: Vec<ISE3q> = group.iter().zip(poses.iter()).map(|(x, y)| x.get_transform(y).into_owned()).collect();
+    let aabbs = compute_aabbs(group, &transforms);
+
+    ray_cast_internal(group, &transforms, &aabbs, ray, max_distance)
+}
+
+/// Casts a batch of rays against the group in parallel.  Output `i` is the closest hit of ray `i`.
+///
+/// World-space AABBs of the shapes are computed once and shared by all rays, so this is the
+/// preferred entry point for dense sensors (e.g., depth cameras or LiDARs).
+pub fn group_ray_cast_batch(group: &Vec<OffsetShape>, poses: &Vec<ISE3q>, rays: &Vec<Ray>, max_distance: f64) -> Vec<Option<GroupRayCastHit>> {
+    assert_eq!(group.len(), poses.len());
+
+    let transforms: Vec<ISE3q> = group.iter().zip(poses.iter()).map(|(x, y)| x.get_transform(y).into_owned()).collect();
+    let aabbs = compute_aabbs(group, &transforms);
+
+    rays.par_iter().map(|ray| ray_cast_internal(group, &transforms, &aabbs, ray, max_distance)).collect()
+}
+
+fn ray_cast_internal(group: &Vec<OffsetShape>, transforms: &Vec<ISE3q>, aabbs: &Vec<Aabb>, ray: &Ray, max_distance: f64) -> Option<GroupRayCastHit> {
+    let dir_norm = ray.dir.norm();
+    if dir_norm == 0.0 { return None; }
+
+    // time of impact is measured in multiples of the ray direction.
+    let mut max_toi = max_distance / dir_norm;
+    let mut out: Option<GroupRayCastHit> = None;
+
+    for (shape_idx, shape) in group.iter().enumerate() {
+        if aabbs[shape_idx].cast_local_ray(ray, max_toi, true).is_none() { continue; }
+
+        let res = shape.shape().cast_ray_and_get_normal(&transforms[shape_idx].0, ray, max_toi, true);
+        if let Some(intersection) = res {
+            max_toi = intersection.time_of_impact;
+            out = Some(GroupRayCastHit {
+                shape_idx,
+                distance: intersection.time_of_impact * dir_norm,
+                point: ray.point_at(intersection.time_of_impact).coords,
+                normal: intersection.normal,
+            });
+        }
+    }
+
+    out
+}
+
+fn compute_aabbs(group: &Vec<OffsetShape>, transforms: &Vec<ISE3q>) -> Vec<Aabb> {
+    group.iter().zip(transforms.iter()).map(|(x, y)| x.shape().compute_aabb(&y.0)).collect()
+}
@@ -0,0 +1,226 @@
+use parry3d_f64::bounding_volume::{Aabb, BoundingVolume};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use crate::double_group_queries::DoubleGroupProximityQueryMode;
+use crate::offset_shape::OffsetShape;
+
+/// Persistent sweep-and-prune broadphase between two groups of shapes.
+///
+/// The AABB endpoints of both groups are kept sorted along a single sweep axis across calls to
+/// `update`.  Since objects move little between frames, re-sorting with insertion sort is close
+/// to linear, and groups that do not move at all (e.g., static environments) cost nothing beyond
+/// a pass over their endpoints.  The resulting candidate pairs are meant to be passed to the
+/// pairwise group queries through `DoubleGroupProximityQueryMode::SubsetOfPairs`.
+#[derive(Clone, Debug)]
+pub struct SweepAndPrune {
+    aabbs_a: Vec<Aabb>,
+    aabbs_b: Vec<Aabb>,
+    endpoints: Vec<SweepAndPruneEndpoint>,
+    sweep_axis: usize,
+    margin: f64
+}
+impl SweepAndPrune {
+    /// Creates a new broadphase for the given groups.
+    ///
+    /// `margin` loosens every AABB by the given amount, so pairs closer than `margin` are also
+    /// reported.  This should match the margin used in subsequent distance or contact queries.
+    pub fn new(group_a: &Vec<OffsetShape>, poses_a: &Vec<ISE3q>, group_b: &Vec<OffsetShape>, poses_b: &Vec<ISE3q>, margin: f64) -> Self {
+        let aabbs_a = compute_aabbs(group_a, poses_a, margin);
+        let aabbs_b = compute_aabbs(group_b, poses_b, margin);
+
+        let mut out = Self {
+            aabbs_a,
+            aabbs_b,
+            endpoints: vec![],
+            sweep_axis: 0,
+            margin,
+        };
+        out.rebuild_endpoints();
+
+        out
+    }
+
+    /// Updates the AABBs of both groups and re-sorts the endpoints.
+    ///
+    /// If the number of shapes in either group changed since the last call, the endpoints and
+    /// the sweep axis are rebuilt from scratch.
+    pub fn update(&mut self, group_a: &Vec<OffsetShape>, poses_a: &Vec<ISE3q>, group_b: &Vec<OffsetShape>, poses_b: &Vec<ISE3q>) {
+        let aabbs_a = compute_aabbs(group_a, poses_a, self.margin);
+        let aabbs_b = compute_aabbs(group_b, poses_b, self.margin);
+        self.set_aabbs(aabbs_a, aabbs_b);
+    }
+
+    /// Updates only group a, leaving group b (e.g., a static environment) untouched.
+    pub fn update_group_a(&mut self, group_a: &Vec<OffsetShape>, poses_a: &Vec<ISE3q>) {
+        let aabbs_a = compute_aabbs(group_a, poses_a, self.margin);
+        let aabbs_b = std::mem::take(&mut self.aabbs_b);
+        self.set_aabbs(aabbs_a, aabbs_b);
+    }
+
+    /// Updates only group b, leaving group a untouched.
+    pub fn update_group_b(&mut self, group_b: &Vec<OffsetShape>, poses_b: &Vec<ISE3q>) {
+        let aabbs_a = std::mem::take(&mut self.aabbs_a);
+        let aabbs_b = compute_aabbs(group_b, poses_b, self.margin);
+        self.set_aabbs(aabbs_a, aabbs_b);
+    }
+
+    /// Returns all pairs `(i, j)` such that AABB `i` of group a overlaps AABB `j` of group b,
+    /// sorted lexicographically.
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut out = vec![];
+        let mut active_a = ActiveSet::new(self.aabbs_a.len());
+        let mut active_b = ActiveSet::new(self.aabbs_b.len());
+
+        for endpoint in &self.endpoints {
+            let shape_idx = endpoint.shape_idx;
+            if endpoint.group_idx == 0 {
+                if endpoint.is_min {
+                    let aabb = &self.aabbs_a[shape_idx];
+                    active_b.members.iter().for_each(|j| { if aabb.intersects(&self.aabbs_b[*j]) { out.push((shape_idx, *j)); } });
+                    active_a.insert(shape_idx);
+                } else {
+                    active_a.remove(shape_idx);
+                }
+            } else {
+                if endpoint.is_min {
+                    let aabb = &self.aabbs_b[shape_idx];
+                    active_a.members.iter().for_each(|i| { if aabb.intersects(&self.aabbs_a[*i]) { out.push((*i, shape_idx)); } });
+                    active_b.insert(shape_idx);
+                } else {
+                    active_b.remove(shape_idx);
+                }
+            }
+        }
+
+        out.sort();
+        out
+    }
+
+    /// Returns the candidate pairs wrapped as a query mode for the pairwise group queries.
+    pub fn get_query_mode(&self) -> DoubleGroupProximityQueryMode {
+        DoubleGroupProximityQueryMode::SubsetOfPairs(self.candidate_pairs())
+    }
+
+    #[inline(always)]
+    pub fn sweep_axis(&self) -> usize {
+        self.sweep_axis
+    }
+
+    #[inline(always)]
+    pub fn margin(&self) -> f64 {
+        self.margin
+    }
+
+    fn set_aabbs(&mut self, aabbs_a: Vec<Aabb>, aabbs_b: Vec<Aabb>) {
+        let sizes_changed = aabbs_a.len() != self.aabbs_a.len() || aabbs_b.len() != self.aabbs_b.len();
+        self.aabbs_a = aabbs_a;
+        self.aabbs_b = aabbs_b;
+        if sizes_changed { self.rebuild_endpoints(); } else { self.refresh_endpoints(); }
+    }
+
+    fn rebuild_endpoints(&mut self) {
+        self.sweep_axis = get_sweep_axis(&self.aabbs_a, &self.aabbs_b);
+
+        self.endpoints.clear();
+        for (group_idx, l) in [self.aabbs_a.len(), self.aabbs_b.len()].iter().enumerate() {
+            for shape_idx in 0..*l {
+                self.endpoints.push(SweepAndPruneEndpoint { value: 0.0, is_min: true, group_idx, shape_idx });
+                self.endpoints.push(SweepAndPruneEndpoint { value: 0.0, is_min: false, group_idx, shape_idx });
+            }
+        }
+
+        self.refresh_endpoints();
+    }
+
+    fn refresh_endpoints(&mut self) {
+        let axis = self.sweep_axis;
+        for endpoint in self.endpoints.iter_mut() {
+            let aabb = if endpoint.group_idx == 0 { &self.aabbs_a[endpoint.shape_idx] } else { &self.aabbs_b[endpoint.shape_idx] };
+            endpoint.value = if endpoint.is_min { aabb.mins[axis] } else { aabb.maxs[axis] };
+        }
+
+        // insertion sort, near-linear when endpoints are already close to sorted from the last frame.
+        for i in 1..self.endpoints.len() {
+            let mut j = i;
+            while j > 0 && self.endpoints[j].goes_before(&self.endpoints[j - 1]) {
+                self.endpoints.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SweepAndPruneEndpoint {
+    value: f64,
+    is_min: bool,
+    group_idx: usize,
+    shape_idx: usize
+}
+impl SweepAndPruneEndpoint {
+    /// Min endpoints go before max endpoints at equal values so that touching boxes count as overlapping.
+    #[inline(always)]
+    fn goes_before(&self, other: &SweepAndPruneEndpoint) -> bool {
+        self.value < other.value || (self.value == other.value && self.is_min && !other.is_min)
+    }
+}
+
+/// Shapes whose interval along the sweep axis is currently open, with O(1) insertion and removal.
+struct ActiveSet {
+    members: Vec<usize>,
+    /// position of each shape in `members`, or `usize::MAX` if it is not active.
+    positions: Vec<usize>
+}
+impl ActiveSet {
+    fn new(num_shapes: usize) -> Self {
+        Self { members: vec![], positions: vec![usize::MAX; num_shapes] }
+    }
+
+    #[inline(always)]
+    fn insert(&mut self, shape_idx: usize) {
+        self.positions[shape_idx] = self.members.len();
+        self.members.push(shape_idx);
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, shape_idx: usize) {
+        let position = self.positions[shape_idx];
+        if position == usize::MAX { return; }
+        self.members.swap_remove(position);
+        if position < self.members.len() { self.positions[self.members[position]] = position; }
+        self.positions[shape_idx] = usize::MAX;
+    }
+}
+
+fn compute_aabbs(group: &Vec<OffsetShape>, poses: &Vec<ISE3q>, margin: f64) -> Vec<Aabb> {
+    assert_eq!(group.len(), poses.len());
+
+    group.iter().zip(poses.iter()).map(|(x, y)| {
+        let pose = x.get_transform(y);
+        x.shape().compute_aabb(&pose.as_ref().0).loosened(margin)
+    }).collect()
+}
+
+/// Chooses the axis along which AABB centers are most spread out.
+fn get_sweep_axis(aabbs_a: &Vec<Aabb>, aabbs_b: &Vec<Aabb>) -> usize {
+    let n = (aabbs_a.len() + aabbs_b.len()) as f64;
+    if n == 0.0 { return 0; }
+
+    let mut out = 0;
+    let mut max_variance = f64::NEG_INFINITY;
+    for axis in 0..3 {
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        aabbs_a.iter().chain(aabbs_b.iter()).for_each(|x| {
+            let c = x.center()[axis];
+            sum += c;
+            sum_sq += c * c;
+        });
+        let variance = sum_sq / n - (sum / n).powi(2);
+        if variance > max_variance {
+            max_variance = variance;
+            out = axis;
+        }
+    }
+
+    out
+}
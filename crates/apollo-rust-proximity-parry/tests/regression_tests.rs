@@ -116,3 +116,262 @@ fn test_bvh_no_collision() {
     // TODO: Investigate why leaf_pairs returns intersections for distant objects (phantom collision).
     // Debugging showed internal AABBs in closure were close/identical despite input being distinct.
 }
+
+#[test]
+fn test_sah_bvh_self_intersection_filter() {
+    use apollo_rust_proximity_parry::bvh::{BvhShapeAABB, BvhShapeOBB};
+
+    let shapes = vec![
+        OffsetShape::new(Ball::new(0.2), None),
+        OffsetShape::new(Ball::new(0.2), None),
+        OffsetShape::new(Ball::new(0.2), None),
+    ];
+    // Shapes 0 and 1 overlap, shape 2 is far away from both.
+    let poses = vec![
+        ISE3q::identity(),
+        ISE3q::from_exponential_coordinates(&apollo_rust_spatial::vectors::V6::new(
+            0.0, 0.0, 0.0, 0.1, 0.0, 0.0,
+        )),
+        ISE3q::from_exponential_coordinates(&apollo_rust_spatial::vectors::V6::new(
+            0.0, 0.0, 0.0, 10.0, 0.0, 0.0,
+        )),
+    ];
+
+    let bvh = apollo_rust_proximity_parry::bvh::Bvh::<BvhShapeAABB>::build_sah(&shapes, &poses);
+    assert_eq!(bvh.layers[0].bvh_shapes.len(), 1);
+    assert_eq!(bvh.layers.last().unwrap().bvh_shapes.len(), 3);
+    assert_eq!(bvh.self_intersection_filter(), vec![(0, 1)]);
+
+    let bvh = apollo_rust_proximity_parry::bvh::Bvh::<BvhShapeOBB>::build_sah(&shapes, &poses);
+    assert_eq!(bvh.self_intersection_filter(), vec![(0, 1)]);
+}
+
+#[test]
+fn test_sah_bvh_refit_keeps_topology() {
+    use apollo_rust_proximity_parry::bvh::BvhShapeAABB;
+
+    let shapes = vec![
+        OffsetShape::new(Ball::new(0.2), None),
+        OffsetShape::new(Ball::new(0.2), None),
+    ];
+    let far_poses = vec![
+        ISE3q::identity(),
+        ISE3q::from_exponential_coordinates(&apollo_rust_spatial::vectors::V6::new(
+            0.0, 0.0, 0.0, 10.0, 0.0, 0.0,
+        )),
+    ];
+    let close_poses = vec![ISE3q::identity(), ISE3q::identity()];
+
+    let mut bvh = apollo_rust_proximity_parry::bvh::Bvh::<BvhShapeAABB>::build_sah(&shapes, &far_poses);
+    assert!(bvh.self_intersection_filter().is_empty());
+
+    let parent_indices: Vec<Vec<Vec<usize>>> = bvh.layers.iter().map(|x| x.parent_indices.clone()).collect();
+    bvh.refit(&shapes, &close_poses);
+    let refit_parent_indices: Vec<Vec<Vec<usize>>> = bvh.layers.iter().map(|x| x.parent_indices.clone()).collect();
+
+    assert_eq!(parent_indices, refit_parent_indices);
+    assert_eq!(bvh.self_intersection_filter(), vec![(0, 1)]);
+}
+
+#[test]
+fn test_sweep_and_prune_candidate_pairs() {
+    use apollo_rust_proximity_parry::sweep_and_prune::SweepAndPrune;
+    use apollo_rust_spatial::vectors::V6;
+
+    let group_a = vec![OffsetShape::new(Ball::new(0.2), None)];
+    let group_b = vec![
+        OffsetShape::new(Ball::new(0.2), None),
+        OffsetShape::new(Ball::new(0.2), None),
+    ];
+    let poses_a = vec![ISE3q::identity()];
+    let poses_b = vec![
+        ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 0.3, 0.0, 0.0)),
+        ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 5.0, 0.0, 0.0)),
+    ];
+
+    let mut sap = SweepAndPrune::new(&group_a, &poses_a, &group_b, &poses_b, 0.0);
+    assert_eq!(sap.candidate_pairs(), vec![(0, 0)]);
+
+    // Move the robot shape next to the second object; the first object is now out of reach.
+    let poses_a = vec![ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 4.8, 0.0, 0.0))];
+    sap.update_group_a(&group_a, &poses_a);
+    assert_eq!(sap.candidate_pairs(), vec![(0, 1)]);
+
+    // A margin widens the set of candidates.
+    let sap = SweepAndPrune::new(&group_a, &poses_a, &group_b, &poses_b, 10.0);
+    assert_eq!(sap.candidate_pairs(), vec![(0, 0), (0, 1)]);
+}
+
+#[test]
+fn test_proximity_loss_function_derivatives() {
+    use apollo_rust_proximity_parry::ProximityLossFunction;
+
+    let loss_functions = vec![
+        ProximityLossFunction::Hinge { threshold: 0.5 },
+        ProximityLossFunction::SmoothHinge { threshold: 0.5, sharpness: 20.0 },
+        ProximityLossFunction::LogBarrier { threshold: 0.5 },
+        ProximityLossFunction::InverseBarrier { threshold: 0.5 },
+        ProximityLossFunction::Exponential { threshold: 0.5, scale: 0.1 },
+        ProximityLossFunction::Gaussian { threshold: 0.5, sigma: 0.2 },
+    ];
+
+    let h = 1e-6;
+    for loss in &loss_functions {
+        for value in [0.05, 0.2, 0.45, 0.7, 1.3] {
+            let fd = (loss.loss(value + h) - loss.loss(value - h)) / (2.0 * h);
+            let analytical = loss.derivative(value);
+            assert!((fd - analytical).abs() < 1e-4 * (1.0 + fd.abs()), "{:?} at {}: {} vs {}", loss, value, fd, analytical);
+            assert!(loss.loss(value) >= 0.0);
+        }
+    }
+
+    // Barrier losses vanish smoothly at the threshold.
+    for loss in &loss_functions[2..4] {
+        assert!(loss.loss(0.5).abs() < 1e-12);
+        assert!(loss.derivative(0.5 - 1e-9).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_parallel_double_group_queries_match_sequential() {
+    use apollo_rust_proximity_parry::double_group_queries::{
+        pairwise_group_query_distance, pairwise_group_query_distance_parallel,
+        pairwise_group_query_intersection, pairwise_group_query_intersection_parallel,
+        DoubleGroupProximityQueryMode,
+    };
+    use apollo_rust_spatial::vectors::V6;
+
+    let n = 20;
+    let shapes: Vec<OffsetShape> = (0..n).map(|_| OffsetShape::new(Ball::new(0.2), None)).collect();
+    let poses: Vec<ISE3q> = (0..n)
+        .map(|i| ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 0.3 * i as f64, 0.0, 0.0)))
+        .collect();
+
+    for early_stop in [false, true] {
+        let mode = DoubleGroupProximityQueryMode::SkipSymmetricalPairs;
+
+        let seq = pairwise_group_query_intersection(&shapes, &poses, &shapes, &poses, &mode, None, early_stop, ());
+        let par = pairwise_group_query_intersection_parallel(&shapes, &poses, &shapes, &poses, &mode, None, early_stop, ());
+        assert_eq!(seq.outputs, par.outputs);
+        assert_eq!(seq.shape_idxs, par.shape_idxs);
+        assert_eq!(seq.num_ground_truth_checks, par.num_ground_truth_checks);
+
+        let seq = pairwise_group_query_distance(&shapes, &poses, &shapes, &poses, &mode, None, early_stop, ());
+        let par = pairwise_group_query_distance_parallel(&shapes, &poses, &shapes, &poses, &mode, None, early_stop, ());
+        assert_eq!(seq.outputs, par.outputs);
+        assert_eq!(seq.shape_idxs, par.shape_idxs);
+        assert_eq!(seq.num_ground_truth_checks, par.num_ground_truth_checks);
+    }
+}
+
+#[test]
+fn test_group_ray_cast_returns_closest_hit() {
+    use apollo_rust_proximity_parry::ray_casting::{group_ray_cast, group_ray_cast_batch};
+    use apollo_rust_spatial::vectors::{V3, V6};
+    use parry3d_f64::math::Point;
+    use parry3d_f64::query::Ray;
+
+    let shapes = vec![
+        OffsetShape::new(Ball::new(0.5), None),
+        OffsetShape::new(Ball::new(0.5), None),
+    ];
+    let poses = vec![
+        ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 4.0, 0.0, 0.0)),
+        ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 2.0, 0.0, 0.0)),
+    ];
+
+    // direction length should not affect the reported distance.
+    let ray = Ray::new(Point::origin(), V3::new(2.0, 0.0, 0.0));
+    let hit = group_ray_cast(&shapes, &poses, &ray, 10.0).expect("error");
+    assert_eq!(hit.shape_idx, 1);
+    assert!((hit.distance - 1.5).abs() < 1e-9);
+    assert!((hit.point - V3::new(1.5, 0.0, 0.0)).norm() < 1e-9);
+    assert!((hit.normal - V3::new(-1.0, 0.0, 0.0)).norm() < 1e-9);
+
+    assert!(group_ray_cast(&shapes, &poses, &ray, 1.0).is_none());
+
+    let rays = vec![ray, Ray::new(Point::origin(), V3::new(0.0, 1.0, 0.0))];
+    let hits = group_ray_cast_batch(&shapes, &poses, &rays, 10.0);
+    assert_eq!(hits[0].as_ref().expect("error").shape_idx, 1);
+    assert!(hits[1].is_none());
+}
+
+#[test]
+fn test_multi_sphere_fit_covers_shape() {
+    use apollo_rust_proximity_parry::offset_shape::{fit_multi_spheres, to_offset_shape_multi_sphere};
+    use apollo_rust_spatial::vectors::V6;
+    use parry3d_f64::math::Point;
+    use parry3d_f64::shape::ConvexPolyhedron;
+
+    // elongated box, like an arm link
+    let mut points = vec![];
+    for x in [-0.5, 0.5] {
+        for y in [-0.05, 0.05] {
+            for z in [-0.05, 0.05] {
+                points.push(Point::new(x, y, z));
+            }
+        }
+    }
+    let cp = ConvexPolyhedron::from_convex_hull(&points).expect("error");
+
+    let spheres = fit_multi_spheres(&cp, 6, 0.0);
+    assert!(spheres.len() <= 6 && spheres.len() > 0);
+    for p in &points {
+        assert!(spheres.iter().any(|(c, r)| (p.coords - c).norm() <= r + 1e-9));
+    }
+    // much tighter than the single bounding sphere of radius ~0.51
+    assert!(spheres.iter().all(|(_, r)| *r < 0.3));
+
+    // every vertex is enclosed for irregular shapes and any number of spheres as well.
+    let irregular: Vec<Point<f64>> = (0..40).map(|i| {
+        let t = i as f64 * 0.7;
+        Point::new(0.3 * t.sin() + 0.05 * (3.1 * t).cos(), 0.2 * (1.3 * t).cos(), 0.1 * t.sin() * (0.5 * t).cos() + 0.02 * i as f64)
+    }).collect();
+    let irregular_cp = ConvexPolyhedron::from_convex_hull(&irregular).expect("error");
+    for num_spheres in 1..8 {
+        let irregular_spheres = fit_multi_spheres(&irregular_cp, num_spheres, 0.0);
+        for p in irregular_cp.points() {
+            assert!(irregular_spheres.iter().any(|(c, r)| (p.coords - c).norm() <= r + 1e-9));
+        }
+    }
+
+    let tight = fit_multi_spheres(&cp, 6, 1.0);
+    assert!(tight.iter().zip(spheres.iter()).all(|(a, b)| a.1 <= b.1 + 1e-12));
+
+    let a = to_offset_shape_multi_sphere(&cp, 6, 0.0);
+    let b = OffsetShape::new(Ball::new(0.1), None);
+    let pose_b = ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 0.0, 1.0, 0.0));
+    let d = a.distance(&ISE3q::identity(), &b, &pose_b);
+    assert!(d > 0.6 && d < 0.9);
+    assert!(a.calculate_max_dis_from_origin_to_point_on_shape() >= 0.5);
+}
+
+#[test]
+fn test_capsule_fit_encloses_shape() {
+    use apollo_rust_proximity_parry::offset_shape::{fit_capsule, to_offset_shape_capsule};
+    use apollo_rust_spatial::vectors::{V3, V6};
+    use parry3d_f64::math::Point;
+
+    let mut points = vec![];
+    for x in [-0.5, 0.5] {
+        for y in [-0.05, 0.05] {
+            for z in [-0.05, 0.05] {
+                points.push(Point::new(x, y, z));
+            }
+        }
+    }
+    let vs: Vec<V3> = points.iter().map(|x| x.coords).collect();
+    let (center, axis, half_length, radius) = fit_capsule(&vs);
+    assert!(axis.x.abs() > 0.99);
+    assert!(radius < 0.1);
+    for p in &vs {
+        let t = (p - center).dot(&axis).clamp(-half_length, half_length);
+        assert!((p - (center + axis * t)).norm() <= radius + 1e-9);
+    }
+
+    let a = to_offset_shape_capsule(&vs);
+    let b = OffsetShape::new(Ball::new(0.1), None);
+    let pose_b = ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 0.0, 1.0, 0.0));
+    let d = a.distance(&ISE3q::identity(), &b, &pose_b);
+    assert!(d > 0.8 && d < 0.85);
+}
@@ -1,10 +1,12 @@
+use std::fmt::Debug;
 use std::sync::Arc;
-use parry3d_f64::query::Contact;
+use nalgebra::DMatrix;
+use parry3d_f64::query::{Contact, Ray};
 use apollo_rust_linalg::V;
-use apollo_rust_proximity_parry::double_group_queries::{ConvertToAverageDistancesTrait, DoubleGroupProximityQueryMode, DoubleGroupProximityQueryOutput};
+use apollo_rust_proximity_parry::double_group_queries::{ConvertToAverageDistancesTrait, DoubleGroupProximityQueryMode, DoubleGroupProximityQueryOutput, pairwise_group_query_contact, pairwise_group_query_contact_padded, pairwise_group_query_distance, pairwise_group_query_distance_padded, pairwise_group_query_intersection, pairwise_group_query_intersection_padded};
 use apollo_rust_proximity_parry::proxima::proxima1::{Proxima1, Proxima1Cache};
 use apollo_rust_proximity_parry::proxima::proxima_core::{ProximaBudget, ProximaOutput, ProximaTrait};
-use apollo_rust_proximity_parry::ProximityLossFunction;
+use apollo_rust_proximity_parry::{ProximityLossFunction, ToIntersectionResult};
 use apollo_rust_modules::{ResourcesSubDirectory};
 use apollo_rust_modules::robot_modules::bounds_module::ApolloBoundsModule;
 use apollo_rust_modules::robot_modules::chain_module::{ApolloChainModule};
@@ -17,15 +19,21 @@ use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module:
 use apollo_rust_modules::robot_modules::mesh_modules::original_meshes_module::ApolloOriginalMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::plain_meshes_module::ApolloPlainMeshesModule;
 use apollo_rust_proximity_parry::bvh::{Bvh, BvhShape};
+use apollo_rust_proximity_parry::sweep_and_prune::SweepAndPrune;
+use apollo_rust_proximity_parry::offset_shape::OffsetShape;
 use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
 use crate::modules::link_shapes_modules::link_shapes_max_distance_from_origin_module::LinkShapesMaxDistanceFromOriginTrait;
 use crate::modules_runtime::link_shapes_distance_statistics_nalgebra_module::ApolloLinkShapesDistanceStatisticsNalgebraModule;
 use crate::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+use crate::modules_runtime::link_shapes_padding_nalgebra_module::ApolloLinkShapesPaddingNalgebraModule;
+use crate::modules_runtime::allowed_collision_matrix::AllowedCollisionMatrix;
+use crate::modules_runtime::attached_objects_module::ApolloAttachedObjectsModule;
 use crate::modules_runtime::link_shapes_simple_skips_nalgebra_module::ApolloLinkShapesSimpleSkipsNalgebraModule;
 use crate::modules_runtime::link_shapes_skips_nalgebra_module::ApolloLinkShapesSkipsNalgebraModule;
 use crate::modules_runtime::urdf_nalgebra_module::ApolloURDFNalgebraModule;
 use crate::robot_functions::robot_kinematics_functions::RobotKinematicsFunctions;
-use crate::robot_functions::robot_proximity_functions::RobotProximityFunctions;
+use crate::robot_functions::robot_proximity_functions::{max_padding, RobotProximityFunctions};
+use crate::robot_functions::robot_ray_casting_functions::{RobotRayCastHit, RobotRayCastingFunctions};
 
 /// The apollo-rust-robotics-core module contains robotics functions and structs that depend
 /// only on robot modules, but without initializing them.  Structs in this crate are initialized
@@ -156,7 +164,9 @@ pub struct ChainNalgebra {
     pub link_shapes_max_distance_from_origin_module: ApolloLinkShapesMaxDistanceFromOriginModule,
     pub link_shapes_distance_statistics_module: ApolloLinkShapesDistanceStatisticsNalgebraModule,
     pub link_shapes_simple_skips_nalgebra_module: ApolloLinkShapesSimpleSkipsNalgebraModule,
-    pub link_shapes_skips_nalgebra_module: ApolloLinkShapesSkipsNalgebraModule,
+    pub link_shapes_padding_nalgebra_module: ApolloLinkShapesPaddingNalgebraModule,
+    pub allowed_collision_matrix: AllowedCollisionMatrix,
+    pub attached_objects_module: ApolloAttachedObjectsModule,
     // pub link_shapes_lie_alg_error_models_nalgebra_module: ApolloLinkShapesLieAlgErrorModelsNalgebraModule,
     pub bounds_module: ApolloBoundsModule
 }
@@ -231,9 +241,32 @@ impl ChainNalgebra {
         &self.link_shapes_simple_skips_nalgebra_module
     }
 
+    /// Returns the preprocessed skips, which are owned by the allowed collision matrix.
     #[inline(always)]
     pub fn link_shapes_skips_nalgebra_module(&self) -> &ApolloLinkShapesSkipsNalgebraModule {
-        &self.link_shapes_skips_nalgebra_module
+        self.allowed_collision_matrix.base_skips()
+    }
+
+    #[inline(always)]
+    pub fn link_shapes_padding_nalgebra_module(&self) -> &ApolloLinkShapesPaddingNalgebraModule {
+        &self.link_shapes_padding_nalgebra_module
+    }
+
+    #[inline(always)]
+    pub fn allowed_collision_matrix(&self) -> &AllowedCollisionMatrix {
+        &self.allowed_collision_matrix
+    }
+
+    /// Returns the allowed collision matrix for editing.  Its rules apply to all self and
+    /// double-chain proximity queries of this chain.
+    #[inline(always)]
+    pub fn allowed_collision_matrix_mut(&mut self) -> &mut AllowedCollisionMatrix {
+        &mut self.allowed_collision_matrix
+    }
+
+    #[inline(always)]
+    pub fn attached_objects_module(&self) -> &ApolloAttachedObjectsModule {
+        &self.attached_objects_module
     }
 
     #[inline(always)]
@@ -267,9 +300,162 @@ impl ChainNalgebra {
         RobotKinematicsFunctions::reverse_of_fk(link_frame, &self.urdf_module, &self.chain_module, &self.dof_module)
     }
 
+    /// Sets the padding (safety margin) of a link.  All proximity queries treat the link, and
+    /// any object attached to it, as if its shapes were inflated by this amount.  Sweep-and-prune
+    /// structures from `get_double_chain_sweep_and_prune` have to be rebuilt after paddings change.
+    pub fn set_link_padding(&mut self, link_idx: usize, padding: f64) {
+        self.link_shapes_padding_nalgebra_module.set_link_padding(link_idx, padding);
+    }
+
+    /// Overrides the padding between a pair of links of this chain, replacing the sum of their
+    /// link paddings.  Passing `None` removes the override.
+    pub fn set_link_pair_padding(&mut self, link_idx_a: usize, link_idx_b: usize, padding: Option<f64>) {
+        self.link_shapes_padding_nalgebra_module.set_link_pair_padding(link_idx_a, link_idx_b, padding);
+    }
+
+    /// Returns the padding matrix for self queries, or `None` if the chain has no padding.
+    pub fn get_self_paddings(&self, link_shape_mode: LinkShapeMode) -> Option<&DMatrix<f64>> {
+        if !self.link_shapes_padding_nalgebra_module.has_padding() { return None; }
+        Some(self.link_shapes_padding_nalgebra_module.get_paddings(link_shape_mode))
+    }
+
+    /// Returns the padding matrix for queries against another chain, or `None` if neither chain has padding.
+    pub fn get_double_chain_paddings(&self, other_chain: &ChainNalgebra, self_link_shape_mode: LinkShapeMode, other_link_shape_mode: LinkShapeMode) -> Option<DMatrix<f64>> {
+        if !self.link_shapes_padding_nalgebra_module.has_padding() && !other_chain.link_shapes_padding_nalgebra_module.has_padding() { return None; }
+        Some(self.link_shapes_padding_nalgebra_module.get_double_chain_paddings(self_link_shape_mode, &other_chain.link_shapes_padding_nalgebra_module, other_link_shape_mode))
+    }
+
+    /// Returns the skips matrix for queries against another chain from the allowed collision
+    /// matrices of both chains, or `None` if neither has rules.
+    pub fn get_double_chain_skips(&self, other_chain: &ChainNalgebra, self_link_shape_mode: LinkShapeMode, other_link_shape_mode: LinkShapeMode) -> Option<DMatrix<bool>> {
+        self.allowed_collision_matrix.get_double_chain_skips(self_link_shape_mode, &other_chain.allowed_collision_matrix, other_link_shape_mode)
+    }
+
+    /// Returns the padding matrix for queries against `num_obstacles` obstacles, or `None` if the chain has no padding.
+    pub fn get_obstacle_paddings(&self, link_shape_mode: LinkShapeMode, num_obstacles: usize) -> Option<DMatrix<f64>> {
+        if !self.link_shapes_padding_nalgebra_module.has_padding() { return None; }
+        Some(self.link_shapes_padding_nalgebra_module.get_obstacle_paddings(link_shape_mode, num_obstacles))
+    }
+
+    /// Rigidly attaches an object to a link, replacing any attached object with the same name.
+    /// Attached objects take part in all self, obstacle, and double-chain queries, where attached
+    /// object `k` has shape index `num_link_shapes + k`.  The BVH, sweep and prune, cascade, and
+    /// Proxima variants only accelerate the link shapes, so attached objects are checked exactly
+    /// against every shape in those variants.  Attached objects are also checked
+    /// against each other (unless attached to the same link) and against objects attached to the
+    /// other chain, following the allowed collision matrix rules that name both objects.  An
+    /// attached object takes the padding of the link it is attached to.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the object, which can also be used in allowed collision matrix rules.
+    /// - `link_idx`: The index of the link the object is attached to.
+    /// - `shape`: The shape of the object.
+    /// - `relative_pose`: The pose of the object relative to the link frame.
+    /// - `touch_links`: Names of links (wildcards allowed) that may touch the object, in addition to its parent link.
+    pub fn attach_object(&mut self, name: &str, link_idx: usize, shape: OffsetShape, relative_pose: ISE3q, touch_links: Vec<String>) {
+        assert!(link_idx < self.urdf_module.links.len(), "link index {} is out of range", link_idx);
+        self.attached_objects_module.attach(name, link_idx, shape, relative_pose, touch_links);
+    }
+
+    /// Detaches the object with the given name, returning `true` if it was attached.
+    pub fn detach_object(&mut self, name: &str) -> bool {
+        self.attached_objects_module.detach(name).is_some()
+    }
+
+    // The attached objects helpers run `query` with `extra_args` when no padding applies, and
+    // `padded_query` with the paddings of the attached objects otherwise.
+
+    fn append_attached_objects_self_query<T: Clone + Debug, E: Clone, Q, QP>(&self, out: &mut DoubleGroupProximityQueryOutput<T>, query: Q, padded_query: QP, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool, extra_args: E)
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T>,
+              QP: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, &DMatrix<f64>) -> DoubleGroupProximityQueryOutput<T>,
+              DoubleGroupProximityQueryOutput<T>: ToIntersectionResult {
+        if self.attached_objects_module.is_empty() || (early_stop && out.to_intersection_result()) { return; }
+        let padded = self.link_shapes_padding_nalgebra_module.has_padding();
+        let skips = self.attached_objects_module.get_self_skips(link_shape_mode, &self.allowed_collision_matrix);
+        let res = if padded {
+            let paddings = self.attached_objects_module.get_self_paddings(link_shape_mode, &self.link_shapes_padding_nalgebra_module);
+            RobotProximityFunctions::attached_objects_self_query(&padded_query, &self.link_shapes_module, link_poses, link_shape_mode, link_shape_rep, &self.attached_objects_module, &skips, early_stop, &paddings)
+        } else {
+            RobotProximityFunctions::attached_objects_self_query(&query, &self.link_shapes_module, link_poses, link_shape_mode, link_shape_rep, &self.attached_objects_module, &skips, early_stop, extra_args.clone())
+        };
+        out.extend(res);
+
+        if self.attached_objects_module.num_attached_objects() < 2 || (early_stop && out.to_intersection_result()) { return; }
+        let num_link_shapes = self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep).len();
+        let skips = self.attached_objects_module.get_objects_skips(&self.allowed_collision_matrix);
+        let res = if padded {
+            let paddings = self.attached_objects_module.get_objects_paddings(&self.link_shapes_padding_nalgebra_module);
+            RobotProximityFunctions::attached_objects_pairs_query(&padded_query, num_link_shapes, link_poses, &self.attached_objects_module, &skips, early_stop, &paddings)
+        } else {
+            RobotProximityFunctions::attached_objects_pairs_query(&query, num_link_shapes, link_poses, &self.attached_objects_module, &skips, early_stop, extra_args)
+        };
+        out.extend(res);
+    }
+
+    fn append_attached_objects_obstacles_query<T: Clone + Debug, E, Q, QP>(&self, out: &mut DoubleGroupProximityQueryOutput<T>, query: Q, padded_query: QP, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool, extra_args: E)
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T>,
+              QP: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, &DMatrix<f64>) -> DoubleGroupProximityQueryOutput<T>,
+              DoubleGroupProximityQueryOutput<T>: ToIntersectionResult {
+        if self.attached_objects_module.is_empty() || (early_stop && out.to_intersection_result()) { return; }
+        let num_link_shapes = self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep).len();
+        let res = if self.link_shapes_padding_nalgebra_module.has_padding() {
+            let paddings = self.attached_objects_module.get_obstacle_paddings(&self.link_shapes_padding_nalgebra_module, obstacles.len());
+            RobotProximityFunctions::attached_objects_obstacles_query(padded_query, num_link_shapes, link_poses, &self.attached_objects_module, obstacles, obstacle_poses, early_stop, &paddings)
+        } else {
+            RobotProximityFunctions::attached_objects_obstacles_query(query, num_link_shapes, link_poses, &self.attached_objects_module, obstacles, obstacle_poses, early_stop, extra_args)
+        };
+        out.extend(res);
+    }
+
+    fn append_attached_objects_double_chain_query<T: Clone + Debug, E: Clone, Q, QP>(&self, out: &mut DoubleGroupProximityQueryOutput<T>, query: Q, padded_query: QP, other_chain: &ChainNalgebra, self_link_poses: &Vec<ISE3q>, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep, other_link_poses: &Vec<ISE3q>, other_link_shape_mode: LinkShapeMode, other_link_shape_rep: LinkShapeRep, early_stop: bool, extra_args: E)
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T>,
+              QP: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, &DMatrix<f64>) -> DoubleGroupProximityQueryOutput<T>,
+              DoubleGroupProximityQueryOutput<T>: ToIntersectionResult {
+        let padded = self.link_shapes_padding_nalgebra_module.has_padding() || other_chain.link_shapes_padding_nalgebra_module.has_padding();
+
+        if !self.attached_objects_module.is_empty() && !(early_stop && out.to_intersection_result()) {
+            let num_self_link_shapes = self.link_shapes_module.get_shapes(self_link_shape_mode, self_link_shape_rep).len();
+            let skips = self.attached_objects_module.get_other_chain_skips(&self.allowed_collision_matrix, other_link_shape_mode, &other_chain.allowed_collision_matrix);
+            let res = if padded {
+                let paddings = self.attached_objects_module.get_other_chain_paddings(&self.link_shapes_padding_nalgebra_module, other_link_shape_mode, &other_chain.link_shapes_padding_nalgebra_module);
+                RobotProximityFunctions::attached_objects_other_chain_query(&padded_query, num_self_link_shapes, self_link_poses, &self.attached_objects_module, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, &skips, early_stop, &paddings)
+            } else {
+                RobotProximityFunctions::attached_objects_other_chain_query(&query, num_self_link_shapes, self_link_poses, &self.attached_objects_module, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, &skips, early_stop, extra_args.clone())
+            };
+            out.extend(res);
+        }
+
+        if !other_chain.attached_objects_module.is_empty() && !(early_stop && out.to_intersection_result()) {
+            let num_other_link_shapes = other_chain.link_shapes_module.get_shapes(other_link_shape_mode, other_link_shape_rep).len();
+            let skips = other_chain.attached_objects_module.get_other_chain_skips(&other_chain.allowed_collision_matrix, self_link_shape_mode, &self.allowed_collision_matrix);
+            let res = if padded {
+                let paddings = other_chain.attached_objects_module.get_other_chain_paddings(&other_chain.link_shapes_padding_nalgebra_module, self_link_shape_mode, &self.link_shapes_padding_nalgebra_module);
+                RobotProximityFunctions::attached_objects_other_chain_query(&padded_query, num_other_link_shapes, other_link_poses, &other_chain.attached_objects_module, &self.link_shapes_module, self_link_poses, self_link_shape_mode, self_link_shape_rep, &skips, early_stop, &paddings)
+            } else {
+                RobotProximityFunctions::attached_objects_other_chain_query(&query, num_other_link_shapes, other_link_poses, &other_chain.attached_objects_module, &self.link_shapes_module, self_link_poses, self_link_shape_mode, self_link_shape_rep, &skips, early_stop, extra_args.clone())
+            };
+            out.extend(res.map_shape_idxs(|(i, j)| (j, i)));
+        }
+
+        if !self.attached_objects_module.is_empty() && !other_chain.attached_objects_module.is_empty() && !(early_stop && out.to_intersection_result()) {
+            let num_self_link_shapes = self.link_shapes_module.get_shapes(self_link_shape_mode, self_link_shape_rep).len();
+            let num_other_link_shapes = other_chain.link_shapes_module.get_shapes(other_link_shape_mode, other_link_shape_rep).len();
+            let skips = self.attached_objects_module.get_other_chain_objects_skips(&self.allowed_collision_matrix, &other_chain.attached_objects_module, &other_chain.allowed_collision_matrix);
+            let res = if padded {
+                let paddings = self.attached_objects_module.get_other_chain_objects_paddings(&self.link_shapes_padding_nalgebra_module, &other_chain.attached_objects_module, &other_chain.link_shapes_padding_nalgebra_module);
+                RobotProximityFunctions::attached_objects_other_chain_objects_query(&padded_query, num_self_link_shapes, self_link_poses, &self.attached_objects_module, num_other_link_shapes, other_link_poses, &other_chain.attached_objects_module, &skips, early_stop, &paddings)
+            } else {
+                RobotProximityFunctions::attached_objects_other_chain_objects_query(&query, num_self_link_shapes, self_link_poses, &self.attached_objects_module, num_other_link_shapes, other_link_poses, &other_chain.attached_objects_module, &skips, early_stop, extra_args)
+            };
+            out.extend(res);
+        }
+    }
+
     pub fn self_intersect(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        RobotProximityFunctions::self_intersect(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), early_stop)
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
+        let mut out = RobotProximityFunctions::self_intersect(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), early_stop);
+        self.append_attached_objects_self_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, link_poses, link_shape_mode, link_shape_rep, early_stop, ());
+        out
     }
 
     pub fn self_intersect_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
@@ -278,13 +464,17 @@ impl ChainNalgebra {
     }
 
     pub fn self_intersect_bvh<B: BvhShape>(&self, bvh: &mut Bvh<B>, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        RobotProximityFunctions::self_intersect_bvh(bvh, self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), early_stop)
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
+        let mut out = RobotProximityFunctions::self_intersect_bvh(bvh, self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), early_stop);
+        self.append_attached_objects_self_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, link_poses, link_shape_mode, link_shape_rep, early_stop, ());
+        out
     }
 
     pub fn self_distance(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        RobotProximityFunctions::self_distance(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), early_stop)
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
+        let mut out = RobotProximityFunctions::self_distance(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), early_stop);
+        self.append_attached_objects_self_query(&mut out, pairwise_group_query_distance, pairwise_group_query_distance_padded, link_poses, link_shape_mode, link_shape_rep, early_stop, ());
+        out
     }
 
     pub fn self_distance_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
@@ -293,13 +483,16 @@ impl ChainNalgebra {
     }
 
     pub fn self_contact(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool, margin: f64, to_wrt_average: bool) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        let res = RobotProximityFunctions::self_contact(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), early_stop, margin);
-        return if to_wrt_average {
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
+        let res = RobotProximityFunctions::self_contact(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), early_stop, margin);
+        // distance statistics only cover link shapes, so attached objects are appended afterwards.
+        let mut out = if to_wrt_average {
             res.to_average_distances(&self.link_shapes_distance_statistics_module.get_stats(&link_shape_rep, &link_shape_mode).averages)
         } else {
             res
-        }
+        };
+        self.append_attached_objects_self_query(&mut out, pairwise_group_query_contact, |a, pa, b, pb, m, s, e, p| pairwise_group_query_contact_padded(a, pa, b, pb, m, s, e, (p, margin)), link_poses, link_shape_mode, link_shape_rep, early_stop, margin);
+        out
     }
 
     pub fn self_contact_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool, margin: f64, to_wrt_average: bool) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
@@ -308,13 +501,15 @@ impl ChainNalgebra {
     }
 
     pub fn self_contact_bvh<B: BvhShape>(&self, bvh: &mut Bvh<B>, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, early_stop: bool, margin: f64, to_wrt_average: bool) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        let res = RobotProximityFunctions::self_contact_bvh(bvh, self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), early_stop, margin);
-        return if to_wrt_average {
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
+        let res = RobotProximityFunctions::self_contact_bvh(bvh, self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), early_stop, margin);
+        let mut out = if to_wrt_average {
             res.to_average_distances(&self.link_shapes_distance_statistics_module.get_stats(&link_shape_rep, &link_shape_mode).averages)
         } else {
             res
-        }
+        };
+        self.append_attached_objects_self_query(&mut out, pairwise_group_query_contact, |a, pa, b, pb, m, s, e, p| pairwise_group_query_contact_padded(a, pa, b, pb, m, s, e, (p, margin)), link_poses, link_shape_mode, link_shape_rep, early_stop, margin);
+        out
     }
 
     pub fn double_chain_intersect(&self,
@@ -326,7 +521,11 @@ impl ChainNalgebra {
                                   other_link_shape_mode: LinkShapeMode,
                                   other_link_shape_rep: LinkShapeRep,
                                   early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
-        RobotProximityFunctions::double_chain_intersect(&self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, None, early_stop, &DoubleGroupProximityQueryMode::AllPossiblePairs)
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_intersect(&self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), early_stop, &DoubleGroupProximityQueryMode::AllPossiblePairs);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, ());
+        out
     }
 
     pub fn double_chain_intersect_bvh<B: BvhShape>(&self,
@@ -340,7 +539,11 @@ impl ChainNalgebra {
                                                    other_link_shape_mode: LinkShapeMode,
                                                    other_link_shape_rep: LinkShapeRep,
                                                    early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
-        RobotProximityFunctions::double_chain_intersect_bvh(self_bvh, other_bvh, &self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, None, early_stop)
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_intersect_bvh(self_bvh, other_bvh, &self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), early_stop);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, ());
+        out
     }
 
     pub fn double_chain_intersect_from_states(&self,
@@ -367,7 +570,11 @@ impl ChainNalgebra {
                                  other_link_shape_mode: LinkShapeMode,
                                  other_link_shape_rep: LinkShapeRep,
                                  early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
-        RobotProximityFunctions::double_chain_distance(&self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, None, early_stop, &DoubleGroupProximityQueryMode::AllPossiblePairs)
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_distance(&self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), early_stop, &DoubleGroupProximityQueryMode::AllPossiblePairs);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_distance, pairwise_group_query_distance_padded, other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, ());
+        out
     }
 
     pub fn double_chain_distance_from_states(&self,
@@ -395,7 +602,11 @@ impl ChainNalgebra {
                                 other_link_shape_rep: LinkShapeRep,
                                 early_stop: bool,
                                 margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
-        RobotProximityFunctions::double_chain_contact(&self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, None, early_stop, margin, &DoubleGroupProximityQueryMode::AllPossiblePairs)
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_contact(&self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), early_stop, margin, &DoubleGroupProximityQueryMode::AllPossiblePairs);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_contact, |a, pa, b, pb, m, s, e, p| pairwise_group_query_contact_padded(a, pa, b, pb, m, s, e, (p, margin)), other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, margin);
+        out
     }
 
     pub fn double_chain_contact_from_states(&self,
@@ -426,26 +637,154 @@ impl ChainNalgebra {
                                                  other_link_shape_rep: LinkShapeRep,
                                                  early_stop: bool,
                                                  margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
-        RobotProximityFunctions::double_chain_contact_bvh(self_bvh, other_bvh, &self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, None, early_stop, margin)
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_contact_bvh(self_bvh, other_bvh, &self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), early_stop, margin);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_contact, |a, pa, b, pb, m, s, e, p| pairwise_group_query_contact_padded(a, pa, b, pb, m, s, e, (p, margin)), other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, margin);
+        out
+    }
+
+    pub fn double_chain_intersect_sweep_and_prune(&self,
+                                                  sweep_and_prune: &mut SweepAndPrune,
+                                                  other_chain: &ChainNalgebra,
+                                                  self_link_poses: &Vec<ISE3q>,
+                                                  self_link_shape_mode: LinkShapeMode,
+                                                  self_link_shape_rep: LinkShapeRep,
+                                                  other_link_poses: &Vec<ISE3q>,
+                                                  other_link_shape_mode: LinkShapeMode,
+                                                  other_link_shape_rep: LinkShapeRep,
+                                                  other_moved: bool,
+                                                  early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_intersect_sweep_and_prune(sweep_and_prune, &self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, other_moved, skips.as_ref(), paddings.as_ref(), early_stop);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, ());
+        out
+    }
+
+    pub fn double_chain_contact_sweep_and_prune(&self,
+                                                sweep_and_prune: &mut SweepAndPrune,
+                                                other_chain: &ChainNalgebra,
+                                                self_link_poses: &Vec<ISE3q>,
+                                                self_link_shape_mode: LinkShapeMode,
+                                                self_link_shape_rep: LinkShapeRep,
+                                                other_link_poses: &Vec<ISE3q>,
+                                                other_link_shape_mode: LinkShapeMode,
+                                                other_link_shape_rep: LinkShapeRep,
+                                                other_moved: bool,
+                                                early_stop: bool,
+                                                margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_contact_sweep_and_prune(sweep_and_prune, &self.link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, &other_chain.link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, other_moved, skips.as_ref(), paddings.as_ref(), early_stop, margin);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_contact, |a, pa, b, pb, m, s, e, p| pairwise_group_query_contact_padded(a, pa, b, pb, m, s, e, (p, margin)), other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, early_stop, margin);
+        out
+    }
+
+    /// Checks the chain against obstacles built in code.  Output pairs are `(shape_idx, obstacle_idx)`.
+    pub fn obstacles_intersect(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let paddings = self.get_obstacle_paddings(link_shape_mode, obstacles.len());
+        let mut out = RobotProximityFunctions::obstacles_intersect(&self.link_shapes_module, link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, paddings.as_ref(), early_stop);
+        self.append_attached_objects_obstacles_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, early_stop, ());
+        out
+    }
+
+    pub fn obstacles_intersect_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let link_poses = self.fk(state);
+        self.obstacles_intersect(&link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, early_stop)
+    }
+
+    pub fn obstacles_distance(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
+        let paddings = self.get_obstacle_paddings(link_shape_mode, obstacles.len());
+        let mut out = RobotProximityFunctions::obstacles_distance(&self.link_shapes_module, link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, paddings.as_ref(), early_stop);
+        self.append_attached_objects_obstacles_query(&mut out, pairwise_group_query_distance, pairwise_group_query_distance_padded, link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, early_stop, ());
+        out
+    }
+
+    pub fn obstacles_distance_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
+        let link_poses = self.fk(state);
+        self.obstacles_distance(&link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, early_stop)
+    }
+
+    pub fn obstacles_contact(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+        let paddings = self.get_obstacle_paddings(link_shape_mode, obstacles.len());
+        let mut out = RobotProximityFunctions::obstacles_contact(&self.link_shapes_module, link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, paddings.as_ref(), early_stop, margin);
+        self.append_attached_objects_obstacles_query(&mut out, pairwise_group_query_contact, |a, pa, b, pb, m, s, e, p| pairwise_group_query_contact_padded(a, pa, b, pb, m, s, e, (p, margin)), link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, early_stop, margin);
+        out
+    }
+
+    pub fn obstacles_contact_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+        let link_poses = self.fk(state);
+        self.obstacles_contact(&link_poses, link_shape_mode, link_shape_rep, obstacles, obstacle_poses, early_stop, margin)
+    }
+
+    /// Checks for self-intersections with a bounding sphere, OBB, convex hull, and convex
+    /// decomposition cascade, which is as accurate as decompositions at close to the cost of
+    /// bounding spheres for pairs that are far apart.  Pairs are reported in full shape indices,
+    /// and attached objects are checked against the full convex hulls of the links.
+    pub fn self_intersect_cascade(&self, link_poses: &Vec<ISE3q>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let full_skips = self.allowed_collision_matrix.get_skips(LinkShapeMode::Full, LinkShapeRep::ConvexHull);
+        let decomposition_skips = self.allowed_collision_matrix.get_skips(LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull);
+        let mut out = RobotProximityFunctions::self_intersect_cascade(&self.link_shapes_module, link_poses, Some(full_skips), Some(decomposition_skips), self.get_self_paddings(LinkShapeMode::Full), early_stop);
+        self.append_attached_objects_self_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, link_poses, LinkShapeMode::Full, LinkShapeRep::ConvexHull, early_stop, ());
+        out
+    }
+
+    pub fn self_intersect_cascade_from_state(&self, state: &V, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let link_poses = self.fk(state);
+        self.self_intersect_cascade(&link_poses, early_stop)
+    }
+
+    pub fn double_chain_intersect_cascade(&self, other_chain: &ChainNalgebra, self_link_poses: &Vec<ISE3q>, other_link_poses: &Vec<ISE3q>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let paddings = self.get_double_chain_paddings(other_chain, LinkShapeMode::Full, LinkShapeMode::Full);
+        let full_skips = self.get_double_chain_skips(other_chain, LinkShapeMode::Full, LinkShapeMode::Full);
+        let decomposition_skips = self.get_double_chain_skips(other_chain, LinkShapeMode::Decomposition, LinkShapeMode::Decomposition);
+        let mut out = RobotProximityFunctions::double_chain_intersect_cascade(&self.link_shapes_module, self_link_poses, &other_chain.link_shapes_module, other_link_poses, full_skips.as_ref(), decomposition_skips.as_ref(), paddings.as_ref(), early_stop);
+        self.append_attached_objects_double_chain_query(&mut out, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, other_chain, self_link_poses, LinkShapeMode::Full, LinkShapeRep::ConvexHull, other_link_poses, LinkShapeMode::Full, LinkShapeRep::ConvexHull, early_stop, ());
+        out
+    }
+
+    pub fn double_chain_intersect_cascade_from_states(&self, other_chain: &ChainNalgebra, self_state: &V, other_state: &V, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let self_link_poses = self.fk(self_state);
+        let other_link_poses = other_chain.fk(other_state);
+        self.double_chain_intersect_cascade(other_chain, &self_link_poses, &other_link_poses, early_stop)
     }
 
     pub fn self_intersect_proxima<P: ProximaTrait>(&self, proxima: &mut P, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, frozen: bool) -> ProximaOutput<bool> {
         let link_shapes_module = &self.link_shapes_module;
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        RobotProximityFunctions::self_intersect_proxima(proxima, link_shapes_module, link_poses, link_shape_mode, link_shape_rep, Some(skips), frozen)
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
+        let mut out = RobotProximityFunctions::self_intersect_proxima(proxima, link_shapes_module, link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), frozen);
+        if !out.result {
+            let mut attached = DoubleGroupProximityQueryOutput::new(vec![], vec![], 0);
+            self.append_attached_objects_self_query(&mut attached, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, link_poses, link_shape_mode, link_shape_rep, true, ());
+            out.result = attached.to_intersection_result();
+        }
+        out
     }
 
     pub fn self_proximity_proxima<P: ProximaTrait>(&self, proxima: &mut P, budget: &ProximaBudget, loss_function: &ProximityLossFunction, p_norm: f64, cutoff_distance: f64, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, frozen: bool) -> ProximaOutput<f64> {
         let link_shapes_module = &self.link_shapes_module;
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
+        let skips = self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep);
         let average_distances = &self.link_shapes_distance_statistics_module.get_stats(&link_shape_rep, &link_shape_mode).averages;
-        RobotProximityFunctions::self_proximity_proxima(proxima, budget, loss_function, p_norm, cutoff_distance, link_shapes_module, link_poses, link_shape_mode, link_shape_rep, Some(skips), Some(average_distances), frozen)
+        let mut out = RobotProximityFunctions::self_proximity_proxima(proxima, budget, loss_function, p_norm, cutoff_distance, link_shapes_module, link_poses, link_shape_mode, link_shape_rep, Some(skips), self.get_self_paddings(link_shape_mode), Some(average_distances), frozen);
+        let mut attached = DoubleGroupProximityQueryOutput::new(vec![], vec![], 0);
+        self.append_attached_objects_self_query(&mut attached, pairwise_group_query_distance, pairwise_group_query_distance_padded, link_poses, link_shape_mode, link_shape_rep, false, ());
+        out.result = add_attached_objects_proximity(out.result, &attached, loss_function, p_norm, cutoff_distance);
+        out
     }
 
     pub fn double_chain_intersect_proxima<P: ProximaTrait>(&self, other_chain: &ChainNalgebra, proxima: &mut P, self_link_poses: &Vec<ISE3q>, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep, other_link_poses: &Vec<ISE3q>, other_link_shape_mode: LinkShapeMode, other_link_shape_rep: LinkShapeRep, frozen: bool) -> ProximaOutput<bool> {
         let self_link_shapes_module = &self.link_shapes_module;
         let other_link_shapes_module = &other_chain.link_shapes_module;
-        RobotProximityFunctions::double_chain_intersect_proxima(proxima, self_link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, frozen)
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_intersect_proxima(proxima, self_link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), frozen);
+        if !out.result {
+            let mut attached = DoubleGroupProximityQueryOutput::new(vec![], vec![], 0);
+            self.append_attached_objects_double_chain_query(&mut attached, pairwise_group_query_intersection, pairwise_group_query_intersection_padded, other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, true, ());
+            out.result = attached.to_intersection_result();
+        }
+        out
     }
 
     pub fn double_chain_proximity_proxima<P: ProximaTrait>(&self,
@@ -464,20 +803,40 @@ impl ChainNalgebra {
                                                            frozen: bool) -> ProximaOutput<f64> {
         let self_link_shapes_module = &self.link_shapes_module;
         let other_link_shapes_module = &other_chain.link_shapes_module;
-        RobotProximityFunctions::double_chain_proximity_proxima(proxima, budget, loss_function, p_norm, cutoff_distance, self_link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, frozen)
+        let skips = self.get_double_chain_skips(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let mut out = RobotProximityFunctions::double_chain_proximity_proxima(proxima, budget, loss_function, p_norm, cutoff_distance, self_link_shapes_module, &self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_shapes_module, other_link_poses, other_link_shape_mode, other_link_shape_rep, skips.as_ref(), paddings.as_ref(), frozen);
+        let mut attached = DoubleGroupProximityQueryOutput::new(vec![], vec![], 0);
+        self.append_attached_objects_double_chain_query(&mut attached, pairwise_group_query_distance, pairwise_group_query_distance_padded, other_chain, self_link_poses, self_link_shape_mode, self_link_shape_rep, other_link_poses, other_link_shape_mode, other_link_shape_rep, false, ());
+        out.result = add_attached_objects_proximity(out.result, &attached, loss_function, p_norm, cutoff_distance);
+        out
+    }
+
+    /// Returns the maximum distance from the link origin to any point on each shape, in shape indices.
+    /// Sphere sets and capsules are not covered by the precomputed module, so they are measured directly.
+    pub fn get_shapes_max_distances_from_origin(&self, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> Vec<f64> {
+        match link_shape_rep {
+            LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => {
+                self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep).iter().map(|x| x.calculate_max_dis_from_origin_to_point_on_shape()).collect()
+            }
+            _ => { self.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep) }
+        }
     }
 
     pub fn get_self_proxima1(&self, interpolation: f64, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> Proxima1 {
         let fk_res = self.fk(state);
         let shapes = self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = self.link_shapes_module.link_poses_to_shape_poses(&fk_res, link_shape_mode);
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
-        let cache = Proxima1Cache::new(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, Some(skips));
+        // pairs are cached unless both the preprocessed skips and the allowed collision matrix skip
+        // them, so pairs the matrix allows and disallows later still have cache elements.
+        let base_skips = self.link_shapes_skips_nalgebra_module().get_skips(link_shape_mode, link_shape_rep);
+        let skips = base_skips.zip_map(self.allowed_collision_matrix.get_skips(link_shape_mode, link_shape_rep), |x, y| x && y);
+        let cache = Proxima1Cache::new(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, Some(&skips));
         
         Proxima1 {
             cache,
-            max_distances_from_origin_a: self.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
-            max_distances_from_origin_b: self.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
+            max_distances_from_origin_a: self.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
+            max_distances_from_origin_b: self.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
             interpolation,
         }
     }
@@ -487,13 +846,13 @@ impl ChainNalgebra {
         let fk_res = self.fk(state);
         let shapes = self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = self.link_shapes_module.link_poses_to_shape_poses(&fk_res, link_shape_mode);
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
+        let skips = self.link_shapes_skips_nalgebra_module().get_skips(link_shape_mode, link_shape_rep);
         let cache = Proxima2bCache::new(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, Some(skips));
 
         Proxima2b {
             cache,
-            max_distances_from_origin_a: self.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
-            max_distances_from_origin_b: self.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
+            max_distances_from_origin_a: self.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
+            max_distances_from_origin_b: self.get_shapes_max_distances_from_origin(link_shape_mode, link_shape_rep),
             interpolation,
         }
     }
@@ -511,8 +870,8 @@ impl ChainNalgebra {
 
         Proxima1 {
             cache,
-            max_distances_from_origin_a: self.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(self_link_shape_mode, self_link_shape_rep),
-            max_distances_from_origin_b: other_chain.link_shapes_max_distance_from_origin_module.get_shapes_max_distances_from_origin(other_link_shape_mode, other_link_shape_rep),
+            max_distances_from_origin_a: self.get_shapes_max_distances_from_origin(self_link_shape_mode, self_link_shape_rep),
+            max_distances_from_origin_b: other_chain.get_shapes_max_distances_from_origin(other_link_shape_mode, other_link_shape_rep),
             interpolation,
         }
     }
@@ -522,7 +881,7 @@ impl ChainNalgebra {
         let fk_res = self.fk(state);
         let shapes = self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = self.link_shapes_module.link_poses_to_shape_poses(&fk_res, link_shape_mode);
-        let skips = self.link_shapes_skips_nalgebra_module.get_skips(link_shape_mode, link_shape_rep);
+        let skips = self.link_shapes_skips_nalgebra_module().get_skips(link_shape_mode, link_shape_rep);
         let cache = Proxima2Cache::new(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, Some(skips), lie_alg_mode);
 
         Proxima2 {
@@ -558,4 +917,97 @@ impl ChainNalgebra {
 
         Bvh::build(shapes, &poses, branch_factor)
     }
-}
\ No newline at end of file
+
+    /// Builds a sweep-and-prune structure over this chain and another chain.  The AABBs are
+    /// loosened by `margin` plus the largest padding between the two chains, so padded pairs are
+    /// still reported as candidates.
+    pub fn get_double_chain_sweep_and_prune(&self, other_chain: &ChainNalgebra, self_state: &V, self_link_shape_mode: LinkShapeMode, self_link_shape_rep: LinkShapeRep, other_state: &V, other_link_shape_mode: LinkShapeMode, other_link_shape_rep: LinkShapeRep, margin: f64) -> SweepAndPrune {
+        let self_fk_res = self.fk(self_state);
+        let self_shapes = self.link_shapes_module.get_shapes(self_link_shape_mode, self_link_shape_rep);
+        let self_poses = self.link_shapes_module.link_poses_to_shape_poses(&self_fk_res, self_link_shape_mode);
+
+        let other_fk_res = other_chain.fk(other_state);
+        let other_shapes = other_chain.link_shapes_module.get_shapes(other_link_shape_mode, other_link_shape_rep);
+        let other_poses = other_chain.link_shapes_module.link_poses_to_shape_poses(&other_fk_res, other_link_shape_mode);
+
+        let paddings = self.get_double_chain_paddings(other_chain, self_link_shape_mode, other_link_shape_mode);
+        let margin = margin + paddings.as_ref().map_or(0.0, |x| max_padding(x));
+
+        SweepAndPrune::new(self_shapes, &self_poses, other_shapes, &other_poses, margin)
+    }
+
+    pub fn get_bvh_sah<B: BvhShape>(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> Bvh<B> {
+        let fk_res = self.fk(state);
+        let shapes = self.link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = self.link_shapes_module.link_poses_to_shape_poses(&fk_res, link_shape_mode);
+
+        Bvh::build_sah(shapes, &poses)
+    }
+
+    pub fn ray_cast(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, ray: &Ray, max_distance: f64) -> Option<RobotRayCastHit> {
+        RobotRayCastingFunctions::ray_cast(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, ray, max_distance)
+    }
+
+    pub fn ray_cast_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, ray: &Ray, max_distance: f64) -> Option<RobotRayCastHit> {
+        let link_poses = self.fk(state);
+        self.ray_cast(&link_poses, link_shape_mode, link_shape_rep, ray, max_distance)
+    }
+
+    pub fn ray_cast_batch(&self, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, rays: &Vec<Ray>, max_distance: f64) -> Vec<Option<RobotRayCastHit>> {
+        RobotRayCastingFunctions::ray_cast_batch(self.link_shapes_module(), link_poses, link_shape_mode, link_shape_rep, rays, max_distance)
+    }
+
+    pub fn ray_cast_batch_from_state(&self, state: &V, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, rays: &Vec<Ray>, max_distance: f64) -> Vec<Option<RobotRayCastHit>> {
+        let link_poses = self.fk(state);
+        self.ray_cast_batch(&link_poses, link_shape_mode, link_shape_rep, rays, max_distance)
+    }
+
+    /// Casts a ray against several chains (e.g., a robot and its environment) at their given states.
+    /// Returns the index of the hit chain in `chains` along with the closest hit over all chains.
+    pub fn multi_chain_ray_cast_from_states(chains: &Vec<&ChainNalgebra>, states: &Vec<V>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, ray: &Ray, max_distance: f64) -> Option<(usize, RobotRayCastHit)> {
+        assert_eq!(chains.len(), states.len());
+
+        let mut out: Option<(usize, RobotRayCastHit)> = None;
+        let mut max_distance = max_distance;
+        for (chain_idx, (chain, state)) in chains.iter().zip(states.iter()).enumerate() {
+            if let Some(hit) = chain.ray_cast_from_state(state, link_shape_mode, link_shape_rep, ray, max_distance) {
+                max_distance = hit.distance;
+                out = Some((chain_idx, hit));
+            }
+        }
+
+        out
+    }
+
+    /// Batch version of `multi_chain_ray_cast_from_states`.  Output `i` is the closest hit of ray `i`.
+    pub fn multi_chain_ray_cast_batch_from_states(chains: &Vec<&ChainNalgebra>, states: &Vec<V>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, rays: &Vec<Ray>, max_distance: f64) -> Vec<Option<(usize, RobotRayCastHit)>> {
+        assert_eq!(chains.len(), states.len());
+
+        let mut out: Vec<Option<(usize, RobotRayCastHit)>> = vec![None; rays.len()];
+        for (chain_idx, (chain, state)) in chains.iter().zip(states.iter()).enumerate() {
+            let res = chain.ray_cast_batch_from_state(state, link_shape_mode, link_shape_rep, rays, max_distance);
+            for (curr, hit) in out.iter_mut().zip(res.into_iter()) {
+                if let Some(hit) = hit {
+                    let closer = match curr {
+                        None => true,
+                        Some((_, c)) => hit.distance < c.distance
+                    };
+                    if closer { *curr = Some((chain_idx, hit)); }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Adds the losses of attached object distances within `cutoff_distance` to a proximity value
+/// aggregated with the same `p_norm`.
+fn add_attached_objects_proximity(value: f64, attached: &DoubleGroupProximityQueryOutput<f64>, loss_function: &ProximityLossFunction, p_norm: f64, cutoff_distance: f64) -> f64 {
+    if attached.outputs.is_empty() { return value; }
+
+    let mut out = value.powf(p_norm);
+    attached.outputs.iter().for_each(|x| { if *x <= cutoff_distance { out += loss_function.loss(*x).powf(p_norm); } });
+
+    out.powf(1.0 / p_norm)
+}
@@ -20,7 +20,7 @@ impl LinkShapesMaxDistanceFromOriginTrait for ApolloLinkShapesMaxDistanceFromOri
         match link_shape_mode {
             LinkShapeMode::Full => {
                 match link_shape_rep {
-                    LinkShapeRep::ConvexHull => {
+                    LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => {
                         self.full_convex_hulls_maximum_distances.iter().filter_map(|x| {
                             match x {
                                 None => { None }
@@ -48,7 +48,7 @@ impl LinkShapesMaxDistanceFromOriginTrait for ApolloLinkShapesMaxDistanceFromOri
             }
             LinkShapeMode::Decomposition => {
                 match link_shape_rep {
-                    LinkShapeRep::ConvexHull => {
+                    LinkShapeRep::ConvexHull | LinkShapeRep::MultiSphere | LinkShapeRep::Capsule => {
                         self.decomposition_convex_hulls_maximum_distances.clone().into_iter().flatten().collect()
                     }
                     LinkShapeRep::OBB => {
@@ -0,0 +1,220 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+use crate::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+use crate::modules_runtime::link_shapes_skips_nalgebra_module::ApolloLinkShapesSkipsNalgebraModule;
+
+/// A rule of an `AllowedCollisionMatrix`.  Names may contain `*` wildcards that match any
+/// sequence of characters (e.g., `"*finger*"`), and a rule applies to a pair in either order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AllowedCollisionRule {
+    pub name_a: String,
+    pub name_b: String,
+    /// `true` if collisions between the pair are allowed (not checked), `false` if they must be
+    /// checked even when the preprocessed skips say otherwise.
+    pub allowed: bool
+}
+impl AllowedCollisionRule {
+    pub fn matches(&self, name_a: &str, name_b: &str) -> bool {
+        (wildcard_match(&self.name_a, name_a) && wildcard_match(&self.name_b, name_b)) ||
+            (wildcard_match(&self.name_a, name_b) && wildcard_match(&self.name_b, name_a))
+    }
+}
+
+/// A runtime-editable allowed collision matrix layered on top of the preprocessed skips of a
+/// chain.  Rules refer to links (or other named objects, such as links of another chain) by name
+/// and are applied in order, so later rules override earlier ones.  Rules added after
+/// `push_scope` are removed again by the matching `pop_scope`, e.g., to allow contact between
+/// the fingers and a grasped part only while it is held.
+///
+/// The matrix owns the preprocessed skips of the chain.  The resolved skip matrices are cached,
+/// so queries cost the same as with the preprocessed skips, and each rule change only updates
+/// the entries of the link pairs it matches.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AllowedCollisionMatrix {
+    pub link_names: Vec<String>,
+    pub full_shape_idx_to_link_idx: Vec<usize>,
+    pub decomposition_shape_idx_to_link_idx: Vec<usize>,
+    rules: Vec<AllowedCollisionRule>,
+    scopes: Vec<usize>,
+    base_skips: ApolloLinkShapesSkipsNalgebraModule,
+    skips: ApolloLinkShapesSkipsNalgebraModule
+}
+impl AllowedCollisionMatrix {
+    /// Creates an allowed collision matrix without any rules.
+    ///
+    /// # Arguments
+    /// - `link_names`: The names of the links of the chain, in link indices.
+    /// - `link_shapes_module`: A reference to the link shapes module, used to map shapes to links.
+    /// - `link_shapes_skips_nalgebra_module`: The preprocessed skips the rules are layered on.
+    ///
+    /// # Returns
+    /// An instance of `AllowedCollisionMatrix`.
+    pub fn new(link_names: Vec<String>, link_shapes_module: &ApolloLinkShapesModule, link_shapes_skips_nalgebra_module: ApolloLinkShapesSkipsNalgebraModule) -> Self {
+        Self {
+            link_names,
+            full_shape_idx_to_link_idx: link_shapes_module.full_shape_idx_to_link_idx().clone(),
+            decomposition_shape_idx_to_link_idx: link_shapes_module.decomposition_shape_idx_to_link_idx_and_link_sub_idx().iter().map(|x| x.0).collect(),
+            rules: vec![],
+            scopes: vec![],
+            skips: link_shapes_skips_nalgebra_module.clone(),
+            base_skips: link_shapes_skips_nalgebra_module,
+        }
+    }
+
+    /// Allows collisions between the given names (i.e., the pair is not checked).
+    pub fn allow(&mut self, name_a: &str, name_b: &str) {
+        self.add_rule(name_a, name_b, true);
+    }
+
+    /// Forbids collisions between the given names, i.e., the pair is always checked.
+    pub fn disallow(&mut self, name_a: &str, name_b: &str) {
+        self.add_rule(name_a, name_b, false);
+    }
+
+    /// Starts a temporary scope.  All rules added until the matching `pop_scope` are discarded by it.
+    pub fn push_scope(&mut self) {
+        self.scopes.push(self.rules.len());
+    }
+
+    /// Ends the innermost temporary scope and discards the rules added within it.
+    pub fn pop_scope(&mut self) {
+        let num_rules = self.scopes.pop().expect("pop_scope called without a matching push_scope");
+        self.remove_rules_from(num_rules);
+    }
+
+    /// Removes all rules and scopes, restoring the preprocessed skips.
+    pub fn clear(&mut self) {
+        self.scopes.clear();
+        self.remove_rules_from(0);
+    }
+
+    /// Returns the decision of the last rule that matches the pair, or `None` if no rule matches.
+    pub fn is_allowed(&self, name_a: &str, name_b: &str) -> Option<bool> {
+        self.rules.iter().rev().find(|x| x.matches(name_a, name_b)).map(|x| x.allowed)
+    }
+
+    /// Returns the rules in the order they are applied.
+    #[inline(always)]
+    pub fn rules(&self) -> &Vec<AllowedCollisionRule> {
+        &self.rules
+    }
+
+    /// Returns the preprocessed skips, without any rules applied.
+    #[inline(always)]
+    pub fn base_skips(&self) -> &ApolloLinkShapesSkipsNalgebraModule {
+        &self.base_skips
+    }
+
+    /// Retrieves the skips matrix for self queries, with all rules applied.
+    #[inline(always)]
+    pub fn get_skips(&self, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> &DMatrix<bool> {
+        self.skips.get_skips(link_shape_mode, link_shape_rep)
+    }
+
+    /// Returns the skips matrix between the shapes of this chain and the shapes of another chain,
+    /// applying the rules of both matrices (the other one's take precedence).  Returns `None` if
+    /// neither has rules.
+    pub fn get_double_chain_skips(&self, self_link_shape_mode: LinkShapeMode, other: &AllowedCollisionMatrix, other_link_shape_mode: LinkShapeMode) -> Option<DMatrix<bool>> {
+        if self.rules.is_empty() && other.rules.is_empty() { return None; }
+
+        let a = self.get_shape_idx_to_link_idx(self_link_shape_mode);
+        let b = other.get_shape_idx_to_link_idx(other_link_shape_mode);
+
+        let link_skips = DMatrix::from_fn(self.link_names.len(), other.link_names.len(), |i, j| {
+            let name_a = &self.link_names[i];
+            let name_b = &other.link_names[j];
+            match other.is_allowed(name_a, name_b).or(self.is_allowed(name_a, name_b)) {
+                None => { false }
+                Some(allowed) => { allowed }
+            }
+        });
+
+        Some(DMatrix::from_fn(a.len(), b.len(), |i, j| link_skips[(a[i], b[j])]))
+    }
+
+    /// Returns the map from shape indices to link indices for the given shape mode.
+    pub fn get_shape_idx_to_link_idx(&self, link_shape_mode: LinkShapeMode) -> &Vec<usize> {
+        match &link_shape_mode {
+            LinkShapeMode::Full => { &self.full_shape_idx_to_link_idx }
+            LinkShapeMode::Decomposition => { &self.decomposition_shape_idx_to_link_idx }
+        }
+    }
+
+    fn add_rule(&mut self, name_a: &str, name_b: &str, allowed: bool) {
+        let rule = AllowedCollisionRule {
+            name_a: name_a.to_string(),
+            name_b: name_b.to_string(),
+            allowed,
+        };
+        let link_pairs = self.get_matching_link_pairs(&[rule.clone()]);
+        self.rules.push(rule);
+        self.update_link_pairs(&link_pairs);
+    }
+
+    fn remove_rules_from(&mut self, num_rules: usize) {
+        if num_rules >= self.rules.len() { return; }
+        let removed = self.rules.split_off(num_rules);
+        let link_pairs = self.get_matching_link_pairs(&removed);
+        self.update_link_pairs(&link_pairs);
+    }
+
+    /// Returns the pairs `(a, b)` of distinct links of the chain matched by any of the given rules.
+    fn get_matching_link_pairs(&self, rules: &[AllowedCollisionRule]) -> Vec<(usize, usize)> {
+        let n = self.link_names.len();
+        let mut out = vec![];
+        for a in 0..n {
+            for b in 0..n {
+                if a != b && rules.iter().any(|x| x.matches(&self.link_names[a], &self.link_names[b])) { out.push((a, b)); }
+            }
+        }
+
+        out
+    }
+
+    /// Resolves the given link pairs against the current rules and rewrites the cached skips of
+    /// their shapes, in every shape mode and representation.
+    fn update_link_pairs(&mut self, link_pairs: &Vec<(usize, usize)>) {
+        for (a, b) in link_pairs {
+            let decision = self.is_allowed(&self.link_names[*a], &self.link_names[*b]);
+
+            for link_shape_mode in [LinkShapeMode::Full, LinkShapeMode::Decomposition] {
+                let shape_idx_to_link_idx = match &link_shape_mode {
+                    LinkShapeMode::Full => { &self.full_shape_idx_to_link_idx }
+                    LinkShapeMode::Decomposition => { &self.decomposition_shape_idx_to_link_idx }
+                };
+                let shapes_a: Vec<usize> = (0..shape_idx_to_link_idx.len()).filter(|x| shape_idx_to_link_idx[*x] == *a).collect();
+                let shapes_b: Vec<usize> = (0..shape_idx_to_link_idx.len()).filter(|x| shape_idx_to_link_idx[*x] == *b).collect();
+
+                for link_shape_rep in [LinkShapeRep::ConvexHull, LinkShapeRep::OBB, LinkShapeRep::BoundingSphere, LinkShapeRep::MultiSphere, LinkShapeRep::Capsule] {
+                    let base = self.base_skips.get_skips(link_shape_mode, link_shape_rep);
+                    let skips = self.skips.get_skips_mut(link_shape_mode, link_shape_rep);
+                    for i in &shapes_a {
+                        for j in &shapes_b {
+                            skips[(*i, *j)] = decision.unwrap_or(base[(*i, *j)]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Matches `name` against `pattern`, where `*` in the pattern matches any sequence of characters.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 { return pattern == name; }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) { return false; }
+
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            None => { return false; }
+            Some(idx) => { rest = &rest[idx + part.len()..]; }
+        }
+    }
+
+    true
+}
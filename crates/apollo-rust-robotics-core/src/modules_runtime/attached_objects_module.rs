@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_mesh_utils::stl::load_stl_file;
+use apollo_rust_mesh_utils::trimesh::ToTriMesh;
+use apollo_rust_proximity_parry::offset_shape::OffsetShape;
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use nalgebra::DMatrix;
+use parry3d_f64::math::Point;
+use parry3d_f64::shape::ConvexPolyhedron;
+use crate::modules_runtime::allowed_collision_matrix::{wildcard_match, AllowedCollisionMatrix};
+use crate::modules_runtime::link_shapes_module::LinkShapeMode;
+use crate::modules_runtime::link_shapes_padding_nalgebra_module::ApolloLinkShapesPaddingNalgebraModule;
+
+/// An object rigidly attached to a link of a chain, e.g., a part held by a gripper.
+#[derive(Clone, Debug)]
+pub struct AttachedObject {
+    pub name: String,
+    pub link_idx: usize,
+    /// The pose of the object relative to the frame of the link it is attached to.
+    pub relative_pose: ISE3q,
+    /// Names of links (wildcards allowed) that may touch the object.  The link the object is
+    /// attached to is always allowed to touch it.
+    pub touch_links: Vec<String>
+}
+
+/// The `ApolloAttachedObjectsModule` struct holds the objects currently attached to a chain.
+/// Shape indices of attached objects in query outputs come after all link shapes of the chain,
+/// i.e., attached object `k` has shape index `num_link_shapes + k`.
+#[derive(Clone, Default)]
+pub struct ApolloAttachedObjectsModule {
+    pub attached_objects: Vec<AttachedObject>,
+    pub shapes: Vec<OffsetShape>
+}
+impl ApolloAttachedObjectsModule {
+    /// Creates a module without any attached objects.
+    pub fn new() -> Self {
+        Self { attached_objects: vec![], shapes: vec![] }
+    }
+
+    /// Attaches an object to a link, replacing any attached object with the same name.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the object, which can also be used in allowed collision matrix rules.
+    /// - `link_idx`: The index of the link the object is attached to.
+    /// - `shape`: The shape of the object.
+    /// - `relative_pose`: The pose of the object relative to the link frame.
+    /// - `touch_links`: Names of links (wildcards allowed) that may touch the object.
+    pub fn attach(&mut self, name: &str, link_idx: usize, shape: OffsetShape, relative_pose: ISE3q, touch_links: Vec<String>) {
+        self.detach(name);
+        self.attached_objects.push(AttachedObject {
+            name: name.to_string(),
+            link_idx,
+            relative_pose,
+            touch_links,
+        });
+        self.shapes.push(shape);
+    }
+
+    /// Detaches the object with the given name.
+    ///
+    /// # Returns
+    /// The detached object and its shape, or `None` if no object with that name is attached.
+    pub fn detach(&mut self, name: &str) -> Option<(AttachedObject, OffsetShape)> {
+        let idx = self.attached_objects.iter().position(|x| x.name == name)?;
+        Some((self.attached_objects.remove(idx), self.shapes.remove(idx)))
+    }
+
+    /// Detaches all objects.
+    pub fn detach_all(&mut self) {
+        self.attached_objects.clear();
+        self.shapes.clear();
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.attached_objects.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn num_attached_objects(&self) -> usize {
+        self.attached_objects.len()
+    }
+
+    #[inline(always)]
+    pub fn shapes(&self) -> &Vec<OffsetShape> {
+        &self.shapes
+    }
+
+    /// Returns the world poses of the attached objects given the poses of the links.
+    pub fn get_poses(&self, link_poses: &Vec<ISE3q>) -> Vec<ISE3q> {
+        self.attached_objects.iter().map(|x| link_poses[x.link_idx].group_operator(&x.relative_pose)).collect()
+    }
+
+    /// Returns the skips matrix between the link shapes of the chain (rows) and the attached
+    /// objects (columns).  A rule of the allowed collision matrix naming the object takes
+    /// precedence; otherwise, the pair is skipped if the link is the parent link or a touch link.
+    pub fn get_self_skips(&self, link_shape_mode: LinkShapeMode, allowed_collision_matrix: &AllowedCollisionMatrix) -> DMatrix<bool> {
+        let shape_idx_to_link_idx = allowed_collision_matrix.get_shape_idx_to_link_idx(link_shape_mode);
+
+        DMatrix::from_fn(shape_idx_to_link_idx.len(), self.attached_objects.len(), |i, j| {
+            let link_idx = shape_idx_to_link_idx[i];
+            let link_name = &allowed_collision_matrix.link_names[link_idx];
+            let object = &self.attached_objects[j];
+            match allowed_collision_matrix.is_allowed(link_name, &object.name) {
+                Some(allowed) => { allowed }
+                None => {
+                    link_idx == object.link_idx || object.touch_links.iter().any(|x| wildcard_match(x, link_name))
+                }
+            }
+        })
+    }
+
+    /// Returns the skips matrix between the link shapes of another chain (rows) and the attached
+    /// objects (columns).  Only allowed collision matrix rules apply, the other chain's first.
+    pub fn get_other_chain_skips(&self, self_allowed_collision_matrix: &AllowedCollisionMatrix, other_link_shape_mode: LinkShapeMode, other_allowed_collision_matrix: &AllowedCollisionMatrix) -> DMatrix<bool> {
+        let shape_idx_to_link_idx = other_allowed_collision_matrix.get_shape_idx_to_link_idx(other_link_shape_mode);
+
+        DMatrix::from_fn(shape_idx_to_link_idx.len(), self.attached_objects.len(), |i, j| {
+            let link_name = &other_allowed_collision_matrix.link_names[shape_idx_to_link_idx[i]];
+            let object_name = &self.attached_objects[j].name;
+            other_allowed_collision_matrix.is_allowed(link_name, object_name).or(self_allowed_collision_matrix.is_allowed(link_name, object_name)).unwrap_or(false)
+        })
+    }
+
+    /// Returns the skips matrix between pairs of attached objects of the chain.  A rule of the
+    /// allowed collision matrix naming both objects takes precedence; otherwise, objects attached
+    /// to the same link are skipped, as they cannot move relative to each other.
+    pub fn get_objects_skips(&self, allowed_collision_matrix: &AllowedCollisionMatrix) -> DMatrix<bool> {
+        let n = self.attached_objects.len();
+
+        DMatrix::from_fn(n, n, |i, j| {
+            let (a, b) = (&self.attached_objects[i], &self.attached_objects[j]);
+            if i == j { return true; }
+            allowed_collision_matrix.is_allowed(&a.name, &b.name).unwrap_or(a.link_idx == b.link_idx)
+        })
+    }
+
+    /// Returns the skips matrix between the attached objects of this chain (rows) and the attached
+    /// objects of another chain (columns).  Only allowed collision matrix rules apply, the other
+    /// chain's first.
+    pub fn get_other_chain_objects_skips(&self, self_allowed_collision_matrix: &AllowedCollisionMatrix, other: &ApolloAttachedObjectsModule, other_allowed_collision_matrix: &AllowedCollisionMatrix) -> DMatrix<bool> {
+        DMatrix::from_fn(self.attached_objects.len(), other.attached_objects.len(), |i, j| {
+            let (name_a, name_b) = (&self.attached_objects[i].name, &other.attached_objects[j].name);
+            other_allowed_collision_matrix.is_allowed(name_a, name_b).or(self_allowed_collision_matrix.is_allowed(name_a, name_b)).unwrap_or(false)
+        })
+    }
+
+    /// Returns the paddings between the link shapes of the chain (rows) and the attached objects
+    /// (columns).  An object takes the padding of the link it is attached to, so each entry is the
+    /// padding between that link and the link of the shape.
+    pub fn get_self_paddings(&self, link_shape_mode: LinkShapeMode, link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule) -> DMatrix<f64> {
+        let shape_idx_to_link_idx = link_shapes_padding_nalgebra_module.get_shape_idx_to_link_idx(link_shape_mode);
+
+        DMatrix::from_fn(shape_idx_to_link_idx.len(), self.attached_objects.len(), |i, j| {
+            link_shapes_padding_nalgebra_module.get_link_pair_padding(shape_idx_to_link_idx[i], self.attached_objects[j].link_idx)
+        })
+    }
+
+    /// Returns the paddings between the attached objects (rows) and `num_obstacles` obstacles
+    /// without padding of their own (columns).
+    pub fn get_obstacle_paddings(&self, link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule, num_obstacles: usize) -> DMatrix<f64> {
+        DMatrix::from_fn(self.attached_objects.len(), num_obstacles, |i, _| {
+            link_shapes_padding_nalgebra_module.link_paddings[self.attached_objects[i].link_idx]
+        })
+    }
+
+    /// Returns the paddings between the link shapes of another chain (rows) and the attached
+    /// objects (columns), i.e., the sum of the two link paddings.
+    pub fn get_other_chain_paddings(&self, self_link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule, other_link_shape_mode: LinkShapeMode, other_link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule) -> DMatrix<f64> {
+        let other_shape_paddings = other_link_shapes_padding_nalgebra_module.get_shape_paddings(other_link_shape_mode);
+
+        DMatrix::from_fn(other_shape_paddings.len(), self.attached_objects.len(), |i, j| {
+            other_shape_paddings[i] + self_link_shapes_padding_nalgebra_module.link_paddings[self.attached_objects[j].link_idx]
+        })
+    }
+
+    /// Returns the paddings between pairs of attached objects of the chain, i.e., the padding
+    /// between the links they are attached to.
+    pub fn get_objects_paddings(&self, link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule) -> DMatrix<f64> {
+        let n = self.attached_objects.len();
+
+        DMatrix::from_fn(n, n, |i, j| {
+            link_shapes_padding_nalgebra_module.get_link_pair_padding(self.attached_objects[i].link_idx, self.attached_objects[j].link_idx)
+        })
+    }
+
+    /// Returns the paddings between the attached objects of this chain (rows) and the attached
+    /// objects of another chain (columns), i.e., the sum of the two link paddings.
+    pub fn get_other_chain_objects_paddings(&self, self_link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule, other: &ApolloAttachedObjectsModule, other_link_shapes_padding_nalgebra_module: &ApolloLinkShapesPaddingNalgebraModule) -> DMatrix<f64> {
+        DMatrix::from_fn(self.attached_objects.len(), other.attached_objects.len(), |i, j| {
+            self_link_shapes_padding_nalgebra_module.link_paddings[self.attached_objects[i].link_idx] + other_link_shapes_padding_nalgebra_module.link_paddings[other.attached_objects[j].link_idx]
+        })
+    }
+
+    /// Loads an STL mesh as a convex hull shape, for attaching meshes as objects.
+    pub fn convex_hull_shape_from_stl_file(path: &PathBuf) -> OffsetShape {
+        let tm = load_stl_file(path).unwrap_or_else(|e| panic!("error: {:?}, {:?}", path, e)).to_trimesh();
+        let points: Vec<Point<f64>> = tm.points().iter().map(|x| Point::new(x[0], x[1], x[2])).collect();
+        let cp = ConvexPolyhedron::from_convex_hull(&points).expect("error");
+        OffsetShape::new(cp, None)
+    }
+}
@@ -10,9 +10,13 @@ pub struct ApolloLinkShapesDistanceStatisticsNalgebraModule {
     pub full_convex_hulls: LinkShapesDistanceStatisticsNalgebra,
     pub full_obbs: LinkShapesDistanceStatisticsNalgebra,
     pub full_bounding_spheres: LinkShapesDistanceStatisticsNalgebra,
+    pub full_multi_spheres: LinkShapesDistanceStatisticsNalgebra,
+    pub full_capsules: LinkShapesDistanceStatisticsNalgebra,
     pub decomposition_convex_hulls: LinkShapesDistanceStatisticsNalgebra,
     pub decomposition_obbs: LinkShapesDistanceStatisticsNalgebra,
     pub decomposition_bounding_spheres: LinkShapesDistanceStatisticsNalgebra,
+    pub decomposition_multi_spheres: LinkShapesDistanceStatisticsNalgebra,
+    pub decomposition_capsules: LinkShapesDistanceStatisticsNalgebra,
 }
 impl ApolloLinkShapesDistanceStatisticsNalgebraModule {
     /// Converts an `ApolloLinkShapesDistanceStatisticsModule` into a `ApolloLinkShapesDistanceStatisticsNalgebraModule`.
@@ -27,9 +31,13 @@ impl ApolloLinkShapesDistanceStatisticsNalgebraModule {
             full_convex_hulls: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.full_convex_hulls),
             full_obbs: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.full_obbs),
             full_bounding_spheres: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.full_bounding_spheres),
+            full_multi_spheres: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.full_multi_spheres),
+            full_capsules: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.full_capsules),
             decomposition_convex_hulls: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.decomposition_convex_hulls),
             decomposition_obbs: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.decomposition_obbs),
             decomposition_bounding_spheres: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.decomposition_bounding_spheres),
+            decomposition_multi_spheres: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.decomposition_multi_spheres),
+            decomposition_capsules: LinkShapesDistanceStatisticsNalgebra::from_link_shapes_distance_statistics(&link_shapes_distance_statistics_module.decomposition_capsules),
         }
     }
 
@@ -48,6 +56,8 @@ impl ApolloLinkShapesDistanceStatisticsNalgebraModule {
                     LinkShapeRep::ConvexHull => { &self.full_convex_hulls }
                     LinkShapeRep::OBB => { &self.full_obbs }
                     LinkShapeRep::BoundingSphere => { &self.full_bounding_spheres }
+                    LinkShapeRep::MultiSphere => { &self.full_multi_spheres }
+                    LinkShapeRep::Capsule => { &self.full_capsules }
                 }
             }
             LinkShapeMode::Decomposition => {
@@ -55,6 +65,8 @@ impl ApolloLinkShapesDistanceStatisticsNalgebraModule {
                     LinkShapeRep::ConvexHull => { &self.decomposition_convex_hulls }
                     LinkShapeRep::OBB => { &self.decomposition_obbs }
                     LinkShapeRep::BoundingSphere => { &self.decomposition_bounding_spheres }
+                    LinkShapeRep::MultiSphere => { &self.decomposition_multi_spheres }
+                    LinkShapeRep::Capsule => { &self.decomposition_capsules }
                 }
             }
         }
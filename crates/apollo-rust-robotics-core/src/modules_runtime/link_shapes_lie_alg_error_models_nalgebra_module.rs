@@ -41,9 +41,10 @@ impl ApolloLinkShapesLieAlgErrorModelsNalgebraModule {
         }
     }
 
+    /// Error models are not fit for sphere sets and capsules, so an error is returned for them.
     #[inline(always)]
-    pub fn get_model(&self, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, lie_alg_mode: LieAlgMode) -> &DMatrix<([f64; 3], [f64; 3])> {
-        match (link_shape_mode, link_shape_rep, lie_alg_mode) {
+    pub fn get_model(&self, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, lie_alg_mode: LieAlgMode) -> Result<&DMatrix<([f64; 3], [f64; 3])>, String> {
+        let out = match (link_shape_mode, link_shape_rep, lie_alg_mode) {
             (LinkShapeMode::Full, LinkShapeRep::ConvexHull, LieAlgMode::Standard) => &self.full_convex_hulls_standard_models,
             (LinkShapeMode::Full, LinkShapeRep::OBB, LieAlgMode::Standard) => &self.full_obbs_standard_models,
             (LinkShapeMode::Full, LinkShapeRep::BoundingSphere, LieAlgMode::Standard) => &self.full_bounding_spheres_standard_models,
@@ -57,7 +58,10 @@ impl ApolloLinkShapesLieAlgErrorModelsNalgebraModule {
             (LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull, LieAlgMode::Pseudo) => &self.decomposition_convex_hulls_pseudo_models,
             (LinkShapeMode::Decomposition, LinkShapeRep::OBB, LieAlgMode::Pseudo) => &self.decomposition_obbs_pseudo_models,
             (LinkShapeMode::Decomposition, LinkShapeRep::BoundingSphere, LieAlgMode::Pseudo) => &self.decomposition_bounding_spheres_pseudo_models,
-        }
+            (_, LinkShapeRep::MultiSphere | LinkShapeRep::Capsule, _) => { return Err(format!("no lie algebra error models for {:?}", link_shape_rep)); }
+        };
+
+        Ok(out)
     }
 }
 */
\ No newline at end of file
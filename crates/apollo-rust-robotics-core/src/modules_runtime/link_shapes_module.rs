@@ -4,35 +4,43 @@ use apollo_rust_mesh_utils::trimesh::ToTriMesh;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
 use apollo_rust_modules::ResourcesSubDirectory;
-use apollo_rust_proximity_parry::offset_shape::{
-    to_offset_shape_bounding_sphere, to_offset_shape_obb, OffsetShape,
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_approximations_module::{
+    ApolloLinkShapesApproximationsModule, BoundingSphereDescriptor, CapsuleDescriptor,
 };
+use apollo_rust_proximity_parry::offset_shape::{to_offset_shape_bounding_sphere, to_offset_shape_obb, OffsetShape};
+use apollo_rust_spatial::isometry3::{ApolloIsometry3Trait, I3};
 use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
-use parry3d_f64::math::Point;
-use parry3d_f64::shape::ConvexPolyhedron;
+use parry3d_f64::math::{Isometry, Point};
+use parry3d_f64::shape::{Capsule, Compound, ConvexPolyhedron, SharedShape};
 
 /// initialized in apollo-rust-robotics
-/// The `ApolloLinkShapesModule` struct holds various link shapes for a robot, including full convex hulls, OBBs, and bounding spheres.
+/// The `ApolloLinkShapesModule` struct holds various link shapes for a robot, including full convex hulls, OBBs, bounding spheres, sphere sets, and capsules.
 #[derive(Clone)]
 pub struct ApolloLinkShapesModule {
     pub full_convex_hulls: Vec<OffsetShape>,
     pub full_obbs: Vec<OffsetShape>,
     pub full_bounding_spheres: Vec<OffsetShape>,
+    pub full_multi_spheres: Vec<OffsetShape>,
+    pub full_capsules: Vec<OffsetShape>,
     pub full_shape_idx_to_link_idx: Vec<usize>,
     pub link_idx_to_full_shape_idx: Vec<Option<usize>>,
     pub decomposition_convex_hulls: Vec<OffsetShape>,
     pub decomposition_obbs: Vec<OffsetShape>,
     pub decomposition_bounding_spheres: Vec<OffsetShape>,
+    pub decomposition_multi_spheres: Vec<OffsetShape>,
+    pub decomposition_capsules: Vec<OffsetShape>,
     pub decomposition_shape_idx_to_link_idx_and_link_sub_idx: Vec<(usize, usize)>,
     pub link_idx_to_decomposition_shape_idxs: Vec<Vec<usize>>,
 }
 impl ApolloLinkShapesModule {
     /// Creates a new `ApolloLinkShapesModule` by loading meshes from the provided mesh modules.
+    /// Sphere sets and capsules are built from the descriptors fit by the preprocessor.
     ///
     /// # Arguments
     /// - `s`: A reference to the `ResourcesSubDirectory` for the directory structure.
     /// - `convex_hull_meshes_module`: A reference to the convex hull meshes module.
     /// - `convex_decomposition_meshes_module`: A reference to the convex decomposition meshes module.
+    /// - `link_shapes_approximations_module`: A reference to the link shapes approximations module.
     ///
     /// # Returns
     /// A new `ApolloLinkShapesModule` instance.
@@ -40,43 +48,55 @@ impl ApolloLinkShapesModule {
         s: &ResourcesSubDirectory<P>,
         convex_hull_meshes_module: &ApolloConvexHullMeshesModule<P>,
         convex_decomposition_meshes_module: &ApolloConvexDecompositionMeshesModule<P>,
+        link_shapes_approximations_module: &ApolloLinkShapesApproximationsModule,
     ) -> Self {
         let mut full_convex_hulls = vec![];
         let mut full_obbs = vec![];
         let mut full_bounding_spheres = vec![];
+        let mut full_multi_spheres = vec![];
+        let mut full_capsules = vec![];
         let mut full_shape_idx_to_link_idx = vec![];
         let mut link_idx_to_full_shape_idx = vec![];
 
         let mut decomposition_convex_hulls = vec![];
         let mut decomposition_obbs = vec![];
         let mut decomposition_bounding_spheres = vec![];
+        let mut decomposition_multi_spheres = vec![];
+        let mut decomposition_capsules = vec![];
         let mut decomposition_shape_idx_to_link_idx_and_link_sub_idx = vec![];
         let mut link_idx_to_decomposition_shape_idxs = vec![];
 
+        let (full_convex_polyhedra, decomposition_convex_polyhedra) = Self::convex_polyhedra_from_mesh_modules(
+            s,
+            convex_hull_meshes_module,
+            convex_decomposition_meshes_module,
+        );
+
         let mut count = 0;
-        convex_hull_meshes_module
-            .stl_link_mesh_relative_paths
-            .iter()
+        full_convex_polyhedra
+            .into_iter()
             .enumerate()
             .for_each(|(link_idx, x)| {
-                if let Some(path_buf) = x {
-                    let full_path = s.resolve_path(path_buf);
-                    let tm = load_stl_file(&full_path)
-                        .expect(&format!("error: {:?}", full_path))
-                        .to_trimesh();
-                    let points: Vec<Point<f64>> = tm
-                        .points()
-                        .iter()
-                        .map(|x| Point::new(x[0], x[1], x[2]))
-                        .collect();
-                    let cp = ConvexPolyhedron::from_convex_hull(&points).expect("error");
-                    let os = OffsetShape::new(cp.clone(), None);
+                if let Some(cp) = x {
                     let obb = to_offset_shape_obb(&cp);
                     let bs = to_offset_shape_bounding_sphere(&cp);
+                    let ms = multi_sphere_from_descriptors(
+                        link_shapes_approximations_module.full_multi_spheres[link_idx]
+                            .as_ref()
+                            .expect("error"),
+                    );
+                    let ca = capsule_from_descriptor(
+                        link_shapes_approximations_module.full_capsules[link_idx]
+                            .as_ref()
+                            .expect("error"),
+                    );
+                    let os = OffsetShape::new(cp, None);
 
                     full_convex_hulls.push(os);
                     full_obbs.push(obb);
                     full_bounding_spheres.push(bs);
+                    full_multi_spheres.push(ms);
+                    full_capsules.push(ca);
 
                     link_idx_to_full_shape_idx.push(Some(count));
                     full_shape_idx_to_link_idx.push(link_idx);
@@ -87,28 +107,27 @@ impl ApolloLinkShapesModule {
             });
 
         let mut count = 0;
-        convex_decomposition_meshes_module
-            .stl_link_mesh_relative_paths
-            .iter()
+        decomposition_convex_polyhedra
+            .into_iter()
             .enumerate()
             .for_each(|(link_idx, x)| {
                 let mut curr = vec![];
-                x.iter().enumerate().for_each(|(link_sub_idx, path_buf)| {
-                    let full_path = s.resolve_path(path_buf);
-                    let tm = load_stl_file(&full_path).expect("error").to_trimesh();
-                    let points: Vec<Point<f64>> = tm
-                        .points()
-                        .iter()
-                        .map(|x| Point::new(x[0], x[1], x[2]))
-                        .collect();
-                    let cp = ConvexPolyhedron::from_convex_hull(&points).expect("error");
-                    let os = OffsetShape::new(cp.clone(), None);
+                x.into_iter().enumerate().for_each(|(link_sub_idx, cp)| {
                     let obb = to_offset_shape_obb(&cp);
                     let bs = to_offset_shape_bounding_sphere(&cp);
+                    let ms = multi_sphere_from_descriptors(
+                        &link_shapes_approximations_module.decomposition_multi_spheres[link_idx][link_sub_idx],
+                    );
+                    let ca = capsule_from_descriptor(
+                        &link_shapes_approximations_module.decomposition_capsules[link_idx][link_sub_idx],
+                    );
+                    let os = OffsetShape::new(cp, None);
 
                     decomposition_convex_hulls.push(os);
                     decomposition_obbs.push(obb);
                     decomposition_bounding_spheres.push(bs);
+                    decomposition_multi_spheres.push(ms);
+                    decomposition_capsules.push(ca);
 
                     decomposition_shape_idx_to_link_idx_and_link_sub_idx
                         .push((link_idx, link_sub_idx));
@@ -122,16 +141,59 @@ impl ApolloLinkShapesModule {
             full_convex_hulls,
             full_obbs,
             full_bounding_spheres,
+            full_multi_spheres,
+            full_capsules,
             full_shape_idx_to_link_idx,
             link_idx_to_full_shape_idx,
             decomposition_convex_hulls,
             decomposition_obbs,
             decomposition_bounding_spheres,
+            decomposition_multi_spheres,
+            decomposition_capsules,
             decomposition_shape_idx_to_link_idx_and_link_sub_idx,
             link_idx_to_decomposition_shape_idxs,
         }
     }
 
+    /// Loads the convex hull of each link and the convex decomposition pieces of each link from
+    /// the provided mesh modules.
+    ///
+    /// # Returns
+    /// A tuple of the convex hulls in link indices (`None` for links without geometry) and the
+    /// convex decomposition pieces in link indices.
+    pub fn convex_polyhedra_from_mesh_modules<P: ApolloPathBufTrait + Clone>(
+        s: &ResourcesSubDirectory<P>,
+        convex_hull_meshes_module: &ApolloConvexHullMeshesModule<P>,
+        convex_decomposition_meshes_module: &ApolloConvexDecompositionMeshesModule<P>,
+    ) -> (Vec<Option<ConvexPolyhedron>>, Vec<Vec<ConvexPolyhedron>>) {
+        let load = |path_buf: &P| {
+            let full_path = s.resolve_path(path_buf);
+            let tm = load_stl_file(&full_path)
+                .unwrap_or_else(|e| panic!("error: {:?}, {:?}", full_path, e))
+                .to_trimesh();
+            let points: Vec<Point<f64>> = tm
+                .points()
+                .iter()
+                .map(|x| Point::new(x[0], x[1], x[2]))
+                .collect();
+            ConvexPolyhedron::from_convex_hull(&points).expect("error")
+        };
+
+        let full = convex_hull_meshes_module
+            .stl_link_mesh_relative_paths
+            .iter()
+            .map(|x| x.as_ref().map(|path_buf| load(path_buf)))
+            .collect();
+
+        let decomposition = convex_decomposition_meshes_module
+            .stl_link_mesh_relative_paths
+            .iter()
+            .map(|x| x.iter().map(|path_buf| load(path_buf)).collect())
+            .collect();
+
+        (full, decomposition)
+    }
+
     /*
     pub fn from_environment_mesh_modules(s: &ResourcesSingleEnvironmentDirectory, convex_hull_meshes_module: &ApolloEnvironmentConvexHullMeshesModule, convex_decomposition_meshes_module: &ApolloEnvironmentConvexDecompositionMeshesModule) -> Self {
         let ss = ResourcesSingleRobotDirectory {
@@ -162,6 +224,18 @@ impl ApolloLinkShapesModule {
         &self.full_bounding_spheres
     }
 
+    /// Returns a reference to the full multi-sphere shapes.
+    #[inline(always)]
+    pub fn full_multi_spheres(&self) -> &Vec<OffsetShape> {
+        &self.full_multi_spheres
+    }
+
+    /// Returns a reference to the full capsule shapes.
+    #[inline(always)]
+    pub fn full_capsules(&self) -> &Vec<OffsetShape> {
+        &self.full_capsules
+    }
+
     /// Returns a reference to the decomposition convex hull shapes.
     #[inline(always)]
     pub fn decomposition_convex_hulls(&self) -> &Vec<OffsetShape> {
@@ -180,6 +254,18 @@ impl ApolloLinkShapesModule {
         &self.decomposition_bounding_spheres
     }
 
+    /// Returns a reference to the decomposition multi-sphere shapes.
+    #[inline(always)]
+    pub fn decomposition_multi_spheres(&self) -> &Vec<OffsetShape> {
+        &self.decomposition_multi_spheres
+    }
+
+    /// Returns a reference to the decomposition capsule shapes.
+    #[inline(always)]
+    pub fn decomposition_capsules(&self) -> &Vec<OffsetShape> {
+        &self.decomposition_capsules
+    }
+
     /// Returns a reference to the shape-to-link index mapping for full shapes.
     #[inline(always)]
     pub fn full_shape_idx_to_link_idx(&self) -> &Vec<usize> {
@@ -242,7 +328,7 @@ impl ApolloLinkShapesModule {
     ///
     /// # Arguments
     /// - `link_shape_mode`: The mode of the link shapes (full or decomposition).
-    /// - `link_shape_rep`: The representation of the link shapes (convex hull, OBB, or bounding sphere).
+    /// - `link_shape_rep`: The representation of the link shapes (convex hull, OBB, bounding sphere, multi-sphere, or capsule).
     ///
     /// # Returns
     /// A reference to a vector of `OffsetShape`.
@@ -256,6 +342,8 @@ impl ApolloLinkShapesModule {
             (LinkShapeMode::Full, LinkShapeRep::ConvexHull) => &self.full_convex_hulls,
             (LinkShapeMode::Full, LinkShapeRep::OBB) => &self.full_obbs,
             (LinkShapeMode::Full, LinkShapeRep::BoundingSphere) => &self.full_bounding_spheres,
+            (LinkShapeMode::Full, LinkShapeRep::MultiSphere) => &self.full_multi_spheres,
+            (LinkShapeMode::Full, LinkShapeRep::Capsule) => &self.full_capsules,
             (LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull) => {
                 &self.decomposition_convex_hulls
             }
@@ -263,6 +351,10 @@ impl ApolloLinkShapesModule {
             (LinkShapeMode::Decomposition, LinkShapeRep::BoundingSphere) => {
                 &self.decomposition_bounding_spheres
             }
+            (LinkShapeMode::Decomposition, LinkShapeRep::MultiSphere) => {
+                &self.decomposition_multi_spheres
+            }
+            (LinkShapeMode::Decomposition, LinkShapeRep::Capsule) => &self.decomposition_capsules,
         }
     }
 
@@ -324,10 +416,32 @@ pub enum LinkShapeMode {
     Decomposition,
 }
 
-/// The `LinkShapeRep` enum represents the representation of link shapes (convex hull, OBB, or bounding sphere).
+/// The `LinkShapeRep` enum represents the representation of link shapes (convex hull, OBB, bounding sphere, multi-sphere, or capsule).
+///
+/// `MultiSphere` approximates each shape with a small set of spheres fit to its convex hull, and
+/// `Capsule` with a near minimum-volume enclosing capsule.  Both are fit by the preprocessor (see
+/// `ApolloLinkShapesApproximationsModule`), and their shape indices match `ConvexHull`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum LinkShapeRep {
     ConvexHull,
     OBB,
     BoundingSphere,
+    MultiSphere,
+    Capsule,
+}
+
+/// Builds a sphere set (a compound of balls in the link frame) from sphere descriptors.
+fn multi_sphere_from_descriptors(descriptors: &Vec<BoundingSphereDescriptor>) -> OffsetShape {
+    let parts = descriptors.iter().map(|x| {
+        (Isometry::translation(x.offset_xyz[0], x.offset_xyz[1], x.offset_xyz[2]), SharedShape::ball(x.radius))
+    }).collect();
+
+    OffsetShape::new(Compound::new(parts), None)
+}
+
+/// Builds a y-aligned capsule with an offset from a capsule descriptor.
+fn capsule_from_descriptor(descriptor: &CapsuleDescriptor) -> OffsetShape {
+    let offset = ISE3q::new(I3::from_slices_euler_angles(&descriptor.offset_xyz, &descriptor.offset_rpy));
+
+    OffsetShape::new(Capsule::new_y(descriptor.half_height, descriptor.radius), Some(offset))
 }
@@ -0,0 +1,135 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_padding_module::ApolloLinkShapesPaddingModule;
+use crate::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode};
+
+/// The `ApolloLinkShapesPaddingNalgebraModule` struct holds link paddings along with padding
+/// matrices in shape indices, ready to be passed into proximity queries.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApolloLinkShapesPaddingNalgebraModule {
+    pub link_paddings: Vec<f64>,
+    pub link_pair_paddings: DMatrix<Option<f64>>,
+    pub full_shape_idx_to_link_idx: Vec<usize>,
+    pub decomposition_shape_idx_to_link_idx: Vec<usize>,
+    pub full_paddings: DMatrix<f64>,
+    pub decomposition_paddings: DMatrix<f64>
+}
+impl ApolloLinkShapesPaddingNalgebraModule {
+    /// Creates a new `ApolloLinkShapesPaddingNalgebraModule` from an `ApolloLinkShapesPaddingModule`.
+    ///
+    /// # Arguments
+    /// - `link_shapes_padding_module`: A reference to the `ApolloLinkShapesPaddingModule` containing the paddings.
+    /// - `link_shapes_module`: A reference to the link shapes module, used to map shapes to links.
+    ///
+    /// # Returns
+    /// An instance of `ApolloLinkShapesPaddingNalgebraModule`.
+    pub fn from_link_shapes_padding_module(link_shapes_padding_module: &ApolloLinkShapesPaddingModule, link_shapes_module: &ApolloLinkShapesModule) -> Self {
+        let num_links = link_shapes_padding_module.link_paddings.len();
+        let mut link_pair_paddings = DMatrix::from_element(num_links, num_links, None);
+        link_shapes_padding_module.link_pair_paddings.iter().for_each(|(a, b, padding)| {
+            link_pair_paddings[(*a, *b)] = Some(*padding);
+            link_pair_paddings[(*b, *a)] = Some(*padding);
+        });
+
+        let full_shape_idx_to_link_idx = link_shapes_module.full_shape_idx_to_link_idx().clone();
+        let decomposition_shape_idx_to_link_idx = link_shapes_module.decomposition_shape_idx_to_link_idx_and_link_sub_idx().iter().map(|x| x.0).collect();
+
+        let mut out = Self {
+            link_paddings: link_shapes_padding_module.link_paddings.clone(),
+            link_pair_paddings,
+            full_shape_idx_to_link_idx,
+            decomposition_shape_idx_to_link_idx,
+            full_paddings: DMatrix::zeros(0, 0),
+            decomposition_paddings: DMatrix::zeros(0, 0),
+        };
+        out.recompute_paddings();
+
+        out
+    }
+
+    /// Sets the padding of a single link.
+    pub fn set_link_padding(&mut self, link_idx: usize, padding: f64) {
+        self.link_paddings[link_idx] = padding;
+        self.recompute_paddings();
+    }
+
+    /// Overrides the padding of a pair of links, replacing the sum of their individual paddings.
+    /// Passing `None` removes the override.
+    pub fn set_link_pair_padding(&mut self, link_idx_a: usize, link_idx_b: usize, padding: Option<f64>) {
+        self.link_pair_paddings[(link_idx_a, link_idx_b)] = padding;
+        self.link_pair_paddings[(link_idx_b, link_idx_a)] = padding;
+        self.recompute_paddings();
+    }
+
+    /// Returns the padding between two links of the same chain.
+    #[inline(always)]
+    pub fn get_link_pair_padding(&self, link_idx_a: usize, link_idx_b: usize) -> f64 {
+        match self.link_pair_paddings[(link_idx_a, link_idx_b)] {
+            None => { self.link_paddings[link_idx_a] + self.link_paddings[link_idx_b] }
+            Some(padding) => { padding }
+        }
+    }
+
+    /// Retrieves the matrix of paddings between pairs of shapes of this chain.
+    ///
+    /// # Arguments
+    /// - `link_shape_mode`: The mode of the link shapes (full or decomposition).
+    ///
+    /// # Returns
+    /// A reference to the `DMatrix<f64>` of paddings in shape indices.
+    #[inline(always)]
+    pub fn get_paddings(&self, link_shape_mode: LinkShapeMode) -> &DMatrix<f64> {
+        match &link_shape_mode {
+            LinkShapeMode::Full => { &self.full_paddings }
+            LinkShapeMode::Decomposition => { &self.decomposition_paddings }
+        }
+    }
+
+    /// Returns the link index of each shape, in shape indices.
+    #[inline(always)]
+    pub fn get_shape_idx_to_link_idx(&self, link_shape_mode: LinkShapeMode) -> &Vec<usize> {
+        match &link_shape_mode {
+            LinkShapeMode::Full => { &self.full_shape_idx_to_link_idx }
+            LinkShapeMode::Decomposition => { &self.decomposition_shape_idx_to_link_idx }
+        }
+    }
+
+    /// Returns the padding of the link of each shape, in shape indices.
+    pub fn get_shape_paddings(&self, link_shape_mode: LinkShapeMode) -> Vec<f64> {
+        self.get_shape_idx_to_link_idx(link_shape_mode).iter().map(|x| self.link_paddings[*x]).collect()
+    }
+
+    /// Returns the matrix of paddings between the shapes of this chain and the shapes of another
+    /// chain.  Pair overrides only apply within a chain, so each entry is the sum of the two link paddings.
+    pub fn get_double_chain_paddings(&self, self_link_shape_mode: LinkShapeMode, other: &ApolloLinkShapesPaddingNalgebraModule, other_link_shape_mode: LinkShapeMode) -> DMatrix<f64> {
+        let a = self.get_shape_paddings(self_link_shape_mode);
+        let b = other.get_shape_paddings(other_link_shape_mode);
+
+        DMatrix::from_fn(a.len(), b.len(), |i, j| a[i] + b[j])
+    }
+
+    /// Returns the matrix of paddings between the shapes of this chain and `num_obstacles` obstacles
+    /// without padding of their own.
+    pub fn get_obstacle_paddings(&self, link_shape_mode: LinkShapeMode, num_obstacles: usize) -> DMatrix<f64> {
+        let a = self.get_shape_paddings(link_shape_mode);
+
+        DMatrix::from_fn(a.len(), num_obstacles, |i, _| a[i])
+    }
+
+    /// Returns `true` if any link or pair of links has a nonzero padding.
+    pub fn has_padding(&self) -> bool {
+        self.link_paddings.iter().any(|x| *x != 0.0) || self.link_pair_paddings.iter().any(|x| match x { None => false, Some(x) => *x != 0.0 })
+    }
+
+    fn recompute_paddings(&mut self) {
+        let f = |shape_idx_to_link_idx: &Vec<usize>| {
+            let n = shape_idx_to_link_idx.len();
+            DMatrix::from_fn(n, n, |i, j| self.get_link_pair_padding(shape_idx_to_link_idx[i], shape_idx_to_link_idx[j]))
+        };
+
+        let full_paddings = f(&self.full_shape_idx_to_link_idx);
+        let decomposition_paddings = f(&self.decomposition_shape_idx_to_link_idx);
+        self.full_paddings = full_paddings;
+        self.decomposition_paddings = decomposition_paddings;
+    }
+}
@@ -10,9 +10,13 @@ pub struct ApolloLinkShapesSimpleSkipsNalgebraModule {
     pub full_convex_hulls_simple_skips: DMatrix<bool>,
     pub full_obbs_simple_skips: DMatrix<bool>,
     pub full_bounding_spheres_simple_skips: DMatrix<bool>,
+    pub full_multi_spheres_simple_skips: DMatrix<bool>,
+    pub full_capsules_simple_skips: DMatrix<bool>,
     pub decomposition_convex_hulls_simple_skips: DMatrix<bool>,
     pub decomposition_obbs_simple_skips: DMatrix<bool>,
-    pub decomposition_bounding_spheres_simple_skips: DMatrix<bool>
+    pub decomposition_bounding_spheres_simple_skips: DMatrix<bool>,
+    pub decomposition_multi_spheres_simple_skips: DMatrix<bool>,
+    pub decomposition_capsules_simple_skips: DMatrix<bool>
 }
 impl ApolloLinkShapesSimpleSkipsNalgebraModule {
     /// Creates a new `ApolloLinkShapesSimpleSkipsNalgebraModule` from an `ApolloLinkShapesSimpleSkipsModule`.
@@ -27,9 +31,13 @@ impl ApolloLinkShapesSimpleSkipsNalgebraModule {
             full_convex_hulls_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.full_convex_hulls_simple_skips),
             full_obbs_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.full_obbs_simple_skips),
             full_bounding_spheres_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.full_bounding_spheres_simple_skips),
+            full_multi_spheres_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.full_multi_spheres_simple_skips),
+            full_capsules_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.full_capsules_simple_skips),
             decomposition_convex_hulls_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.decomposition_convex_hulls_simple_skips),
             decomposition_obbs_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.decomposition_obbs_simple_skips),
             decomposition_bounding_spheres_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.decomposition_bounding_spheres_simple_skips),
+            decomposition_multi_spheres_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.decomposition_multi_spheres_simple_skips),
+            decomposition_capsules_simple_skips: dmatrix_from_2dvec(&apollo_link_shapes_simple_skips_module.decomposition_capsules_simple_skips),
         }
     }
 
@@ -37,7 +45,7 @@ impl ApolloLinkShapesSimpleSkipsNalgebraModule {
     ///
     /// # Arguments
     /// - `link_shape_mode`: The mode of the link shapes (full or decomposition).
-    /// - `link_shape_rep`: The representation of the link shapes (convex hull, OBB, or bounding sphere).
+    /// - `link_shape_rep`: The representation of the link shapes (convex hull, OBB, bounding sphere, sphere set, or capsule).
     ///
     /// # Returns
     /// A reference to the corresponding `DMatrix<bool>` representing the simple skips.
@@ -49,6 +57,8 @@ impl ApolloLinkShapesSimpleSkipsNalgebraModule {
                     LinkShapeRep::ConvexHull => { &self.full_convex_hulls_simple_skips }
                     LinkShapeRep::OBB => { &self.full_obbs_simple_skips }
                     LinkShapeRep::BoundingSphere => { &self.full_bounding_spheres_simple_skips }
+                    LinkShapeRep::MultiSphere => { &self.full_multi_spheres_simple_skips }
+                    LinkShapeRep::Capsule => { &self.full_capsules_simple_skips }
                 }
             }
             LinkShapeMode::Decomposition => {
@@ -56,6 +66,8 @@ impl ApolloLinkShapesSimpleSkipsNalgebraModule {
                     LinkShapeRep::ConvexHull => { &self.decomposition_convex_hulls_simple_skips }
                     LinkShapeRep::OBB => { &self.decomposition_obbs_simple_skips }
                     LinkShapeRep::BoundingSphere => { &self.decomposition_bounding_spheres_simple_skips }
+                    LinkShapeRep::MultiSphere => { &self.decomposition_multi_spheres_simple_skips }
+                    LinkShapeRep::Capsule => { &self.decomposition_capsules_simple_skips }
                 }
             }
         }
@@ -10,9 +10,13 @@ pub struct ApolloLinkShapesSkipsNalgebraModule {
     pub full_convex_hulls_skips: DMatrix<bool>,
     pub full_obbs_skips: DMatrix<bool>,
     pub full_bounding_spheres_skips: DMatrix<bool>,
+    pub full_multi_spheres_skips: DMatrix<bool>,
+    pub full_capsules_skips: DMatrix<bool>,
     pub decomposition_convex_hulls_skips: DMatrix<bool>,
     pub decomposition_obbs_skips: DMatrix<bool>,
-    pub decomposition_bounding_spheres_skips: DMatrix<bool>
+    pub decomposition_bounding_spheres_skips: DMatrix<bool>,
+    pub decomposition_multi_spheres_skips: DMatrix<bool>,
+    pub decomposition_capsules_skips: DMatrix<bool>
 }
 impl ApolloLinkShapesSkipsNalgebraModule {
     /// Creates a new `ApolloLinkShapesSkipsNalgebraModule` from an `ApolloLinkShapesSkipsModule`.
@@ -27,9 +31,13 @@ impl ApolloLinkShapesSkipsNalgebraModule {
             full_convex_hulls_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.full_convex_hulls_skips),
             full_obbs_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.full_obbs_skips),
             full_bounding_spheres_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.full_bounding_spheres_skips),
+            full_multi_spheres_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.full_multi_spheres_skips),
+            full_capsules_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.full_capsules_skips),
             decomposition_convex_hulls_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.decomposition_convex_hulls_skips),
             decomposition_obbs_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.decomposition_obbs_skips),
             decomposition_bounding_spheres_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.decomposition_bounding_spheres_skips),
+            decomposition_multi_spheres_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.decomposition_multi_spheres_skips),
+            decomposition_capsules_skips: dmatrix_from_2dvec(&apollo_link_shapes_skips_module.decomposition_capsules_skips),
         }
     }
 
@@ -37,7 +45,7 @@ impl ApolloLinkShapesSkipsNalgebraModule {
     ///
     /// # Arguments
     /// - `link_shape_mode`: The mode of the link shapes (full or decomposition).
-    /// - `link_shape_rep`: The representation of the link shapes (convex hull, OBB, or bounding sphere).
+    /// - `link_shape_rep`: The representation of the link shapes (convex hull, OBB, bounding sphere, sphere set, or capsule).
     ///
     /// # Returns
     /// A reference to the corresponding `DMatrix<bool>` representing the skips.
@@ -49,6 +57,8 @@ impl ApolloLinkShapesSkipsNalgebraModule {
                     LinkShapeRep::ConvexHull => { &self.full_convex_hulls_skips }
                     LinkShapeRep::OBB => { &self.full_obbs_skips }
                     LinkShapeRep::BoundingSphere => { &self.full_bounding_spheres_skips }
+                    LinkShapeRep::MultiSphere => { &self.full_multi_spheres_skips }
+                    LinkShapeRep::Capsule => { &self.full_capsules_skips }
                 }
             }
             LinkShapeMode::Decomposition => {
@@ -56,6 +66,33 @@ impl ApolloLinkShapesSkipsNalgebraModule {
                     LinkShapeRep::ConvexHull => { &self.decomposition_convex_hulls_skips }
                     LinkShapeRep::OBB => { &self.decomposition_obbs_skips }
                     LinkShapeRep::BoundingSphere => { &self.decomposition_bounding_spheres_skips }
+                    LinkShapeRep::MultiSphere => { &self.decomposition_multi_spheres_skips }
+                    LinkShapeRep::Capsule => { &self.decomposition_capsules_skips }
+                }
+            }
+        }
+    }
+
+    /// Retrieves a mutable reference to the skips matrix based on the provided `LinkShapeMode` and `LinkShapeRep`.
+    #[inline(always)]
+    pub fn get_skips_mut(&mut self, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> &mut DMatrix<bool> {
+        match &link_shape_mode {
+            LinkShapeMode::Full => {
+                match &link_shape_rep {
+                    LinkShapeRep::ConvexHull => { &mut self.full_convex_hulls_skips }
+                    LinkShapeRep::OBB => { &mut self.full_obbs_skips }
+                    LinkShapeRep::BoundingSphere => { &mut self.full_bounding_spheres_skips }
+                    LinkShapeRep::MultiSphere => { &mut self.full_multi_spheres_skips }
+                    LinkShapeRep::Capsule => { &mut self.full_capsules_skips }
+                }
+            }
+            LinkShapeMode::Decomposition => {
+                match &link_shape_rep {
+                    LinkShapeRep::ConvexHull => { &mut self.decomposition_convex_hulls_skips }
+                    LinkShapeRep::OBB => { &mut self.decomposition_obbs_skips }
+                    LinkShapeRep::BoundingSphere => { &mut self.decomposition_bounding_spheres_skips }
+                    LinkShapeRep::MultiSphere => { &mut self.decomposition_multi_spheres_skips }
+                    LinkShapeRep::Capsule => { &mut self.decomposition_capsules_skips }
                 }
             }
         }
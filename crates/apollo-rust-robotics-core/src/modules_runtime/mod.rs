@@ -5,6 +5,10 @@ pub mod link_shapes_distance_statistics_nalgebra_module;
 pub mod link_shapes_simple_skips_nalgebra_module;
 pub mod link_shapes_skips_nalgebra_module;
 pub mod link_shapes_lie_alg_error_models_nalgebra_module;
+pub mod signed_distance_field_nalgebra_module;
 
 
 
+pub mod link_shapes_padding_nalgebra_module;
+pub mod allowed_collision_matrix;
+pub mod attached_objects_module;
@@ -0,0 +1,133 @@
+use apollo_rust_modules::robot_modules::signed_distance_field_module::ApolloSignedDistanceFieldModule;
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V3;
+use crate::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+
+/// The `ApolloSignedDistanceFieldNalgebraModule` struct holds a precomputed signed distance field
+/// of a static environment and answers distance and gradient queries with trilinear interpolation.
+#[derive(Clone, Debug)]
+pub struct ApolloSignedDistanceFieldNalgebraModule {
+    pub origin: V3,
+    pub voxel_size: f64,
+    pub dims: [usize; 3],
+    pub distances: Vec<f64>,
+    pub gradients: Vec<V3>
+}
+impl ApolloSignedDistanceFieldNalgebraModule {
+    /// Creates a new `ApolloSignedDistanceFieldNalgebraModule` from an `ApolloSignedDistanceFieldModule`.
+    ///
+    /// # Arguments
+    /// - `signed_distance_field_module`: A reference to the `ApolloSignedDistanceFieldModule` containing the voxelized field.
+    ///
+    /// # Returns
+    /// An instance of `ApolloSignedDistanceFieldNalgebraModule`.
+    pub fn from_signed_distance_field_module(signed_distance_field_module: &ApolloSignedDistanceFieldModule) -> Self {
+        let o = &signed_distance_field_module.origin;
+
+        Self {
+            origin: V3::new(o[0], o[1], o[2]),
+            voxel_size: signed_distance_field_module.voxel_size,
+            dims: signed_distance_field_module.dims,
+            distances: signed_distance_field_module.distances.clone(),
+            gradients: signed_distance_field_module.gradients.iter().map(|x| V3::new(x[0], x[1], x[2])).collect(),
+        }
+    }
+
+    /// Returns the signed distance and its gradient at a point in the world frame.
+    ///
+    /// Inside the grid, both are trilinearly interpolated.  Outside the grid, the point is clamped
+    /// to the grid and the distance from the point to the clamped point is added, which keeps the
+    /// result continuous and a reasonable estimate far from the environment.
+    ///
+    /// # Arguments
+    /// - `point`: The query point in the world frame.
+    ///
+    /// # Returns
+    /// A tuple containing the signed distance and its gradient with respect to `point`.
+    pub fn distance_and_gradient(&self, point: &V3) -> (f64, V3) {
+        let mut clamped = V3::zeros();
+        let mut idxs = [0usize; 3];
+        let mut ts = [0.0; 3];
+        let mut is_clamped = [false; 3];
+
+        for axis in 0..3 {
+            let max_coord = (self.dims[axis] - 1) as f64;
+            let coord = (point[axis] - self.origin[axis]) / self.voxel_size;
+            let c = coord.clamp(0.0, max_coord);
+            is_clamped[axis] = c != coord;
+            clamped[axis] = self.origin[axis] + c * self.voxel_size;
+
+            if self.dims[axis] < 2 {
+                idxs[axis] = 0;
+                ts[axis] = 0.0;
+            } else {
+                let i = (c.floor() as usize).min(self.dims[axis] - 2);
+                idxs[axis] = i;
+                ts[axis] = c - i as f64;
+            }
+        }
+
+        let mut distance = 0.0;
+        let mut gradient = V3::zeros();
+        for corner in 0..8 {
+            let mut weight = 1.0;
+            let mut ijk = [0usize; 3];
+            for axis in 0..3 {
+                let upper = (corner >> axis) & 1 == 1;
+                let t = ts[axis];
+                weight *= if upper { t } else { 1.0 - t };
+                ijk[axis] = if upper { (idxs[axis] + 1).min(self.dims[axis] - 1) } else { idxs[axis] };
+            }
+            if weight == 0.0 { continue; }
+
+            let idx = self.get_idx(ijk[0], ijk[1], ijk[2]);
+            distance += weight * self.distances[idx];
+            gradient += weight * self.gradients[idx];
+        }
+
+        let offset = point - clamped;
+        let offset_norm = offset.norm();
+        if offset_norm > 0.0 {
+            for axis in 0..3 { if is_clamped[axis] { gradient[axis] = 0.0; } }
+            distance += offset_norm;
+            gradient += offset / offset_norm;
+        }
+
+        (distance, gradient)
+    }
+
+    /// Returns the signed distance and gradient of a sphere, i.e., the field at its center minus its radius.
+    #[inline(always)]
+    pub fn sphere_distance_and_gradient(&self, center: &V3, radius: f64) -> (f64, V3) {
+        let (distance, gradient) = self.distance_and_gradient(center);
+        (distance - radius, gradient)
+    }
+
+    /// Returns the signed distance and gradient (with respect to the sphere center) of the world
+    /// bounding sphere of every shape of the robot.  With `LinkShapeRep::BoundingSphere`, these
+    /// are exactly the link shapes.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    ///
+    /// # Returns
+    /// A vector with one `(distance, gradient)` tuple per shape.
+    pub fn link_spheres_distances_and_gradients(&self, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> Vec<(f64, V3)> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+
+        shapes.iter().zip(poses.iter()).map(|(shape, pose)| {
+            let transform = shape.get_transform(pose);
+            let bounding_sphere = shape.shape().compute_bounding_sphere(&transform.as_ref().0);
+            self.sphere_distance_and_gradient(&bounding_sphere.center().coords, bounding_sphere.radius())
+        }).collect()
+    }
+
+    #[inline(always)]
+    fn get_idx(&self, i: usize, j: usize, k: usize) -> usize {
+        i + self.dims[0] * (j + self.dims[1] * k)
+    }
+}
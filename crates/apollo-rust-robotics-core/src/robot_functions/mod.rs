@@ -1,2 +1,3 @@
 pub mod robot_kinematics_functions;
-pub mod robot_proximity_functions;
\ No newline at end of file
+pub mod robot_proximity_functions;
+pub mod robot_ray_casting_functions;
\ No newline at end of file
@@ -1,11 +1,15 @@
-use apollo_rust_proximity_parry::double_group_queries::{DoubleGroupProximityQueryMode, DoubleGroupProximityQueryOutput, pairwise_group_query_contact, pairwise_group_query_distance, pairwise_group_query_intersection};
+use std::fmt::Debug;
+use apollo_rust_proximity_parry::double_group_queries::{DoubleGroupProximityQueryMode, DoubleGroupProximityQueryOutput, get_double_group_query_pairs, pairwise_group_query_contact, pairwise_group_query_contact_padded, pairwise_group_query_distance, pairwise_group_query_distance_padded, pairwise_group_query_intersection, pairwise_group_query_intersection_padded};
 use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
 use nalgebra::DMatrix;
-use parry3d_f64::query::Contact;
+use parry3d_f64::query::{Contact, distance, intersection_test};
 use apollo_rust_proximity_parry::proxima::proxima_core::{ProximaBudget, ProximaOutput, ProximaTrait};
 use apollo_rust_proximity_parry::{ProximityLossFunction};
 use apollo_rust_proximity_parry::bvh::{Bvh, BvhShape};
+use apollo_rust_proximity_parry::sweep_and_prune::SweepAndPrune;
+use apollo_rust_proximity_parry::offset_shape::OffsetShape;
 use crate::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+use crate::modules_runtime::attached_objects_module::ApolloAttachedObjectsModule;
 
 pub struct RobotProximityFunctions;
 impl RobotProximityFunctions {
@@ -17,15 +21,19 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     ///
     /// # Returns
     /// A `DoubleGroupProximityQueryOutput<bool>` indicating if self-intersections were found.
-    pub fn self_intersect(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+    pub fn self_intersect(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
         let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        pairwise_group_query_intersection(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, ())
+        match paddings {
+            None => { pairwise_group_query_intersection(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_intersection_padded(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, paddings) }
+        }
     }
 
     /// Checks for self-intersections using a BVH.
@@ -37,18 +45,27 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.  The bounding volumes are loosened by the largest padding.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     ///
     /// # Returns
     /// A `DoubleGroupProximityQueryOutput<bool>` indicating if self-intersections were found.
-    pub fn self_intersect_bvh<B: BvhShape>(bvh: &mut Bvh<B>, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+    pub fn self_intersect_bvh<B: BvhShape>(bvh: &mut Bvh<B>, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
         let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        bvh.update(&shapes, &poses);
-        let pairs = bvh.intersection_filter(&bvh).iter().filter(|x| x.0 <= x.1).map(|x| x.clone()).collect();
-
-        pairwise_group_query_intersection(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, ())
+        bvh.refit(&shapes, &poses);
+
+        match paddings {
+            None => {
+                let pairs = bvh.self_intersection_filter();
+                pairwise_group_query_intersection(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, ())
+            }
+            Some(paddings) => {
+                let pairs = bvh.self_distance_filter(max_padding(paddings));
+                pairwise_group_query_intersection_padded(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, paddings)
+            }
+        }
     }
 
     /// Computes the self-distance between links in the chain.
@@ -59,15 +76,19 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     ///
     /// # Returns
     /// A `DoubleGroupProximityQueryOutput<f64>` representing the distance.
-    pub fn self_distance(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
+    pub fn self_distance(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
         let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        pairwise_group_query_distance(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, ())
+        match paddings {
+            None => { pairwise_group_query_distance(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_distance_padded(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, paddings) }
+        }
     }
 
     /// Computes contact points between links in the chain.
@@ -78,16 +99,20 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     /// - `margin`: A margin value for the proximity calculation.
     ///
     /// # Returns
     /// A `DoubleGroupProximityQueryOutput<Option<Contact>>` representing the contact points.
-    pub fn self_contact(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+    pub fn self_contact(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
         let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        pairwise_group_query_contact(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, margin)
+        match paddings {
+            None => { pairwise_group_query_contact(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, margin) }
+            Some(paddings) => { pairwise_group_query_contact_padded(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, early_stop, (paddings, margin)) }
+        }
     }
 
     /// Computes contact points using a BVH.
@@ -99,19 +124,28 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.  The bounding volumes are loosened by the largest padding.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     /// - `margin`: A margin value for the proximity calculation.
     ///
     /// # Returns
     /// A `DoubleGroupProximityQueryOutput<Option<Contact>>` representing the contact points.
-    pub fn self_contact_bvh<B: BvhShape>(bvh: &mut Bvh<B>, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+    pub fn self_contact_bvh<B: BvhShape>(bvh: &mut Bvh<B>, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
         let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        bvh.update(&shapes, &poses);
-        let pairs = bvh.distance_filter(&bvh, margin).iter().filter(|x| x.0 <= x.1).map(|x| x.clone()).collect();
-
-        pairwise_group_query_contact(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, margin)
+        bvh.refit(&shapes, &poses);
+
+        match paddings {
+            None => {
+                let pairs = bvh.self_distance_filter(margin);
+                pairwise_group_query_contact(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, margin)
+            }
+            Some(paddings) => {
+                let pairs = bvh.self_distance_filter(margin + max_padding(paddings));
+                pairwise_group_query_contact_padded(shapes, &poses, shapes, &poses, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, (paddings, margin))
+            }
+        }
     }
 
     /// Checks for double-chain intersections between two chains.
@@ -126,6 +160,7 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
     /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     /// - `double_group_proximity_query_mode`: The query mode for proximity calculations.
     ///
@@ -140,6 +175,7 @@ impl RobotProximityFunctions {
                                   link_shape_mode_b: LinkShapeMode,
                                   link_shape_rep_b: LinkShapeRep,
                                   skips: Option<&DMatrix<bool>>,
+                                  paddings: Option<&DMatrix<f64>>,
                                   early_stop: bool,
                                   double_group_proximity_query_mode: &DoubleGroupProximityQueryMode) -> DoubleGroupProximityQueryOutput<bool> {
         let shapes_a = link_shapes_module_a.get_shapes(link_shape_mode_a, link_shape_rep_a);
@@ -148,23 +184,31 @@ impl RobotProximityFunctions {
         let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
         let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
 
-        pairwise_group_query_intersection(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, ())
+        match paddings {
+            None => { pairwise_group_query_intersection(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_intersection_padded(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, paddings) }
+        }
     }
 
-    /// Computes contact points using a BVH.
+    /// Checks for double-chain intersections using a BVH over each chain.
     ///
     /// # Arguments
-    /// - `bvh`: A mutable reference to a BVH structure.
-    /// - `link_shapes_module`: A reference to the link shapes module.
-    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
-    /// - `link_shape_mode`: The shape mode of the links.
-    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `self_bvh`: A mutable reference to a BVH structure for the first chain.
+    /// - `other_bvh`: A mutable reference to a BVH structure for the second chain.
+    /// - `link_shapes_module_a`: A reference to the link shapes module of the first chain.
+    /// - `link_poses_a`: A reference to a vector of `ISE3q` representing the poses of each link in the first chain.
+    /// - `link_shape_mode_a`: The shape mode of the links in the first chain.
+    /// - `link_shape_rep_a`: The representation mode of the links in the first chain.
+    /// - `link_shapes_module_b`: A reference to the link shapes module of the second chain.
+    /// - `link_poses_b`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
+    /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
+    /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.  The bounding volumes are loosened by the largest padding.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
-    /// - `margin`: A margin value for the proximity calculation.
     ///
     /// # Returns
-    /// A `DoubleGroupProximityQueryOutput<Option<Contact>>` representing the contact points.
+    /// A `DoubleGroupProximityQueryOutput<bool>` indicating if intersections were found.
     pub fn double_chain_intersect_bvh<B: BvhShape>(self_bvh: &mut Bvh<B>,
                                                    other_bvh: &mut Bvh<B>,
                                                    link_shapes_module_a: &ApolloLinkShapesModule,
@@ -176,6 +220,7 @@ impl RobotProximityFunctions {
                                                    link_shape_mode_b: LinkShapeMode,
                                                    link_shape_rep_b: LinkShapeRep,
                                                    skips: Option<&DMatrix<bool>>,
+                                                   paddings: Option<&DMatrix<f64>>,
                                                    early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
         let shapes_a = link_shapes_module_a.get_shapes(link_shape_mode_a, link_shape_rep_a);
         let poses_a = link_shapes_module_a.link_poses_to_shape_poses(link_poses_a, link_shape_mode_a);
@@ -183,11 +228,19 @@ impl RobotProximityFunctions {
         let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
         let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
 
-        self_bvh.update(&shapes_a, &poses_a);
-        other_bvh.update(&shapes_b, &poses_b);
-        let pairs = self_bvh.intersection_filter(&other_bvh);
-
-        pairwise_group_query_intersection(shapes_a, &poses_a, shapes_b, &poses_b, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, ())
+        self_bvh.refit(&shapes_a, &poses_a);
+        other_bvh.refit(&shapes_b, &poses_b);
+
+        match paddings {
+            None => {
+                let pairs = self_bvh.intersection_filter(&other_bvh);
+                pairwise_group_query_intersection(shapes_a, &poses_a, shapes_b, &poses_b, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, ())
+            }
+            Some(paddings) => {
+                let pairs = self_bvh.distance_filter(&other_bvh, max_padding(paddings));
+                pairwise_group_query_intersection_padded(shapes_a, &poses_a, shapes_b, &poses_b, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, paddings)
+            }
+        }
     }
 
     /// Computes the distance between two robot chains.
@@ -202,6 +255,7 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
     /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     /// - `double_group_proximity_query_mode`: The query mode for proximity calculations.
     ///
@@ -216,6 +270,7 @@ impl RobotProximityFunctions {
                                   link_shape_mode_b: LinkShapeMode,
                                   link_shape_rep_b: LinkShapeRep,
                                   skips: Option<&DMatrix<bool>>,
+                                  paddings: Option<&DMatrix<f64>>,
                                   early_stop: bool,
                                  double_group_proximity_query_mode: &DoubleGroupProximityQueryMode) -> DoubleGroupProximityQueryOutput<f64> {
         let shapes_a = link_shapes_module_a.get_shapes(link_shape_mode_a, link_shape_rep_a);
@@ -224,7 +279,10 @@ impl RobotProximityFunctions {
         let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
         let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
 
-        pairwise_group_query_distance(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, ())
+        match paddings {
+            None => { pairwise_group_query_distance(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_distance_padded(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, paddings) }
+        }
     }
 
     /// Computes the contact points between two robot chains.
@@ -239,6 +297,7 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
     /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     /// - `margin`: A margin value for the proximity calculation.
     /// - `double_group_proximity_query_mode`: The query mode for proximity calculations.
@@ -254,6 +313,7 @@ impl RobotProximityFunctions {
                                 link_shape_mode_b: LinkShapeMode,
                                 link_shape_rep_b: LinkShapeRep,
                                 skips: Option<&DMatrix<bool>>,
+                                paddings: Option<&DMatrix<f64>>,
                                 early_stop: bool,
                                 margin: f64,
                                 double_group_proximity_query_mode: &DoubleGroupProximityQueryMode) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
@@ -263,7 +323,84 @@ impl RobotProximityFunctions {
         let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
         let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
 
-        pairwise_group_query_contact(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, margin)
+        match paddings {
+            None => { pairwise_group_query_contact(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, margin) }
+            Some(paddings) => { pairwise_group_query_contact_padded(shapes_a, &poses_a, shapes_b, &poses_b, double_group_proximity_query_mode, skips, early_stop, (paddings, margin)) }
+        }
+    }
+
+    /// Checks for intersections between the robot's links and a set of obstacles given directly
+    /// as shapes (e.g., obstacles detected at runtime), without an environment chain.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `obstacles`: The obstacle shapes.
+    /// - `obstacle_poses`: The world pose of each obstacle.
+    /// - `paddings`: An optional matrix of paddings between each shape and each obstacle.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<bool>` with `(shape_idx, obstacle_idx)` pairs.
+    pub fn obstacles_intersect(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, paddings: Option<&DMatrix<f64>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+
+        match paddings {
+            None => { pairwise_group_query_intersection(shapes, &poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_intersection_padded(shapes, &poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, paddings) }
+        }
+    }
+
+    /// Computes the distances between the robot's links and a set of obstacles given directly as shapes.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `obstacles`: The obstacle shapes.
+    /// - `obstacle_poses`: The world pose of each obstacle.
+    /// - `paddings`: An optional matrix of paddings between each shape and each obstacle.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<f64>` with `(shape_idx, obstacle_idx)` pairs.
+    pub fn obstacles_distance(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, paddings: Option<&DMatrix<f64>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<f64> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+
+        match paddings {
+            None => { pairwise_group_query_distance(shapes, &poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_distance_padded(shapes, &poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, paddings) }
+        }
+    }
+
+    /// Computes the contacts between the robot's links and a set of obstacles given directly as shapes.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `obstacles`: The obstacle shapes.
+    /// - `obstacle_poses`: The world pose of each obstacle.
+    /// - `paddings`: An optional matrix of paddings between each shape and each obstacle.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `margin`: A margin value for the proximity calculation.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<Option<Contact>>` with `(shape_idx, obstacle_idx)` pairs.
+    pub fn obstacles_contact(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, paddings: Option<&DMatrix<f64>>, early_stop: bool, margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+
+        match paddings {
+            None => { pairwise_group_query_contact(shapes, &poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, margin) }
+            Some(paddings) => { pairwise_group_query_contact_padded(shapes, &poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, (paddings, margin)) }
+        }
     }
 
     /// Computes the contact points between two robot chains using BVHs.
@@ -280,6 +417,7 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
     /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.  The bounding volumes are loosened by the largest padding.
     /// - `early_stop`: A boolean flag to enable early stopping during checks.
     /// - `margin`: A margin value for the proximity calculation.
     ///
@@ -296,6 +434,7 @@ impl RobotProximityFunctions {
                                                  link_shape_mode_b: LinkShapeMode,
                                                  link_shape_rep_b: LinkShapeRep,
                                                  skips: Option<&DMatrix<bool>>,
+                                                 paddings: Option<&DMatrix<f64>>,
                                                  early_stop: bool,
                                                  margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
         let shapes_a = link_shapes_module_a.get_shapes(link_shape_mode_a, link_shape_rep_a);
@@ -304,12 +443,222 @@ impl RobotProximityFunctions {
         let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
         let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
 
-        self_bvh.update(&shapes_a, &poses_a);
-        other_bvh.update(&shapes_b, &poses_b);
-        let pairs = self_bvh.distance_filter(&other_bvh, margin);
+        self_bvh.refit(&shapes_a, &poses_a);
+        other_bvh.refit(&shapes_b, &poses_b);
+
+        match paddings {
+            None => {
+                let pairs = self_bvh.distance_filter(&other_bvh, margin);
+                pairwise_group_query_contact(shapes_a, &poses_a, shapes_b, &poses_b, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, margin)
+            }
+            Some(paddings) => {
+                let pairs = self_bvh.distance_filter(&other_bvh, margin + max_padding(paddings));
+                pairwise_group_query_contact_padded(shapes_a, &poses_a, shapes_b, &poses_b, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, (paddings, margin))
+            }
+        }
+    }
+
+    /// Checks for double-chain intersections using a persistent sweep-and-prune broadphase.
+    ///
+    /// # Arguments
+    /// - `sweep_and_prune`: A mutable reference to a sweep-and-prune structure built over the two chains.  Its margin should be at least the largest padding.
+    /// - `link_shapes_module_a`: A reference to the link shapes module of the first chain.
+    /// - `link_poses_a`: A reference to a vector of `ISE3q` representing the poses of each link in the first chain.
+    /// - `link_shape_mode_a`: The shape mode of the links in the first chain.
+    /// - `link_shape_rep_a`: The representation mode of the links in the first chain.
+    /// - `link_shapes_module_b`: A reference to the link shapes module of the second chain.
+    /// - `link_poses_b`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
+    /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
+    /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
+    /// - `update_b`: Whether the second chain moved since the last call.  Pass `false` for static environments.
+    /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<bool>` indicating if intersections were found.
+    pub fn double_chain_intersect_sweep_and_prune(sweep_and_prune: &mut SweepAndPrune,
+                                                  link_shapes_module_a: &ApolloLinkShapesModule,
+                                                  link_poses_a: &Vec<ISE3q>,
+                                                  link_shape_mode_a: LinkShapeMode,
+                                                  link_shape_rep_a: LinkShapeRep,
+                                                  link_shapes_module_b: &ApolloLinkShapesModule,
+                                                  link_poses_b: &Vec<ISE3q>,
+                                                  link_shape_mode_b: LinkShapeMode,
+                                                  link_shape_rep_b: LinkShapeRep,
+                                                  update_b: bool,
+                                                  skips: Option<&DMatrix<bool>>,
+                                                  paddings: Option<&DMatrix<f64>>,
+                                                  early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let shapes_a = link_shapes_module_a.get_shapes(link_shape_mode_a, link_shape_rep_a);
+        let poses_a = link_shapes_module_a.link_poses_to_shape_poses(link_poses_a, link_shape_mode_a);
+
+        let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
+        let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
 
-        pairwise_group_query_contact(shapes_a, &poses_a, shapes_b, &poses_b, &DoubleGroupProximityQueryMode::SubsetOfPairs(pairs), skips, early_stop, margin)
+        if update_b { sweep_and_prune.update(shapes_a, &poses_a, shapes_b, &poses_b); } else { sweep_and_prune.update_group_a(shapes_a, &poses_a); }
+        let query_mode = sweep_and_prune.get_query_mode();
 
+        match paddings {
+            None => { pairwise_group_query_intersection(shapes_a, &poses_a, shapes_b, &poses_b, &query_mode, skips, early_stop, ()) }
+            Some(paddings) => { pairwise_group_query_intersection_padded(shapes_a, &poses_a, shapes_b, &poses_b, &query_mode, skips, early_stop, paddings) }
+        }
+    }
+
+    /// Computes the contact points between two robot chains using a persistent sweep-and-prune broadphase.
+    ///
+    /// # Arguments
+    /// - `sweep_and_prune`: A mutable reference to a sweep-and-prune structure built over the two chains.  Its margin should be at least `margin` plus the largest padding.
+    /// - `link_shapes_module_a`: A reference to the link shapes module of the first chain.
+    /// - `link_poses_a`: A reference to a vector of `ISE3q` representing the poses of each link in the first chain.
+    /// - `link_shape_mode_a`: The shape mode of the links in the first chain.
+    /// - `link_shape_rep_a`: The representation mode of the links in the first chain.
+    /// - `link_shapes_module_b`: A reference to the link shapes module of the second chain.
+    /// - `link_poses_b`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
+    /// - `link_shape_mode_b`: The shape mode of the links in the second chain.
+    /// - `link_shape_rep_b`: The representation mode of the links in the second chain.
+    /// - `update_b`: Whether the second chain moved since the last call.  Pass `false` for static environments.
+    /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `margin`: A margin value for the proximity calculation.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<Option<Contact>>` representing the contact points.
+    pub fn double_chain_contact_sweep_and_prune(sweep_and_prune: &mut SweepAndPrune,
+                                                link_shapes_module_a: &ApolloLinkShapesModule,
+                                                link_poses_a: &Vec<ISE3q>,
+                                                link_shape_mode_a: LinkShapeMode,
+                                                link_shape_rep_a: LinkShapeRep,
+                                                link_shapes_module_b: &ApolloLinkShapesModule,
+                                                link_poses_b: &Vec<ISE3q>,
+                                                link_shape_mode_b: LinkShapeMode,
+                                                link_shape_rep_b: LinkShapeRep,
+                                                update_b: bool,
+                                                skips: Option<&DMatrix<bool>>,
+                                                paddings: Option<&DMatrix<f64>>,
+                                                early_stop: bool,
+                                                margin: f64) -> DoubleGroupProximityQueryOutput<Option<Contact>> {
+        let shapes_a = link_shapes_module_a.get_shapes(link_shape_mode_a, link_shape_rep_a);
+        let poses_a = link_shapes_module_a.link_poses_to_shape_poses(link_poses_a, link_shape_mode_a);
+
+        let shapes_b = link_shapes_module_b.get_shapes(link_shape_mode_b, link_shape_rep_b);
+        let poses_b = link_shapes_module_b.link_poses_to_shape_poses(link_poses_b, link_shape_mode_b);
+
+        if update_b { sweep_and_prune.update(shapes_a, &poses_a, shapes_b, &poses_b); } else { sweep_and_prune.update_group_a(shapes_a, &poses_a); }
+        let query_mode = sweep_and_prune.get_query_mode();
+
+        match paddings {
+            None => { pairwise_group_query_contact(shapes_a, &poses_a, shapes_b, &poses_b, &query_mode, skips, early_stop, margin) }
+            Some(paddings) => { pairwise_group_query_contact_padded(shapes_a, &poses_a, shapes_b, &poses_b, &query_mode, skips, early_stop, (paddings, margin)) }
+        }
+    }
+
+    /// Checks for self-intersections with a cascade of link shape representations.  Each pair of
+    /// links is tested with bounding spheres, then OBBs, then convex hulls, and finally convex
+    /// decompositions, and only moves on to the next level while the shapes are still in contact.
+    /// The result matches a query on convex decompositions, but most pairs are resolved cheaply.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `full_skips`: An optional matrix of boolean values to skip certain pairs, in full shape indices.
+    /// - `decomposition_skips`: An optional matrix of boolean values to skip certain pairs of convex subcomponents, in decomposition shape indices.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes, in full shape indices.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<bool>` with the intersecting pairs in full shape indices.
+    pub fn self_intersect_cascade(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, full_skips: Option<&DMatrix<bool>>, decomposition_skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        Self::intersect_cascade(link_shapes_module, link_poses, link_shapes_module, link_poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, full_skips, decomposition_skips, paddings, early_stop)
+    }
+
+    /// Checks for intersections between two chains with a cascade of link shape representations.
+    /// See `self_intersect_cascade`.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module_a`: A reference to the link shapes module of the first chain.
+    /// - `link_poses_a`: A reference to a vector of `ISE3q` representing the poses of each link in the first chain.
+    /// - `link_shapes_module_b`: A reference to the link shapes module of the second chain.
+    /// - `link_poses_b`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
+    /// - `full_skips`: An optional matrix of boolean values to skip certain pairs, in full shape indices.
+    /// - `decomposition_skips`: An optional matrix of boolean values to skip certain pairs of convex subcomponents, in decomposition shape indices.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes, in full shape indices.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<bool>` with the intersecting pairs in full shape indices.
+    pub fn double_chain_intersect_cascade(link_shapes_module_a: &ApolloLinkShapesModule,
+                                          link_poses_a: &Vec<ISE3q>,
+                                          link_shapes_module_b: &ApolloLinkShapesModule,
+                                          link_poses_b: &Vec<ISE3q>,
+                                          full_skips: Option<&DMatrix<bool>>,
+                                          decomposition_skips: Option<&DMatrix<bool>>,
+                                          paddings: Option<&DMatrix<f64>>,
+                                          early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        Self::intersect_cascade(link_shapes_module_a, link_poses_a, link_shapes_module_b, link_poses_b, &DoubleGroupProximityQueryMode::AllPossiblePairs, full_skips, decomposition_skips, paddings, early_stop)
+    }
+
+    fn intersect_cascade(link_shapes_module_a: &ApolloLinkShapesModule,
+                         link_poses_a: &Vec<ISE3q>,
+                         link_shapes_module_b: &ApolloLinkShapesModule,
+                         link_poses_b: &Vec<ISE3q>,
+                         query_mode: &DoubleGroupProximityQueryMode,
+                         full_skips: Option<&DMatrix<bool>>,
+                         decomposition_skips: Option<&DMatrix<bool>>,
+                         paddings: Option<&DMatrix<f64>>,
+                         early_stop: bool) -> DoubleGroupProximityQueryOutput<bool> {
+        let full_shape_idx_to_link_idx_a = link_shapes_module_a.full_shape_idx_to_link_idx();
+        let full_shape_idx_to_link_idx_b = link_shapes_module_b.full_shape_idx_to_link_idx();
+
+        let mut num_ground_truth_checks = 0;
+        let mut test = |sa: &OffsetShape, pa: &ISE3q, sb: &OffsetShape, pb: &ISE3q, padding: f64| -> bool {
+            num_ground_truth_checks += 1;
+            let ppa = sa.get_transform(pa);
+            let ppb = sb.get_transform(pb);
+            if padding == 0.0 { intersection_test(&ppa.0, &**sa.shape(), &ppb.0, &**sb.shape()).expect("error") }
+            else { distance(&ppa.0, &**sa.shape(), &ppb.0, &**sb.shape()).expect("error") <= padding }
+        };
+
+        let pairs = get_double_group_query_pairs(full_shape_idx_to_link_idx_a.len(), full_shape_idx_to_link_idx_b.len(), query_mode, full_skips);
+        let mut outputs = vec![];
+        let mut shape_idxs = vec![];
+        'l: for (i, j) in pairs {
+            let link_a = full_shape_idx_to_link_idx_a[i];
+            let link_b = full_shape_idx_to_link_idx_b[j];
+            let pa = &link_poses_a[link_a];
+            let pb = &link_poses_b[link_b];
+            let padding = match paddings {
+                None => { 0.0 }
+                Some(paddings) => { paddings[(i, j)] }
+            };
+
+            if !test(&link_shapes_module_a.full_bounding_spheres()[i], pa, &link_shapes_module_b.full_bounding_spheres()[j], pb, padding) { continue 'l; }
+            if !test(&link_shapes_module_a.full_obbs()[i], pa, &link_shapes_module_b.full_obbs()[j], pb, padding) { continue 'l; }
+            if !test(&link_shapes_module_a.full_convex_hulls()[i], pa, &link_shapes_module_b.full_convex_hulls()[j], pb, padding) { continue 'l; }
+
+            let decomposition_idxs_a = &link_shapes_module_a.link_idx_to_decomposition_shape_idxs()[link_a];
+            let decomposition_idxs_b = &link_shapes_module_b.link_idx_to_decomposition_shape_idxs()[link_b];
+            // without a decomposition, the convex hull is the finest available representation.
+            let mut intersect = decomposition_idxs_a.is_empty() || decomposition_idxs_b.is_empty();
+            'd: for di in decomposition_idxs_a {
+                for dj in decomposition_idxs_b {
+                    if let Some(decomposition_skips) = decomposition_skips { if decomposition_skips[(*di, *dj)] { continue; } }
+                    if test(&link_shapes_module_a.decomposition_convex_hulls()[*di], pa, &link_shapes_module_b.decomposition_convex_hulls()[*dj], pb, padding) {
+                        intersect = true;
+                        break 'd;
+                    }
+                }
+            }
+
+            if intersect {
+                outputs.push(true);
+                shape_idxs.push((i, j));
+                if early_stop { break 'l; }
+            }
+        }
+
+        DoubleGroupProximityQueryOutput::new(outputs, shape_idxs, num_ground_truth_checks)
     }
 
     /// Checks for self-intersections using Proxima.
@@ -321,15 +670,16 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `frozen`: A boolean flag indicating whether the Proxima process should be frozen.
     ///
     /// # Returns
     /// A `ProximaOutput<bool>` representing if intersections were found.
-    pub fn self_intersect_proxima<P: ProximaTrait>(proxima: &mut P, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, frozen: bool) -> ProximaOutput<bool> {
+    pub fn self_intersect_proxima<P: ProximaTrait>(proxima: &mut P, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, frozen: bool) -> ProximaOutput<bool> {
         let group = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        proxima.proxima_for_intersection(&group, &poses, &group, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs,  skips, frozen)
+        proxima.proxima_for_intersection(&group, &poses, &group, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, skips, paddings, frozen)
     }
 
     /// Computes the proximity values using Proxima for a single chain.
@@ -345,16 +695,17 @@ impl RobotProximityFunctions {
     /// - `link_shape_mode`: The shape mode of the links.
     /// - `link_shape_rep`: The representation mode of the links.
     /// - `skips`: An optional matrix of boolean values to skip certain checks.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `average_distances`: An optional matrix of average distances.
     /// - `frozen`: A boolean flag indicating whether the Proxima process should be frozen.
     ///
     /// # Returns
     /// A `ProximaOutput<f64>` representing the proximity values.
-    pub fn self_proximity_proxima<P: ProximaTrait>(proxima: &mut P, budget: &ProximaBudget, loss_function: &ProximityLossFunction, p_norm: f64, cutoff_distance: f64, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, average_distances: Option<&DMatrix<f64>>, frozen: bool) -> ProximaOutput<f64> {
+    pub fn self_proximity_proxima<P: ProximaTrait>(proxima: &mut P, budget: &ProximaBudget, loss_function: &ProximityLossFunction, p_norm: f64, cutoff_distance: f64, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, skips: Option<&DMatrix<bool>>, paddings: Option<&DMatrix<f64>>, average_distances: Option<&DMatrix<f64>>, frozen: bool) -> ProximaOutput<f64> {
         let group = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
         let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
 
-        proxima.proxima_for_proximity(budget, &group, &poses, &group, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, loss_function, p_norm, cutoff_distance, skips, average_distances, frozen)
+        proxima.proxima_for_proximity(budget, &group, &poses, &group, &poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, loss_function, p_norm, cutoff_distance, skips, paddings, average_distances, frozen)
     }
 
     /// Checks for double-chain intersections using Proxima.
@@ -369,6 +720,8 @@ impl RobotProximityFunctions {
     /// - `other_link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
     /// - `other_link_shape_mode`: The shape mode of the links in the second chain.
     /// - `other_link_shape_rep`: The representation mode of the links in the second chain.
+    /// - `skips`: An optional matrix indicating which pairs of shapes to skip.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `frozen`: A boolean flag indicating whether the Proxima process should be frozen.
     ///
     /// # Returns
@@ -382,6 +735,8 @@ impl RobotProximityFunctions {
                                                            other_link_poses: &Vec<ISE3q>,
                                                            other_link_shape_mode: LinkShapeMode,
                                                            other_link_shape_rep: LinkShapeRep,
+                                                           skips: Option<&DMatrix<bool>>,
+                                                           paddings: Option<&DMatrix<f64>>,
                                                            frozen: bool) -> ProximaOutput<bool> {
         let self_group = self_link_shapes_module.get_shapes(self_link_shape_mode, self_link_shape_rep);
         let self_poses = self_link_shapes_module.link_poses_to_shape_poses(self_link_poses, self_link_shape_mode);
@@ -389,7 +744,7 @@ impl RobotProximityFunctions {
         let other_group = other_link_shapes_module.get_shapes(other_link_shape_mode, other_link_shape_rep);
         let other_poses = other_link_shapes_module.link_poses_to_shape_poses(other_link_poses, other_link_shape_mode);
 
-        proxima.proxima_for_intersection(&self_group, &self_poses, &other_group, &other_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs,  None, frozen)
+        proxima.proxima_for_intersection(&self_group, &self_poses, &other_group, &other_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, skips, paddings, frozen)
     }
 
     /// Computes the proximity values using Proxima for double chains.
@@ -408,6 +763,8 @@ impl RobotProximityFunctions {
     /// - `other_link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
     /// - `other_link_shape_mode`: The shape mode of the links in the second chain.
     /// - `other_link_shape_rep`: The representation mode of the links in the second chain.
+    /// - `skips`: An optional matrix indicating which pairs of shapes to skip.
+    /// - `paddings`: An optional matrix of paddings (safety margins) between pairs of shapes.
     /// - `frozen`: A boolean flag indicating whether the Proxima process should be frozen.
     ///
     /// # Returns
@@ -425,6 +782,8 @@ impl RobotProximityFunctions {
                                                            other_link_poses: &Vec<ISE3q>,
                                                            other_link_shape_mode: LinkShapeMode,
                                                            other_link_shape_rep: LinkShapeRep,
+                                                           skips: Option<&DMatrix<bool>>,
+                                                           paddings: Option<&DMatrix<f64>>,
                                                            frozen: bool) -> ProximaOutput<f64> {
         let self_group = self_link_shapes_module.get_shapes(self_link_shape_mode, self_link_shape_rep);
         let self_poses = self_link_shapes_module.link_poses_to_shape_poses(self_link_poses, self_link_shape_mode);
@@ -432,6 +791,136 @@ impl RobotProximityFunctions {
         let other_group = other_link_shapes_module.get_shapes(other_link_shape_mode, other_link_shape_rep);
         let other_poses = other_link_shapes_module.link_poses_to_shape_poses(other_link_poses, other_link_shape_mode);
 
-        proxima.proxima_for_proximity(budget, self_group, &self_poses, other_group, &other_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, loss_function, p_norm, cutoff_distance, None, None, frozen)
+        proxima.proxima_for_proximity(budget, self_group, &self_poses, other_group, &other_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, loss_function, p_norm, cutoff_distance, skips, paddings, None, frozen)
     }
-}
\ No newline at end of file
+    /// Runs a pairwise query between the link shapes of a chain and the objects attached to it.
+    /// Attached object `k` is reported with shape index `num_link_shapes + k`.
+    ///
+    /// # Arguments
+    /// - `query`: The pairwise query, e.g., `pairwise_group_query_intersection`.
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `attached_objects_module`: A reference to the attached objects module.
+    /// - `skips`: A matrix indicating which link shape (rows) and attached object (columns) pairs to skip.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `extra_args`: The extra arguments of the query (e.g., the margin for contact queries).
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<T>` with `(shape_idx, num_link_shapes + object_idx)` pairs.
+    pub fn attached_objects_self_query<T: Clone + Debug, E, Q>(query: Q, link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, attached_objects_module: &ApolloAttachedObjectsModule, skips: &DMatrix<bool>, early_stop: bool, extra_args: E) -> DoubleGroupProximityQueryOutput<T>
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+        let object_poses = attached_objects_module.get_poses(link_poses);
+
+        let num_shapes = shapes.len();
+        query(shapes, &poses, attached_objects_module.shapes(), &object_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, Some(skips), early_stop, extra_args)
+            .map_shape_idxs(|(i, j)| (i, num_shapes + j))
+    }
+
+    /// Runs a pairwise query between the objects attached to a chain.
+    ///
+    /// # Arguments
+    /// - `query`: The pairwise query, e.g., `pairwise_group_query_intersection`.
+    /// - `num_link_shapes`: The number of link shapes of the chain in the queried shape mode.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `attached_objects_module`: A reference to the attached objects module.
+    /// - `skips`: A matrix indicating which pairs of attached objects to skip.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `extra_args`: The extra arguments of the query (e.g., the margin for contact queries).
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<T>` with `(num_link_shapes + object_idx_a, num_link_shapes + object_idx_b)` pairs.
+    pub fn attached_objects_pairs_query<T: Clone + Debug, E, Q>(query: Q, num_link_shapes: usize, link_poses: &Vec<ISE3q>, attached_objects_module: &ApolloAttachedObjectsModule, skips: &DMatrix<bool>, early_stop: bool, extra_args: E) -> DoubleGroupProximityQueryOutput<T>
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T> {
+        let object_poses = attached_objects_module.get_poses(link_poses);
+        let shapes = attached_objects_module.shapes();
+
+        query(shapes, &object_poses, shapes, &object_poses, &DoubleGroupProximityQueryMode::SkipSymmetricalPairs, Some(skips), early_stop, extra_args)
+            .map_shape_idxs(|(i, j)| (num_link_shapes + i, num_link_shapes + j))
+    }
+
+    /// Runs a pairwise query between the objects attached to a chain and a set of obstacles.
+    ///
+    /// # Arguments
+    /// - `query`: The pairwise query, e.g., `pairwise_group_query_intersection`.
+    /// - `num_link_shapes`: The number of link shapes of the chain in the queried shape mode.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `attached_objects_module`: A reference to the attached objects module.
+    /// - `obstacles`: The obstacle shapes.
+    /// - `obstacle_poses`: The world pose of each obstacle.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `extra_args`: The extra arguments of the query (e.g., the margin for contact queries).
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<T>` with `(num_link_shapes + object_idx, obstacle_idx)` pairs.
+    pub fn attached_objects_obstacles_query<T: Clone + Debug, E, Q>(query: Q, num_link_shapes: usize, link_poses: &Vec<ISE3q>, attached_objects_module: &ApolloAttachedObjectsModule, obstacles: &Vec<OffsetShape>, obstacle_poses: &Vec<ISE3q>, early_stop: bool, extra_args: E) -> DoubleGroupProximityQueryOutput<T>
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T> {
+        let object_poses = attached_objects_module.get_poses(link_poses);
+
+        query(attached_objects_module.shapes(), &object_poses, obstacles, obstacle_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, None, early_stop, extra_args)
+            .map_shape_idxs(|(i, j)| (num_link_shapes + i, j))
+    }
+
+    /// Runs a pairwise query between the objects attached to one chain and the link shapes of
+    /// another chain.  Attached object `k` is reported with shape index `num_self_link_shapes + k`.
+    ///
+    /// # Arguments
+    /// - `query`: The pairwise query, e.g., `pairwise_group_query_intersection`.
+    /// - `num_self_link_shapes`: The number of link shapes of the chain holding the objects in the queried shape mode.
+    /// - `self_link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain holding the objects.
+    /// - `attached_objects_module`: A reference to the attached objects module of the chain holding the objects.
+    /// - `other_link_shapes_module`: A reference to the link shapes module of the other chain.
+    /// - `other_link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the other chain.
+    /// - `other_link_shape_mode`: The shape mode of the links in the other chain.
+    /// - `other_link_shape_rep`: The representation mode of the links in the other chain.
+    /// - `skips`: A matrix indicating which other link shape (rows) and attached object (columns) pairs to skip.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `extra_args`: The extra arguments of the query (e.g., the margin for contact queries).
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<T>` with `(num_self_link_shapes + object_idx, other_shape_idx)` pairs.
+    pub fn attached_objects_other_chain_query<T: Clone + Debug, E, Q>(query: Q, num_self_link_shapes: usize, self_link_poses: &Vec<ISE3q>, attached_objects_module: &ApolloAttachedObjectsModule, other_link_shapes_module: &ApolloLinkShapesModule, other_link_poses: &Vec<ISE3q>, other_link_shape_mode: LinkShapeMode, other_link_shape_rep: LinkShapeRep, skips: &DMatrix<bool>, early_stop: bool, extra_args: E) -> DoubleGroupProximityQueryOutput<T>
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T> {
+        let other_shapes = other_link_shapes_module.get_shapes(other_link_shape_mode, other_link_shape_rep);
+        let other_poses = other_link_shapes_module.link_poses_to_shape_poses(other_link_poses, other_link_shape_mode);
+        let object_poses = attached_objects_module.get_poses(self_link_poses);
+
+        query(other_shapes, &other_poses, attached_objects_module.shapes(), &object_poses, &DoubleGroupProximityQueryMode::AllPossiblePairs, Some(skips), early_stop, extra_args)
+            .map_shape_idxs(|(i, j)| (num_self_link_shapes + j, i))
+    }
+
+    /// Runs a pairwise query between the objects attached to one chain and the objects attached
+    /// to another chain.
+    ///
+    /// # Arguments
+    /// - `query`: The pairwise query, e.g., `pairwise_group_query_intersection`.
+    /// - `num_link_shapes_a`: The number of link shapes of the first chain in the queried shape mode.
+    /// - `link_poses_a`: A reference to a vector of `ISE3q` representing the poses of each link in the first chain.
+    /// - `attached_objects_module_a`: A reference to the attached objects module of the first chain.
+    /// - `num_link_shapes_b`: The number of link shapes of the second chain in the queried shape mode.
+    /// - `link_poses_b`: A reference to a vector of `ISE3q` representing the poses of each link in the second chain.
+    /// - `attached_objects_module_b`: A reference to the attached objects module of the second chain.
+    /// - `skips`: A matrix indicating which pairs of attached objects (first chain in rows) to skip.
+    /// - `early_stop`: A boolean flag to enable early stopping during checks.
+    /// - `extra_args`: The extra arguments of the query (e.g., the margin for contact queries).
+    ///
+    /// # Returns
+    /// A `DoubleGroupProximityQueryOutput<T>` with `(num_link_shapes_a + object_idx_a, num_link_shapes_b + object_idx_b)` pairs.
+    pub fn attached_objects_other_chain_objects_query<T: Clone + Debug, E, Q>(query: Q, num_link_shapes_a: usize, link_poses_a: &Vec<ISE3q>, attached_objects_module_a: &ApolloAttachedObjectsModule, num_link_shapes_b: usize, link_poses_b: &Vec<ISE3q>, attached_objects_module_b: &ApolloAttachedObjectsModule, skips: &DMatrix<bool>, early_stop: bool, extra_args: E) -> DoubleGroupProximityQueryOutput<T>
+        where Q: Fn(&Vec<OffsetShape>, &Vec<ISE3q>, &Vec<OffsetShape>, &Vec<ISE3q>, &DoubleGroupProximityQueryMode, Option<&DMatrix<bool>>, bool, E) -> DoubleGroupProximityQueryOutput<T> {
+        let object_poses_a = attached_objects_module_a.get_poses(link_poses_a);
+        let object_poses_b = attached_objects_module_b.get_poses(link_poses_b);
+
+        query(attached_objects_module_a.shapes(), &object_poses_a, attached_objects_module_b.shapes(), &object_poses_b, &DoubleGroupProximityQueryMode::AllPossiblePairs, Some(skips), early_stop, extra_args)
+            .map_shape_idxs(|(i, j)| (num_link_shapes_a + i, num_link_shapes_b + j))
+    }
+}
+
+/// Returns the largest entry of a paddings matrix, or zero if there is none.  Broadphase bounds are
+/// loosened by this amount so that padded pairs are not culled before the narrowphase.
+pub fn max_padding(paddings: &DMatrix<f64>) -> f64 {
+    paddings.iter().fold(0.0, |acc: f64, x| acc.max(*x))
+}
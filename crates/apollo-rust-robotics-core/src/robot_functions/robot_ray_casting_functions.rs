@@ -0,0 +1,75 @@
+use apollo_rust_proximity_parry::ray_casting::{group_ray_cast, group_ray_cast_batch, GroupRayCastHit};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V3;
+use parry3d_f64::query::Ray;
+use crate::modules_runtime::link_shapes_module::{ApolloLinkShapesModule, LinkShapeMode, LinkShapeRep};
+
+/// The closest hit of a ray against the links of a robot.
+#[derive(Clone, Debug)]
+pub struct RobotRayCastHit {
+    /// Index of the hit link.
+    pub link_idx: usize,
+    /// Index of the hit subcomponent within the link (always 0 for `LinkShapeMode::Full`).
+    pub subcomponent_idx: usize,
+    /// Distance from the ray origin to the hit point.
+    pub distance: f64,
+    /// Hit point in the world frame.
+    pub point: V3,
+    /// Outward surface normal at the hit point in the world frame.
+    pub normal: V3
+}
+
+pub struct RobotRayCastingFunctions;
+impl RobotRayCastingFunctions {
+    /// Casts a ray against the robot's links and returns the closest hit.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `ray`: The ray in the world frame.
+    /// - `max_distance`: Hits farther than this distance from the ray origin are ignored.
+    ///
+    /// # Returns
+    /// The closest `RobotRayCastHit`, or `None` if the ray does not hit any link.
+    pub fn ray_cast(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, ray: &Ray, max_distance: f64) -> Option<RobotRayCastHit> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+
+        let res = group_ray_cast(shapes, &poses, ray, max_distance);
+        res.map(|x| Self::to_robot_ray_cast_hit(link_shapes_module, link_shape_mode, x))
+    }
+
+    /// Casts a batch of rays against the robot's links in parallel.
+    ///
+    /// # Arguments
+    /// - `link_shapes_module`: A reference to the link shapes module.
+    /// - `link_poses`: A reference to a vector of `ISE3q` representing the poses of each link in the chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    /// - `rays`: The rays in the world frame.
+    /// - `max_distance`: Hits farther than this distance from the ray origin are ignored.
+    ///
+    /// # Returns
+    /// A vector with the closest hit of each ray, in the same order as `rays`.
+    pub fn ray_cast_batch(link_shapes_module: &ApolloLinkShapesModule, link_poses: &Vec<ISE3q>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep, rays: &Vec<Ray>, max_distance: f64) -> Vec<Option<RobotRayCastHit>> {
+        let shapes = link_shapes_module.get_shapes(link_shape_mode, link_shape_rep);
+        let poses = link_shapes_module.link_poses_to_shape_poses(link_poses, link_shape_mode);
+
+        let res = group_ray_cast_batch(shapes, &poses, rays, max_distance);
+        res.into_iter().map(|x| x.map(|y| Self::to_robot_ray_cast_hit(link_shapes_module, link_shape_mode, y))).collect()
+    }
+
+    fn to_robot_ray_cast_hit(link_shapes_module: &ApolloLinkShapesModule, link_shape_mode: LinkShapeMode, hit: GroupRayCastHit) -> RobotRayCastHit {
+        let (link_idx, subcomponent_idx) = link_shapes_module.get_link_idx_and_subcomponent_idx_from_shape_idx(hit.shape_idx, &link_shape_mode);
+
+        RobotRayCastHit {
+            link_idx,
+            subcomponent_idx,
+            distance: hit.distance,
+            point: hit.point,
+            normal: hit.normal,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod sensors;
+
 use apollo_rust_modules::robot_modules::bounds_module::ApolloBoundsModule;
 use apollo_rust_modules::robot_modules::chain_module::ApolloChainModule;
 use apollo_rust_modules::robot_modules::connections_module::ApolloConnectionsModule;
@@ -10,16 +12,22 @@ use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_distanc
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_max_distance_from_origin_module::ApolloLinkShapesMaxDistanceFromOriginModule;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_simple_skips_module::ApolloLinkShapesSimpleSkipsModule;
 use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_skips_module::ApolloLinkShapesSkipsModule;
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_padding_module::ApolloLinkShapesPaddingModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_decomposition_meshes_module::ApolloConvexDecompositionMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::convex_hull_meshes_module::ApolloConvexHullMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::original_meshes_module::ApolloOriginalMeshesModule;
 use apollo_rust_modules::robot_modules::mesh_modules::plain_meshes_module::ApolloPlainMeshesModule;
+use apollo_rust_modules::robot_modules::signed_distance_field_module::{ApolloSignedDistanceFieldModule, ApolloSignedDistanceFieldParameters};
 pub use apollo_rust_modules::{ResourcesRootDirectory, ResourcesSubDirectory, ResourcesType};
-use apollo_rust_preprocessor::PreprocessorModule;
+use apollo_rust_preprocessor::{PreprocessorModule, PreprocessorModuleWithParameters};
 use apollo_rust_robotics_core::modules_runtime::link_shapes_distance_statistics_nalgebra_module::ApolloLinkShapesDistanceStatisticsNalgebraModule;
 use apollo_rust_robotics_core::modules_runtime::link_shapes_module::ApolloLinkShapesModule;
 use apollo_rust_robotics_core::modules_runtime::link_shapes_simple_skips_nalgebra_module::ApolloLinkShapesSimpleSkipsNalgebraModule;
 use apollo_rust_robotics_core::modules_runtime::link_shapes_skips_nalgebra_module::ApolloLinkShapesSkipsNalgebraModule;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_padding_nalgebra_module::ApolloLinkShapesPaddingNalgebraModule;
+use apollo_rust_robotics_core::modules_runtime::allowed_collision_matrix::AllowedCollisionMatrix;
+use apollo_rust_robotics_core::modules_runtime::attached_objects_module::ApolloAttachedObjectsModule;
+use apollo_rust_robotics_core::modules_runtime::signed_distance_field_nalgebra_module::ApolloSignedDistanceFieldNalgebraModule;
 use apollo_rust_robotics_core::modules_runtime::urdf_nalgebra_module::ApolloURDFNalgebraModule;
 pub use apollo_rust_robotics_core::ChainNalgebra;
 
@@ -78,13 +86,14 @@ impl ChainBuildersTrait for ChainNalgebra {
         let convex_decomposition_meshes_module =
             ApolloConvexDecompositionMeshesModule::load_or_build(&s, false).expect("error");
         ApolloFirstLookVisModule::load_or_build(&s, false).expect("error");
+        let link_shapes_approximations_module =
+            ApolloLinkShapesApproximationsModule::load_or_build(&s, false).expect("error");
         let link_shapes_module = ApolloLinkShapesModule::from_mesh_modules(
             &s,
             &convex_hull_meshes_module,
             &convex_decomposition_meshes_module,
+            &link_shapes_approximations_module,
         );
-        let link_shapes_approximations_module =
-            ApolloLinkShapesApproximationsModule::load_or_build(&s, false).expect("error");
         let link_shapes_max_distance_from_origin_module =
             ApolloLinkShapesMaxDistanceFromOriginModule::load_or_build(&s, false).expect("error");
         let link_shapes_distance_statistics_module =
@@ -102,6 +111,18 @@ impl ChainBuildersTrait for ChainNalgebra {
             ApolloLinkShapesSkipsNalgebraModule::from_link_shapes_skips_module(
                 &link_shapes_skips_module,
             );
+        let link_shapes_padding_module =
+            ApolloLinkShapesPaddingModule::load_or_build(&s, false).expect("error");
+        let link_shapes_padding_nalgebra_module =
+            ApolloLinkShapesPaddingNalgebraModule::from_link_shapes_padding_module(
+                &link_shapes_padding_module,
+                &link_shapes_module,
+            );
+        let allowed_collision_matrix = AllowedCollisionMatrix::new(
+            apollo_urdf_module.links.iter().map(|x| x.name.clone()).collect(),
+            &link_shapes_module,
+            link_shapes_skips_nalgebra_module,
+        );
         let bounds_module = ApolloBoundsModule::load_or_build(&s, false).expect("error");
 
         Self {
@@ -119,7 +140,9 @@ impl ChainBuildersTrait for ChainNalgebra {
             link_shapes_max_distance_from_origin_module,
             link_shapes_distance_statistics_module,
             link_shapes_simple_skips_nalgebra_module,
-            link_shapes_skips_nalgebra_module,
+            link_shapes_padding_nalgebra_module,
+            allowed_collision_matrix,
+            attached_objects_module: ApolloAttachedObjectsModule::new(),
             bounds_module,
         }
     }
@@ -207,3 +230,24 @@ impl ToChainFromPath for PathBuf {
         ChainNalgebra::new_from_path(self, resources_type)
     }
 }
+
+/// Trait for loading (or building, on first use) the signed distance field of a static environment.
+pub trait ToSignedDistanceField {
+    /// Returns the runtime signed distance field of the environment at its zero state, built
+    /// with default parameters.
+    fn to_signed_distance_field(&self) -> ApolloSignedDistanceFieldNalgebraModule {
+        self.to_signed_distance_field_with_parameters(&ApolloSignedDistanceFieldParameters::default())
+    }
+
+    /// Returns the runtime signed distance field of the environment at its zero state.  The
+    /// saved field is rebuilt if it was built with different parameters.
+    fn to_signed_distance_field_with_parameters(&self, parameters: &ApolloSignedDistanceFieldParameters) -> ApolloSignedDistanceFieldNalgebraModule;
+}
+
+/// Implementation of `ToSignedDistanceField` for the `ChainNalgebra` type.
+impl ToSignedDistanceField for ChainNalgebra {
+    fn to_signed_distance_field_with_parameters(&self, parameters: &ApolloSignedDistanceFieldParameters) -> ApolloSignedDistanceFieldNalgebraModule {
+        let signed_distance_field_module = ApolloSignedDistanceFieldModule::load_or_build_with_parameters(self.resources_sub_directory(), parameters, false).expect("error");
+        ApolloSignedDistanceFieldNalgebraModule::from_signed_distance_field_module(&signed_distance_field_module)
+    }
+}
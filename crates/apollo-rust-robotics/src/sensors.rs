@@ -0,0 +1,276 @@
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_linalg::V;
+use apollo_rust_robotics_core::ChainNalgebra;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V3;
+use parry3d_f64::math::Point;
+use parry3d_f64::query::Ray;
+
+/// Where a simulated sensor is attached.
+#[derive(Clone, Debug)]
+pub enum SensorMount {
+    /// Fixed in the world frame.
+    World { pose: ISE3q },
+    /// Rigidly attached to link `link_idx` of chain `chain_idx` (indices into the `chains` passed
+    /// to the sensor), with `offset` expressed in the link frame.
+    Link { chain_idx: usize, link_idx: usize, offset: ISE3q }
+}
+impl SensorMount {
+    /// Returns the world pose of the sensor for the given chain states.
+    pub fn get_sensor_pose(&self, chains: &Vec<&ChainNalgebra>, states: &Vec<V>) -> ISE3q {
+        match self {
+            SensorMount::World { pose } => { pose.clone() }
+            SensorMount::Link { chain_idx, link_idx, offset } => {
+                let link_poses = chains[*chain_idx].fk(&states[*chain_idx]);
+                link_poses[*link_idx].group_operator(offset)
+            }
+        }
+    }
+}
+
+/// A ray return of a simulated sensor.
+#[derive(Clone, Debug)]
+pub struct SensorHit {
+    /// Distance from the sensor origin along the ray.
+    pub range: f64,
+    /// Hit point in the world frame.
+    pub point: V3,
+    pub chain_idx: usize,
+    pub link_idx: usize
+}
+
+/// Pinhole camera intrinsics.  The camera frame follows the optical convention: z points along
+/// the optical axis, x to the right of the image, and y down.
+#[derive(Clone, Debug)]
+pub struct PinholeCameraModel {
+    pub width: usize,
+    pub height: usize,
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    /// Near clipping distance.  Rays start this far from the sensor origin, so surfaces closer
+    /// than this are not seen.  The mounting link is not excluded from the ray casts, so rays
+    /// that leave the sensor through it still hit it unless `min_range` is past its surface.
+    pub min_range: f64,
+    pub max_range: f64
+}
+impl PinholeCameraModel {
+    pub fn new(width: usize, height: usize, fx: f64, fy: f64, cx: f64, cy: f64, min_range: f64, max_range: f64) -> Self {
+        Self { width, height, fx, fy, cx, cy, min_range, max_range }
+    }
+
+    /// Creates a camera with square pixels and a centered principal point from a horizontal
+    /// field of view in radians.
+    pub fn new_from_horizontal_fov(width: usize, height: usize, horizontal_fov: f64, min_range: f64, max_range: f64) -> Self {
+        let f = (width as f64 / 2.0) / (horizontal_fov / 2.0).tan();
+        Self::new(width, height, f, f, width as f64 / 2.0, height as f64 / 2.0, min_range, max_range)
+    }
+
+    /// Returns the (unnormalized, z = 1) ray direction through the center of pixel `(u, v)` in the camera frame.
+    #[inline(always)]
+    pub fn pixel_direction(&self, u: usize, v: usize) -> V3 {
+        V3::new((u as f64 + 0.5 - self.cx) / self.fx, (v as f64 + 0.5 - self.cy) / self.fy, 1.0)
+    }
+}
+
+/// A depth image stored row-major, with `f64::INFINITY` for pixels without a return.
+///
+/// Depth is measured along the optical axis (z-depth), not along the pixel ray.
+#[derive(Clone, Debug)]
+pub struct DepthImage {
+    pub width: usize,
+    pub height: usize,
+    pub depths: Vec<f64>,
+    /// `(chain_idx, link_idx)` seen at each pixel, usable as a segmentation mask.
+    pub labels: Vec<Option<(usize, usize)>>,
+    /// World pose of the camera when the image was rendered.
+    pub camera_pose: ISE3q
+}
+impl DepthImage {
+    #[inline(always)]
+    pub fn get_depth(&self, u: usize, v: usize) -> f64 {
+        self.depths[v * self.width + u]
+    }
+
+    #[inline(always)]
+    pub fn get_label(&self, u: usize, v: usize) -> Option<(usize, usize)> {
+        self.labels[v * self.width + u]
+    }
+
+    /// Back-projects all valid pixels into a point cloud in the camera frame.
+    pub fn to_point_cloud_in_camera_frame(&self, model: &PinholeCameraModel) -> Vec<V3> {
+        let mut out = vec![];
+        for v in 0..self.height {
+            for u in 0..self.width {
+                let depth = self.get_depth(u, v);
+                if depth.is_finite() { out.push(model.pixel_direction(u, v) * depth); }
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulatedDepthCamera {
+    pub mount: SensorMount,
+    pub model: PinholeCameraModel
+}
+impl SimulatedDepthCamera {
+    pub fn new(mount: SensorMount, model: PinholeCameraModel) -> Self {
+        Self { mount, model }
+    }
+
+    /// Renders a depth image of the given chains at the given states.
+    ///
+    /// # Arguments
+    /// - `chains`: The chains in the scene (e.g., a robot and its environment).
+    /// - `states`: The state of each chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    ///
+    /// # Returns
+    /// The rendered `DepthImage`.
+    pub fn render(&self, chains: &Vec<&ChainNalgebra>, states: &Vec<V>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> DepthImage {
+        let camera_pose = self.mount.get_sensor_pose(chains, states);
+
+        let mut directions = vec![];
+        for v in 0..self.model.height {
+            for u in 0..self.model.width {
+                directions.push(self.model.pixel_direction(u, v));
+            }
+        }
+
+        let hits = cast_sensor_rays(&camera_pose, &directions, self.model.min_range, self.model.max_range, chains, states, link_shape_mode, link_shape_rep);
+
+        let mut depths = vec![];
+        let mut labels = vec![];
+        for (direction, hit) in directions.iter().zip(hits.iter()) {
+            match hit {
+                None => {
+                    depths.push(f64::INFINITY);
+                    labels.push(None);
+                }
+                Some(hit) => {
+                    depths.push(hit.range / direction.norm());
+                    labels.push(Some((hit.chain_idx, hit.link_idx)));
+                }
+            }
+        }
+
+        DepthImage {
+            width: self.model.width,
+            height: self.model.height,
+            depths,
+            labels,
+            camera_pose,
+        }
+    }
+}
+
+/// A LiDAR scan pattern as a grid of azimuth and elevation angles.  The sensor frame has x
+/// forward, y left, and z up; azimuth is measured about z from x, elevation up from the xy-plane.
+#[derive(Clone, Debug)]
+pub struct LidarScanPattern {
+    pub azimuths: Vec<f64>,
+    pub elevations: Vec<f64>,
+    /// Returns closer than this are discarded.  Rays start this far from the sensor origin.
+    pub min_range: f64,
+    pub max_range: f64
+}
+impl LidarScanPattern {
+    pub fn new(azimuths: Vec<f64>, elevations: Vec<f64>, min_range: f64, max_range: f64) -> Self {
+        Self { azimuths, elevations, min_range, max_range }
+    }
+
+    /// Full 360 degree scan with `num_azimuths` evenly spaced columns and `num_elevations` rings
+    /// evenly spaced between `min_elevation` and `max_elevation` (inclusive).
+    pub fn new_spinning(num_azimuths: usize, num_elevations: usize, min_elevation: f64, max_elevation: f64, min_range: f64, max_range: f64) -> Self {
+        assert!(num_azimuths > 0 && num_elevations > 0);
+
+        let azimuths = (0..num_azimuths).map(|i| 2.0 * std::f64::consts::PI * i as f64 / num_azimuths as f64).collect();
+        let elevations = if num_elevations == 1 {
+            vec![(min_elevation + max_elevation) / 2.0]
+        } else {
+            (0..num_elevations).map(|i| min_elevation + (max_elevation - min_elevation) * i as f64 / (num_elevations - 1) as f64).collect()
+        };
+
+        Self::new(azimuths, elevations, min_range, max_range)
+    }
+
+    /// Returns the unit ray directions in the sensor frame, ordered elevation-major.
+    pub fn directions(&self) -> Vec<V3> {
+        let mut out = vec![];
+        for elevation in &self.elevations {
+            for azimuth in &self.azimuths {
+                out.push(V3::new(elevation.cos() * azimuth.cos(), elevation.cos() * azimuth.sin(), elevation.sin()));
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LidarScan {
+    /// One entry per ray of the scan pattern (elevation-major).
+    pub returns: Vec<Option<SensorHit>>,
+    /// World pose of the sensor when the scan was taken.
+    pub sensor_pose: ISE3q
+}
+impl LidarScan {
+    /// Returns the hit points in the world frame.
+    pub fn point_cloud(&self) -> Vec<V3> {
+        self.returns.iter().filter_map(|x| x.as_ref().map(|y| y.point.clone())).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulatedLidar {
+    pub mount: SensorMount,
+    pub pattern: LidarScanPattern
+}
+impl SimulatedLidar {
+    pub fn new(mount: SensorMount, pattern: LidarScanPattern) -> Self {
+        Self { mount, pattern }
+    }
+
+    /// Takes a scan of the given chains at the given states.
+    ///
+    /// # Arguments
+    /// - `chains`: The chains in the scene (e.g., a robot and its environment).
+    /// - `states`: The state of each chain.
+    /// - `link_shape_mode`: The shape mode of the links.
+    /// - `link_shape_rep`: The representation mode of the links.
+    ///
+    /// # Returns
+    /// The resulting `LidarScan`.
+    pub fn scan(&self, chains: &Vec<&ChainNalgebra>, states: &Vec<V>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> LidarScan {
+        let sensor_pose = self.mount.get_sensor_pose(chains, states);
+        let directions = self.pattern.directions();
+        let returns = cast_sensor_rays(&sensor_pose, &directions, self.pattern.min_range, self.pattern.max_range, chains, states, link_shape_mode, link_shape_rep);
+
+        LidarScan {
+            returns,
+            sensor_pose,
+        }
+    }
+}
+
+/// Casts rays with sensor-frame `directions` from `sensor_pose`, starting `min_range` from the origin.
+fn cast_sensor_rays(sensor_pose: &ISE3q, directions: &Vec<V3>, min_range: f64, max_range: f64, chains: &Vec<&ChainNalgebra>, states: &Vec<V>, link_shape_mode: LinkShapeMode, link_shape_rep: LinkShapeRep) -> Vec<Option<SensorHit>> {
+    let origin = sensor_pose.0.translation.vector;
+    let rays: Vec<Ray> = directions.iter().map(|x| {
+        let dir = (sensor_pose.0.rotation * x).normalize();
+        Ray::new(Point::from(origin + dir * min_range), dir)
+    }).collect();
+
+    let hits = ChainNalgebra::multi_chain_ray_cast_batch_from_states(chains, states, link_shape_mode, link_shape_rep, &rays, max_range - min_range);
+
+    hits.into_iter().map(|x| x.map(|(chain_idx, hit)| SensorHit {
+        range: hit.distance + min_range,
+        point: hit.point,
+        chain_idx,
+        link_idx: hit.link_idx,
+    })).collect()
+}
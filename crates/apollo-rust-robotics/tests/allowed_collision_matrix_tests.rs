@@ -0,0 +1,66 @@
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::allowed_collision_matrix::wildcard_match;
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use std::path::PathBuf;
+
+#[test]
+fn test_wildcard_match() {
+    assert!(wildcard_match("wrist_1_link", "wrist_1_link"));
+    assert!(wildcard_match("*", "wrist_1_link"));
+    assert!(wildcard_match("wrist_*", "wrist_1_link"));
+    assert!(wildcard_match("*_link", "wrist_1_link"));
+    assert!(wildcard_match("w*1*link", "wrist_1_link"));
+    assert!(!wildcard_match("wrist_*", "forearm_link"));
+    assert!(!wildcard_match("wrist_1", "wrist_1_link"));
+    assert!(!wildcard_match("*_link_*", "wrist_1_link"));
+}
+
+#[test]
+fn test_ur5_allowed_collision_matrix() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let mut chain = path.to_chain(ResourcesType::Robot);
+    let state = V::new(&[0.0, -1.0, 1.0, 0.0, 0.5, 0.0]);
+    let mode = LinkShapeMode::Full;
+    let rep = LinkShapeRep::ConvexHull;
+
+    let link_names = chain.allowed_collision_matrix().link_names.clone();
+    let shape_link_names: Vec<String> = chain.link_shapes_module().full_shape_idx_to_link_idx().iter().map(|x| link_names[*x].clone()).collect();
+    let link_name_of_shape = |shape_idx: usize| -> String { shape_link_names[shape_idx].clone() };
+
+    let before = chain.self_distance_from_state(&state, mode, rep, false);
+    assert!(before.shape_idxs.iter().any(|(i, j)| link_name_of_shape(*i).starts_with("wrist_") || link_name_of_shape(*j).starts_with("wrist_")));
+
+    chain.allowed_collision_matrix_mut().push_scope();
+    chain.allowed_collision_matrix_mut().allow("wrist_*", "*");
+    let during = chain.self_distance_from_state(&state, mode, rep, false);
+    assert!(during.shape_idxs.len() < before.shape_idxs.len());
+    assert!(during.shape_idxs.iter().all(|(i, j)| !link_name_of_shape(*i).starts_with("wrist_") && !link_name_of_shape(*j).starts_with("wrist_")));
+
+    // a later rule overrides an earlier one.
+    chain.allowed_collision_matrix_mut().disallow("wrist_1_link", "base_link");
+    assert_eq!(chain.allowed_collision_matrix().is_allowed("base_link", "wrist_1_link"), Some(false));
+    chain.allowed_collision_matrix_mut().pop_scope();
+
+    let after = chain.self_distance_from_state(&state, mode, rep, false);
+    assert_eq!(before.shape_idxs, after.shape_idxs);
+    assert_eq!(chain.allowed_collision_matrix().is_allowed("base_link", "wrist_1_link"), None);
+    // popping the scope restores the preprocessed skips in every mode and representation.
+    for m in [LinkShapeMode::Full, LinkShapeMode::Decomposition] {
+        for r in [LinkShapeRep::ConvexHull, LinkShapeRep::OBB, LinkShapeRep::BoundingSphere, LinkShapeRep::MultiSphere, LinkShapeRep::Capsule] {
+            assert_eq!(chain.allowed_collision_matrix().get_skips(m, r), chain.link_shapes_skips_nalgebra_module().get_skips(m, r));
+        }
+    }
+
+    // disallowing a preprocessed skip (adjacent links) forces the pair to be checked.
+    chain.allowed_collision_matrix_mut().disallow("shoulder_link", "upper_arm_link");
+    let forced = chain.self_distance_from_state(&state, mode, rep, false);
+    assert!(forced.shape_idxs.iter().any(|(i, j)| {
+        let (a, b) = (link_name_of_shape(*i), link_name_of_shape(*j));
+        (a == "shoulder_link" && b == "upper_arm_link") || (a == "upper_arm_link" && b == "shoulder_link")
+    }));
+    chain.allowed_collision_matrix_mut().clear();
+    assert_eq!(chain.allowed_collision_matrix().get_skips(mode, rep), chain.link_shapes_skips_nalgebra_module().get_skips(mode, rep));
+}
@@ -0,0 +1,77 @@
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_proximity_parry::ToIntersectionResult;
+use apollo_rust_proximity_parry::bvh::BvhShapeAABB;
+use apollo_rust_proximity_parry::offset_shape::OffsetShape;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V6;
+use parry3d_f64::shape::{Ball, Cuboid};
+use parry3d_f64::na::Vector3;
+use std::path::PathBuf;
+
+#[test]
+fn test_ur5_attached_objects() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let mut chain = path.to_chain(ResourcesType::Robot);
+    let state = V::new(&[0.0, -1.0, 1.0, 0.0, 0.5, 0.0]);
+    let mode = LinkShapeMode::Full;
+    let rep = LinkShapeRep::ConvexHull;
+    let num_shapes = chain.link_shapes_module().get_shapes(mode, rep).len();
+
+    let link_names = chain.allowed_collision_matrix().link_names.clone();
+    let ee_link_idx = link_names.iter().position(|x| x == "ee_link").expect("error");
+    let relative_pose = ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 0.3, 0.0, 0.0));
+
+    let link_poses = chain.fk(&state);
+    let object_pose = link_poses[ee_link_idx].group_operator(&relative_pose);
+    let obstacles = vec![OffsetShape::new(Ball::new(0.02), None)];
+    let obstacle_poses = vec![object_pose];
+
+    assert!(!chain.obstacles_intersect(&link_poses, mode, rep, &obstacles, &obstacle_poses, false).to_intersection_result());
+    let self_before = chain.self_distance(&link_poses, mode, rep, false);
+
+    chain.attach_object("part", ee_link_idx, OffsetShape::new(Cuboid::new(Vector3::new(0.05, 0.05, 0.05)), None), relative_pose.clone(), vec!["wrist_*".to_string()]);
+
+    let res = chain.obstacles_intersect(&link_poses, mode, rep, &obstacles, &obstacle_poses, false);
+    assert!(res.to_intersection_result());
+    assert!(res.shape_idxs.iter().zip(res.outputs.iter()).any(|(x, y)| *x == (num_shapes, 0) && *y));
+
+    // the part is checked against all links except its parent link and the touch links.
+    let self_after = chain.self_distance(&link_poses, mode, rep, false);
+    let object_pairs: Vec<(usize, usize)> = self_after.shape_idxs.iter().filter(|x| x.1 == num_shapes).cloned().collect();
+    assert_eq!(self_after.shape_idxs.len(), self_before.shape_idxs.len() + object_pairs.len());
+    assert!(!object_pairs.is_empty());
+    object_pairs.iter().for_each(|(i, _)| {
+        let link_name = &link_names[chain.link_shapes_module().full_shape_idx_to_link_idx()[*i]];
+        assert!(link_name != "ee_link" && !link_name.starts_with("wrist_"));
+    });
+
+    // allowed collision matrix rules naming the object take precedence over touch links.
+    chain.allowed_collision_matrix_mut().allow("part", "*");
+    assert_eq!(chain.self_distance(&link_poses, mode, rep, false).shape_idxs.len(), self_before.shape_idxs.len());
+    chain.allowed_collision_matrix_mut().clear();
+
+    // objects on different links are checked against each other unless a rule allows the pair.
+    let wrist_1_link_idx = link_names.iter().position(|x| x == "wrist_1_link").expect("error");
+    let tool_pose = link_poses[wrist_1_link_idx].inverse().group_operator(&obstacle_poses[0]);
+    chain.attach_object("tool", wrist_1_link_idx, OffsetShape::new(Ball::new(0.05), None), tool_pose, vec!["*".to_string()]);
+    let res = chain.self_intersect(&link_poses, mode, rep, false);
+    assert!(res.shape_idxs.iter().zip(res.outputs.iter()).any(|(x, y)| *x == (num_shapes, num_shapes + 1) && *y));
+    // the accelerated variants check attached objects as well.
+    let mut bvh = chain.get_bvh::<BvhShapeAABB>(&state, mode, rep, 2);
+    let res = chain.self_intersect_bvh(&mut bvh, &link_poses, mode, rep, false);
+    assert!(res.shape_idxs.iter().zip(res.outputs.iter()).any(|(x, y)| *x == (num_shapes, num_shapes + 1) && *y));
+    assert!(chain.self_intersect_cascade(&link_poses, true).to_intersection_result());
+    chain.allowed_collision_matrix_mut().allow("part", "tool");
+    assert!(chain.self_intersect(&link_poses, mode, rep, false).shape_idxs.iter().all(|x| *x != (num_shapes, num_shapes + 1)));
+    chain.allowed_collision_matrix_mut().clear();
+    assert!(chain.detach_object("tool"));
+
+    assert!(chain.detach_object("part"));
+    assert!(!chain.detach_object("part"));
+    assert!(!chain.obstacles_intersect(&link_poses, mode, rep, &obstacles, &obstacle_poses, false).to_intersection_result());
+}
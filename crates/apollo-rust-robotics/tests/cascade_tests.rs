@@ -0,0 +1,24 @@
+use apollo_rust_proximity_parry::ToIntersectionResult;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use std::path::PathBuf;
+
+#[test]
+fn test_cascade_matches_convex_decomposition() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let chain = path.to_chain(ResourcesType::Robot);
+
+    for _ in 0..50 {
+        let state_a = chain.sample_random_state();
+        let state_b = chain.sample_random_state();
+
+        let cascade = chain.double_chain_intersect_cascade_from_states(&chain, &state_a, &state_b, false);
+        let decomposition = chain.double_chain_intersect_from_states(&chain, &state_a, LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull, &state_b, LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull, false);
+        assert_eq!(cascade.to_intersection_result(), decomposition.to_intersection_result());
+
+        let cascade = chain.self_intersect_cascade_from_state(&state_a, true);
+        assert!(cascade.outputs.len() <= 1);
+    }
+}
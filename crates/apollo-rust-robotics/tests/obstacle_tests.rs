@@ -0,0 +1,41 @@
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_proximity_parry::ToIntersectionResult;
+use apollo_rust_proximity_parry::offset_shape::OffsetShape;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V6;
+use parry3d_f64::shape::{Ball, Cuboid};
+use parry3d_f64::na::Vector3;
+use std::path::PathBuf;
+
+#[test]
+fn test_ur5_against_code_defined_obstacles() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let chain = path.to_chain(ResourcesType::Robot);
+    let state = V::new(&[0.0; 6]);
+
+    let obstacles = vec![
+        OffsetShape::new(Ball::new(0.2), None),
+        OffsetShape::new(Cuboid::new(Vector3::new(0.1, 0.1, 0.1)), None),
+    ];
+    let poses = vec![
+        ISE3q::identity(),
+        ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 5.0, 0.0, 0.0)),
+    ];
+
+    let res = chain.obstacles_intersect_from_state(&state, LinkShapeMode::Full, LinkShapeRep::ConvexHull, &obstacles, &poses, false);
+    assert!(res.to_intersection_result());
+    assert!(res.outputs.iter().zip(res.shape_idxs.iter()).all(|(x, (_, j))| !*x || *j == 0));
+
+    let far = vec![obstacles[1].clone()];
+    let far_poses = vec![poses[1].clone()];
+    let res = chain.obstacles_distance_from_state(&state, LinkShapeMode::Full, LinkShapeRep::ConvexHull, &far, &far_poses, false);
+    assert!(res.outputs.iter().all(|x| *x > 3.0));
+
+    let res = chain.obstacles_contact_from_state(&state, LinkShapeMode::Decomposition, LinkShapeRep::ConvexHull, &far, &far_poses, false, 10.0);
+    assert!(res.outputs.iter().all(|x| x.as_ref().expect("error").dist > 3.0));
+}
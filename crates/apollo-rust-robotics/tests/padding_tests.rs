@@ -0,0 +1,93 @@
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_proximity_parry::ToIntersectionResult;
+use apollo_rust_proximity_parry::bvh::BvhShapeAABB;
+use apollo_rust_proximity_parry::offset_shape::OffsetShape;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V6;
+use parry3d_f64::shape::Cuboid;
+use parry3d_f64::na::Vector3;
+use std::path::PathBuf;
+
+#[test]
+fn test_ur5_link_paddings() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let mut chain = path.to_chain(ResourcesType::Robot);
+    let state = V::new(&[0.0, -1.0, 1.0, 0.0, 0.5, 0.0]);
+    let mode = LinkShapeMode::Full;
+    let rep = LinkShapeRep::ConvexHull;
+
+    let obstacles = vec![OffsetShape::new(Cuboid::new(Vector3::new(0.1, 0.1, 0.1)), None)];
+    let poses = vec![ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 5.0, 0.0, 0.0))];
+
+    let self_before = chain.self_distance_from_state(&state, mode, rep, false);
+    let obstacles_before = chain.obstacles_distance_from_state(&state, mode, rep, &obstacles, &poses, false);
+    assert!(!chain.obstacles_intersect_from_state(&state, mode, rep, &obstacles, &poses, false).to_intersection_result());
+
+    let num_links = chain.link_shapes_padding_nalgebra_module().link_paddings.len();
+    for link_idx in 0..num_links { chain.set_link_padding(link_idx, 0.1); }
+
+    let self_after = chain.self_distance_from_state(&state, mode, rep, false);
+    assert_eq!(self_before.shape_idxs, self_after.shape_idxs);
+    self_before.outputs.iter().zip(self_after.outputs.iter()).for_each(|(a, b)| assert!((a - b - 0.2).abs() < 1e-9));
+
+    let obstacles_after = chain.obstacles_distance_from_state(&state, mode, rep, &obstacles, &poses, false);
+    obstacles_before.outputs.iter().zip(obstacles_after.outputs.iter()).for_each(|(a, b)| assert!((a - b - 0.1).abs() < 1e-9));
+
+    // a pair override replaces the sum of the link paddings.
+    let (i, j) = self_after.shape_idxs[0];
+    let link_i = chain.link_shapes_module().get_link_idx_and_subcomponent_idx_from_shape_idx(i, &mode).0;
+    let link_j = chain.link_shapes_module().get_link_idx_and_subcomponent_idx_from_shape_idx(j, &mode).0;
+    chain.set_link_pair_padding(link_i, link_j, Some(0.0));
+    let self_override = chain.self_distance_from_state(&state, mode, rep, false);
+    assert!((self_override.outputs[0] - self_before.outputs[0]).abs() < 1e-9);
+
+    for link_idx in 0..num_links { chain.set_link_padding(link_idx, 10.0); }
+    assert!(chain.obstacles_intersect_from_state(&state, mode, rep, &obstacles, &poses, false).to_intersection_result());
+}
+
+#[test]
+fn test_ur5_paddings_in_bvh_and_attached_objects() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let mut chain = path.to_chain(ResourcesType::Robot);
+    let state = V::new(&[0.0, -1.0, 1.0, 0.0, 0.5, 0.0]);
+    let mode = LinkShapeMode::Full;
+    let rep = LinkShapeRep::ConvexHull;
+    let link_poses = chain.fk(&state);
+    let mut bvh = chain.get_bvh::<BvhShapeAABB>(&state, mode, rep, 2);
+
+    let num_links = chain.link_shapes_padding_nalgebra_module().link_paddings.len();
+    for link_idx in 0..num_links { chain.set_link_padding(link_idx, 0.1); }
+
+    // the bvh broadphase is loosened by the padding, so it reports the same colliding pairs.
+    let full = chain.self_intersect(&link_poses, mode, rep, false);
+    let res = chain.self_intersect_bvh(&mut bvh, &link_poses, mode, rep, false);
+    assert!(full.to_intersection_result());
+    full.shape_idxs.iter().zip(full.outputs.iter()).filter(|(_, y)| **y).for_each(|(x, _)| {
+        assert!(res.shape_idxs.iter().zip(res.outputs.iter()).any(|(a, b)| a == x && *b));
+    });
+
+    // an attached object takes the padding of its link.
+    let ee_link_idx = chain.urdf_module().links.iter().position(|x| x.name == "ee_link").expect("error");
+    let relative_pose = ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 0.3, 0.0, 0.0));
+    chain.attach_object("part", ee_link_idx, OffsetShape::new(Cuboid::new(Vector3::new(0.05, 0.05, 0.05)), None), relative_pose, vec![]);
+    let num_shapes = chain.link_shapes_module().get_shapes(mode, rep).len();
+
+    let obstacles = vec![OffsetShape::new(Cuboid::new(Vector3::new(0.1, 0.1, 0.1)), None)];
+    let poses = vec![ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 5.0, 0.0, 0.0))];
+    let object_distance = |chain: &apollo_rust_robotics_core::ChainNalgebra| {
+        let res = chain.obstacles_distance(&link_poses, mode, rep, &obstacles, &poses, false);
+        let idx = res.shape_idxs.iter().position(|x| *x == (num_shapes, 0)).expect("error");
+        res.outputs[idx]
+    };
+
+    let padded = object_distance(&chain);
+    chain.set_link_padding(ee_link_idx, 0.0);
+    assert!((object_distance(&chain) - padded - 0.1).abs() < 1e-9);
+}
@@ -0,0 +1,76 @@
+use apollo_rust_lie::LieGroupElement;
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_robotics::sensors::{LidarScanPattern, PinholeCameraModel, SensorMount, SimulatedDepthCamera, SimulatedLidar};
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use apollo_rust_spatial::lie::se3_implicit_quaternion::ISE3q;
+use apollo_rust_spatial::vectors::V6;
+use std::f64::consts::PI;
+use std::path::PathBuf;
+
+/// Camera 2m in front of the UR5 along +x, optical axis pointing back at the robot.
+fn camera_pose() -> ISE3q {
+    let translation = ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 2.0, 0.0, 0.5));
+    let rotation = ISE3q::from_exponential_coordinates(&V6::new(0.0, -PI / 2.0, 0.0, 0.0, 0.0, 0.0));
+    translation.group_operator(&rotation)
+}
+
+#[test]
+fn test_ur5_depth_camera() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let chain = path.to_chain(ResourcesType::Robot);
+    let chains = vec![&chain];
+    let states = vec![V::new(&[0.0; 6])];
+
+    let model = PinholeCameraModel::new_from_horizontal_fov(32, 24, PI / 3.0, 0.01, 5.0);
+    let camera = SimulatedDepthCamera::new(SensorMount::World { pose: camera_pose() }, model.clone());
+    let image = camera.render(&chains, &states, LinkShapeMode::Full, LinkShapeRep::ConvexHull);
+
+    assert_eq!(image.depths.len(), 32 * 24);
+    let num_hits = image.depths.iter().filter(|x| x.is_finite()).count();
+    assert!(num_hits > 0);
+    assert!(num_hits < 32 * 24);
+    for (depth, label) in image.depths.iter().zip(image.labels.iter()) {
+        assert_eq!(depth.is_finite(), label.is_some());
+        if depth.is_finite() { assert!(*depth > 1.5 && *depth < 2.5); }
+    }
+    assert_eq!(image.to_point_cloud_in_camera_frame(&model).len(), num_hits);
+
+    // looking away from the robot sees nothing.
+    let away = camera_pose().group_operator(&ISE3q::from_exponential_coordinates(&V6::new(0.0, PI, 0.0, 0.0, 0.0, 0.0)));
+    let camera = SimulatedDepthCamera::new(SensorMount::World { pose: away }, model);
+    let image = camera.render(&chains, &states, LinkShapeMode::Full, LinkShapeRep::ConvexHull);
+    assert!(image.depths.iter().all(|x| x.is_infinite()));
+}
+
+#[test]
+fn test_ur5_lidar() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let chain = path.to_chain(ResourcesType::Robot);
+    let chains = vec![&chain];
+    let states = vec![V::new(&[0.0; 6])];
+
+    let mount = SensorMount::World { pose: ISE3q::from_exponential_coordinates(&V6::new(0.0, 0.0, 0.0, 2.0, 0.0, 0.5)) };
+    let pattern = LidarScanPattern::new_spinning(360, 8, -0.2, 0.2, 0.05, 10.0);
+    let lidar = SimulatedLidar::new(mount, pattern.clone());
+    let scan = lidar.scan(&chains, &states, LinkShapeMode::Full, LinkShapeRep::ConvexHull);
+
+    assert_eq!(scan.returns.len(), 360 * 8);
+    let points = scan.point_cloud();
+    assert!(points.len() > 0);
+
+    let origin = scan.sensor_pose.0.translation.vector;
+    for (hit, direction) in scan.returns.iter().zip(pattern.directions().iter()) {
+        if let Some(hit) = hit {
+            assert_eq!(hit.chain_idx, 0);
+            assert!(hit.range > 1.5 && hit.range <= 10.0);
+            assert!((origin + direction * hit.range - hit.point).norm() < 1e-6);
+            // the robot is behind the sensor along -x.
+            assert!(direction[0] < 0.0);
+        }
+    }
+}
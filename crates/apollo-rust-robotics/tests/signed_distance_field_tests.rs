@@ -0,0 +1,58 @@
+use apollo_rust_modules::robot_modules::signed_distance_field_module::{ApolloSignedDistanceFieldModule, ApolloSignedDistanceFieldParameters};
+use apollo_rust_robotics_core::modules_runtime::signed_distance_field_nalgebra_module::ApolloSignedDistanceFieldNalgebraModule;
+use apollo_rust_spatial::vectors::V3;
+
+/// Field of a ball of radius 0.5 at the origin, sampled on [-1, 1]^3.
+fn ball_field() -> ApolloSignedDistanceFieldNalgebraModule {
+    let voxel_size = 0.05;
+    let n = 41;
+    let origin = [-1.0, -1.0, -1.0];
+
+    let mut distances = vec![];
+    let mut gradients = vec![];
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                let p = V3::new(origin[0] + i as f64 * voxel_size, origin[1] + j as f64 * voxel_size, origin[2] + k as f64 * voxel_size);
+                distances.push(p.norm() - 0.5);
+                let g = if p.norm() > 0.0 { p / p.norm() } else { V3::zeros() };
+                gradients.push([g[0], g[1], g[2]]);
+            }
+        }
+    }
+
+    let module = ApolloSignedDistanceFieldModule { parameters: ApolloSignedDistanceFieldParameters::new(voxel_size, n * n * n), origin, voxel_size, dims: [n, n, n], distances, gradients };
+    ApolloSignedDistanceFieldNalgebraModule::from_signed_distance_field_module(&module)
+}
+
+#[test]
+fn test_signed_distance_field_interpolation() {
+    let field = ball_field();
+
+    let (d, g) = field.distance_and_gradient(&V3::new(0.8, 0.0, 0.0));
+    assert!((d - 0.3).abs() < 1e-9);
+    assert!((g - V3::new(1.0, 0.0, 0.0)).norm() < 1e-9);
+
+    let (d, _) = field.distance_and_gradient(&V3::new(0.0, 0.0, 0.0));
+    assert!((d + 0.5).abs() < 1e-9);
+
+    let p = V3::new(0.31, -0.42, 0.17);
+    let (d, g) = field.distance_and_gradient(&p);
+    assert!((d - (p.norm() - 0.5)).abs() < 1e-2);
+    assert!((g - p / p.norm()).norm() < 0.1);
+
+    let (d, _) = field.sphere_distance_and_gradient(&V3::new(0.0, 0.9, 0.0), 0.1);
+    assert!((d - 0.3).abs() < 1e-9);
+}
+
+#[test]
+fn test_signed_distance_field_outside_grid() {
+    let field = ball_field();
+
+    // continuous across the grid boundary and increasing away from the grid.
+    let (d_inside, _) = field.distance_and_gradient(&V3::new(1.0, 0.0, 0.0));
+    let (d_outside, g_outside) = field.distance_and_gradient(&V3::new(1.5, 0.0, 0.0));
+    assert!((d_inside - 0.5).abs() < 1e-9);
+    assert!((d_outside - 1.0).abs() < 1e-9);
+    assert!((g_outside - V3::new(1.0, 0.0, 0.0)).norm() < 1e-9);
+}
@@ -0,0 +1,60 @@
+use apollo_rust_linalg::{ApolloDVectorTrait, V};
+use apollo_rust_preprocessor::robot_modules_preprocessor::modules::link_shapes_modules::link_shapes_skips_module::{classify_link_shape_pairs, sample_link_poses, LinkShapePairClass};
+use apollo_rust_preprocessor::utils::progress_bar::ProgressBarWrapper;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use apollo_rust_robotics_core::robot_functions::robot_proximity_functions::RobotProximityFunctions;
+use std::path::PathBuf;
+
+#[test]
+fn test_ur5_link_shape_pair_classification() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let chain = path.to_chain(ResourcesType::Robot);
+    let mode = LinkShapeMode::Full;
+    let rep = LinkShapeRep::ConvexHull;
+    let mut progress_bar = ProgressBarWrapper::new("ur5", "link_shapes_skips_module");
+    progress_bar.set_max_increment(2000);
+
+    let samples = sample_link_poses(chain.urdf_module(), chain.chain_module(), chain.dof_module(), chain.bounds_module(), 1000, 0);
+    let classes = classify_link_shape_pairs(chain.link_shapes_module(), chain.urdf_module(), chain.chain_module(), chain.dof_module(), chain.bounds_module(), mode, rep, &samples, 0.95, &mut progress_bar);
+    let n = classes.len();
+    let shape_idx = |name: &str| {
+        let link_idx = chain.urdf_module().links.iter().position(|x| x.name == name).expect("error");
+        chain.link_shapes_module().link_idx_to_full_shape_idx[link_idx].expect("error")
+    };
+
+    for i in 0..n {
+        assert_eq!(classes[i][i], LinkShapePairClass::SameLink);
+        for j in 0..n { assert_eq!(classes[i][j], classes[j][i]); }
+    }
+
+    // consecutive links are adjacent, and links two joints apart are not.
+    let links = ["base_link", "shoulder_link", "upper_arm_link", "forearm_link", "wrist_1_link", "wrist_2_link", "wrist_3_link"];
+    for x in links.windows(2) {
+        assert_eq!(classes[shape_idx(x[0])][shape_idx(x[1])], LinkShapePairClass::Adjacent);
+    }
+    for x in links.windows(3) {
+        assert_ne!(classes[shape_idx(x[0])][shape_idx(x[2])], LinkShapePairClass::Adjacent);
+    }
+
+    // exactly the non-adjacent pairs in collision at the default state are default colliding.
+    let res = RobotProximityFunctions::self_intersect(chain.link_shapes_module(), &chain.fk(&V::new(&[0.0; 6])), mode, rep, None, None, false);
+    res.outputs.iter().zip(res.shape_idxs.iter()).for_each(|(intersect, (i, j))| {
+        match classes[*i][*j] {
+            LinkShapePairClass::SameLink | LinkShapePairClass::Adjacent => {}
+            c => { assert_eq!(c == LinkShapePairClass::DefaultColliding, *intersect); }
+        }
+    });
+
+    // the ur5 can fold onto itself, so some pairs have to be checked, and others never touch.
+    assert!(classes.iter().flatten().any(|x| *x == LinkShapePairClass::Check));
+    assert!(classes.iter().flatten().any(|x| *x == LinkShapePairClass::NeverColliding));
+    assert!(classes.iter().flatten().all(|x| *x != LinkShapePairClass::AlwaysColliding));
+
+    // with a tiny ratio, every pair that ever collides is always colliding.
+    let classes = classify_link_shape_pairs(chain.link_shapes_module(), chain.urdf_module(), chain.chain_module(), chain.dof_module(), chain.bounds_module(), mode, rep, &samples, 1e-9, &mut progress_bar);
+    assert!(classes.iter().flatten().all(|x| *x != LinkShapePairClass::Check));
+    assert!(classes.iter().flatten().any(|x| *x == LinkShapePairClass::AlwaysColliding));
+}
@@ -0,0 +1,63 @@
+use apollo_rust_modules::robot_modules::link_shapes_modules::link_shapes_skips_module::ApolloLinkShapesSkipsModule;
+use apollo_rust_modules::robot_modules::named_groups_module::ApolloNamedGroupsModule;
+use apollo_rust_modules::robot_modules::urdf_module::ApolloURDFModule;
+use apollo_rust_preprocessor::robot_modules_preprocessor::modules::link_shapes_modules::link_shapes_skips_module::{classify_link_shape_pairs, sample_link_poses, LinkShapePairClass};
+use apollo_rust_preprocessor::srdf::ApolloSRDF;
+use apollo_rust_preprocessor::utils::progress_bar::ProgressBarWrapper;
+use apollo_rust_preprocessor::PreprocessorModule;
+use apollo_rust_robotics::{ResourcesType, ToChainFromPath};
+use apollo_rust_robotics_core::modules_runtime::link_shapes_module::{LinkShapeMode, LinkShapeRep};
+use std::path::PathBuf;
+
+#[test]
+fn test_ur5_srdf_round_trip() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../../ur5_urdd");
+
+    let chain = path.to_chain(ResourcesType::Robot);
+    let s = chain.resources_sub_directory();
+    let urdf_module = ApolloURDFModule::load_or_build(s, false).expect("error");
+    let named_groups_module = ApolloNamedGroupsModule::load_or_build(s, false).expect("error");
+    let link_shapes_skips_module = ApolloLinkShapesSkipsModule::load_or_build(s, false).expect("error");
+    let link_shapes_module = chain.link_shapes_module();
+
+    let mut progress_bar = ProgressBarWrapper::new("ur5", "link_shapes_skips_module");
+    progress_bar.set_max_increment(500);
+    let samples = sample_link_poses(chain.urdf_module(), chain.chain_module(), chain.dof_module(), chain.bounds_module(), 500, 0);
+    let classes = classify_link_shape_pairs(link_shapes_module, chain.urdf_module(), chain.chain_module(), chain.dof_module(), chain.bounds_module(), LinkShapeMode::Full, LinkShapeRep::ConvexHull, &samples, 0.95, &mut progress_bar);
+
+    let srdf = ApolloSRDF::from_modules(&urdf_module, &named_groups_module, link_shapes_module, &link_shapes_skips_module, &classes);
+    assert!(srdf.disabled_collisions.iter().any(|x| x.reason == "Adjacent"));
+    assert!(srdf.is_collision_disabled("shoulder_link", "upper_arm_link"));
+
+    let parsed = ApolloSRDF::from_srdf_string(&srdf.to_srdf_string()).expect("error");
+    assert_eq!(parsed.robot_name, srdf.robot_name);
+    assert_eq!(parsed.named_groups_module.groups.len(), named_groups_module.groups.len());
+    assert_eq!(parsed.named_groups_module.named_configurations.len(), named_groups_module.named_configurations.len());
+    assert_eq!(parsed.disabled_collisions.len(), srdf.disabled_collisions.len());
+    parsed.disabled_collisions.iter().zip(srdf.disabled_collisions.iter()).for_each(|(a, b)| {
+        assert_eq!((&a.link1, &a.link2, &a.reason), (&b.link1, &b.link2, &b.reason));
+    });
+
+    // every classified skip and no checked pair comes back as a skip, in every representation.
+    let imported = parsed.to_link_shapes_skips_module(&urdf_module, link_shapes_module);
+    for skips in [&imported.full_convex_hulls_skips, &imported.full_obbs_skips, &imported.full_capsules_skips] {
+        for i in 0..classes.len() {
+            for j in 0..classes.len() {
+                let expected = classes[i][j] != LinkShapePairClass::Check || link_shapes_skips_module.full_convex_hulls_skips[i][j];
+                assert_eq!(skips[i][j], expected);
+            }
+        }
+    }
+
+    // decomposition shapes take the skips of their links.
+    let n = imported.decomposition_convex_hulls_skips.len();
+    for i in 0..n {
+        let (link_i, _) = link_shapes_module.decomposition_shape_idx_to_link_idx_and_link_sub_idx[i];
+        for j in 0..n {
+            let (link_j, _) = link_shapes_module.decomposition_shape_idx_to_link_idx_and_link_sub_idx[j];
+            let expected = link_i == link_j || parsed.is_collision_disabled(&urdf_module.links[link_i].name, &urdf_module.links[link_j].name);
+            assert_eq!(imported.decomposition_convex_hulls_skips[i][j], expected);
+        }
+    }
+}
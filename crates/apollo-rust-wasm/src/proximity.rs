@@ -53,6 +53,7 @@ impl WASMProximity {
             LinkShapeMode::Decomposition, // Using decomposition for more accurate collision
             LinkShapeRep::ConvexHull,     // Convex Hull is standard
             None,                         // No skips for now (or we could expose loading skips)
+            None,                         // No paddings
             true,                         // early stop
         );
 